@@ -305,6 +305,13 @@ static E2E_LITERAL_REDACTIONS: &[(&str, &str)] = &[
     ("[UPLOADING]", "   Uploading"),
     ("[UPLOADED]", "    Uploaded"),
     ("[VERIFYING]", "   Verifying"),
+    ("[VERIFIED]", "    Verified"),
+    ("[UNIFYING]", "    Unifying"),
+    ("[STUBBING]", "    Stubbing"),
+    ("[PATCHING]", "    Patching"),
+    ("[SAVED]", "       Saved"),
+    ("[MIRRORED]", "    Mirrored"),
+    ("[REPACKAGING]", " Repackaging"),
     ("[ARCHIVING]", "   Archiving"),
     ("[INSTALLING]", "  Installing"),
     ("[REPLACING]", "   Replacing"),
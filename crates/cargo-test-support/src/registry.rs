@@ -574,6 +574,7 @@ pub struct Package {
     proc_macro: bool,
     links: Option<String>,
     rust_version: Option<String>,
+    pubtime: Option<String>,
     cargo_features: Vec<String>,
     v: Option<u32>,
 }
@@ -1227,6 +1228,7 @@ fn save_new_crate(
         new_crate.links,
         new_crate.rust_version.as_deref(),
         None,
+        None,
     );
 
     write_to_index(registry_path, &new_crate.name, line, false);
@@ -1253,6 +1255,7 @@ impl Package {
             proc_macro: false,
             links: None,
             rust_version: None,
+            pubtime: None,
             cargo_features: Vec::new(),
             v: None,
         }
@@ -1404,6 +1407,12 @@ impl Package {
         self
     }
 
+    /// Sets the publish time recorded in the index, in RFC 3339 format.
+    pub fn pubtime(&mut self, pubtime: &str) -> &mut Package {
+        self.pubtime = Some(pubtime.into());
+        self
+    }
+
     /// Causes the JSON line emitted in the index to be invalid, presumably
     /// causing Cargo to skip over this version.
     pub fn invalid_json(&mut self, invalid: bool) -> &mut Package {
@@ -1494,6 +1503,7 @@ impl Package {
             self.yanked,
             self.links.clone(),
             self.rust_version.as_deref(),
+            self.pubtime.as_deref(),
             self.v,
         );
 
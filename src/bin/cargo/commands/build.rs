@@ -1,6 +1,8 @@
 use crate::command_prelude::*;
 
+use cargo::core::compiler::build_plan::executor::{self, Plan};
 use cargo::ops;
+use cargo_util::paths;
 
 pub fn cli() -> Command {
     subcommand("build")
@@ -36,7 +38,9 @@ pub fn cli() -> Command {
         .arg_target_dir()
         .arg_artifact_dir()
         .arg_build_plan()
+        .arg_replay_build_plan()
         .arg_unit_graph()
+        .arg_explain_rebuild()
         .arg_timings()
         .arg_manifest_path()
         .arg_lockfile_path()
@@ -47,6 +51,14 @@ pub fn cli() -> Command {
 }
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    if let Some(plan) = args.value_of_path("replay-build-plan", gctx) {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--replay-build-plan", 5579)?;
+        let plan = Plan::from_json(&paths::read(&plan)?)?;
+        executor::execute(gctx, &plan)?;
+        return Ok(());
+    }
+
     let ws = args.workspace(gctx)?;
     let mut compile_opts =
         args.compile_options(gctx, CompileMode::Build, Some(&ws), ProfileChecking::Custom)?;
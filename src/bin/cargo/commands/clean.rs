@@ -84,6 +84,15 @@ pub fn cli() -> Command {
                     .value_name("DURATION")
                     .value_parser(parse_time_span),
                 )
+                .arg(
+                    opt(
+                        "max-shared-deps-age",
+                        "Deletes units from the shared build directory that have \
+                        not been used since the given age (unstable)",
+                    )
+                    .value_name("DURATION")
+                    .value_parser(parse_time_span),
+                )
                 .arg(
                     opt(
                         "max-src-size",
@@ -177,13 +186,14 @@ fn gc(gctx: &GlobalContext, args: &ArgMatches) -> CliResult {
         max_crate_size: size_opt("max-crate-size"),
         max_git_size: size_opt("max-git-size"),
         max_download_size: size_opt("max-download-size"),
+        max_shared_deps_age: duration_opt("max-shared-deps-age"),
     };
     if let Some(age) = duration_opt("max-download-age") {
         gc_opts.set_max_download_age(age);
     }
     // If the user sets any options, then only perform the options requested.
     // If no options are set, do the default behavior.
-    if !gc_opts.is_download_cache_opt_set() {
+    if !gc_opts.is_download_cache_opt_set() && gc_opts.max_shared_deps_age.is_none() {
         gc_opts.update_for_auto_gc(gctx)?;
     }
 
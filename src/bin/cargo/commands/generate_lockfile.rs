@@ -6,6 +6,7 @@ pub fn cli() -> Command {
     subcommand("generate-lockfile")
         .about("Generate the lockfile for a package")
         .arg_silent_suggestion()
+        .arg_lockfile_message_format()
         .arg_manifest_path()
         .arg_lockfile_path()
         .arg_ignore_rust_version_with_help(
@@ -25,7 +26,8 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
             gctx.cli_unstable().msrv_policy,
         )?;
     }
+    let message_format = args.lockfile_message_format(gctx)?;
     let ws = args.workspace(gctx)?;
-    ops::generate_lockfile(&ws)?;
+    ops::generate_lockfile(&ws, message_format)?;
     Ok(())
 }
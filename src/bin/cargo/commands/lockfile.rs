@@ -0,0 +1,130 @@
+use crate::command_prelude::*;
+use cargo::ops::cargo_lockfile::{self, MergeOptions};
+use std::path::PathBuf;
+
+pub fn cli() -> Command {
+    subcommand("lockfile")
+        .about("Compare, merge, verify and unify Cargo.lock files (unstable)")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            subcommand("diff")
+                .about("Display the package changes between two lockfiles")
+                .arg(
+                    Arg::new("old")
+                        .help("The lockfile to compare from")
+                        .value_name("OLD")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("new")
+                        .help("The lockfile to compare to [default: the workspace lockfile]")
+                        .value_name("NEW")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg_silent_suggestion()
+                .arg_manifest_path()
+                .arg_lockfile_path(),
+        )
+        .subcommand(
+            subcommand("merge")
+                .about("Merge the changes of two lockfiles, as a git merge driver")
+                .arg(
+                    Arg::new("base")
+                        .help("The lockfile both sides started from")
+                        .value_name("BASE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("ours")
+                        .help("Our lockfile")
+                        .value_name("OURS")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("theirs")
+                        .help("Their lockfile")
+                        .value_name("THEIRS")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    opt(
+                        "output",
+                        "Where to write the merged lockfile [default: OURS]",
+                    )
+                    .short('o')
+                    .value_name("PATH")
+                    .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg_silent_suggestion()
+                .arg_manifest_path(),
+        )
+        .subcommand(
+            subcommand("unify")
+                .about(
+                    "Resolve the workspaces of the `super-workspace.members` config together, \
+                     and update their lockfiles",
+                )
+                .arg(flag(
+                    "check",
+                    "Only check that the lockfiles agree with the joint resolve",
+                ))
+                .arg_silent_suggestion(),
+        )
+        .subcommand(
+            subcommand("verify")
+                .about("Check the entries of the workspace lockfile against their sources")
+                .arg_silent_suggestion()
+                .arg_manifest_path()
+                .arg_lockfile_path(),
+        )
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command_untracked(
+        gctx,
+        "lockfile",
+        "cargo-lockfile",
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    match args.subcommand() {
+        Some(("diff", args)) => {
+            let ws = args.workspace(gctx)?;
+            let old = args.get_one::<PathBuf>("old").unwrap();
+            let new = args.get_one::<PathBuf>("new");
+            cargo_lockfile::diff(&ws, old, new.map(PathBuf::as_path))?;
+        }
+        Some(("merge", args)) => {
+            // The merged lockfile only depends on the two sides.
+            gctx.set_offline(true);
+            let ws = args.workspace(gctx)?;
+            let ours = args.get_one::<PathBuf>("ours").unwrap();
+            let opts = MergeOptions {
+                base: args.get_one::<PathBuf>("base").unwrap(),
+                ours,
+                theirs: args.get_one::<PathBuf>("theirs").unwrap(),
+                output: args.get_one::<PathBuf>("output").unwrap_or(ours),
+            };
+            cargo_lockfile::merge(&ws, &opts)?;
+        }
+        Some(("unify", args)) => {
+            cargo_lockfile::unify(gctx, args.flag("check"))?;
+        }
+        Some(("verify", args)) => {
+            let ws = args.workspace(gctx)?;
+            cargo_lockfile::verify(&ws)?;
+        }
+        Some((cmd, _)) => {
+            unreachable!("unexpected command {}", cmd)
+        }
+        None => {
+            unreachable!("unexpected command")
+        }
+    }
+    Ok(())
+}
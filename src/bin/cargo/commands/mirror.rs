@@ -0,0 +1,65 @@
+use crate::command_prelude::*;
+use cargo::ops;
+use std::path::PathBuf;
+
+pub fn cli() -> Command {
+    subcommand("mirror")
+        .about("Build a registry mirror of the packages of lockfiles (unstable)")
+        .arg(
+            Arg::new("path")
+                .action(ArgAction::Set)
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Where to write the mirror (`mirror` by default)"),
+        )
+        .arg(
+            multi_opt(
+                "lockfile",
+                "PATH",
+                "Lockfile listing packages to mirror [default: the workspace lockfile]",
+            )
+            .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(opt("url", "URL the mirror will be served from").value_name("URL"))
+        .arg_silent_suggestion()
+        .arg_manifest_path()
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command_untracked(
+        gctx,
+        "mirror",
+        "cargo-mirror",
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+
+    let mut lockfiles: Vec<PathBuf> = args
+        .get_many::<PathBuf>("lockfile")
+        .unwrap_or_default()
+        .cloned()
+        .collect();
+    if lockfiles.is_empty() {
+        let ws = args.workspace(gctx)?;
+        let lockfile = ws.lock_root().as_path_unlocked().join("Cargo.lock");
+        if !lockfile.exists() {
+            return Err(anyhow::format_err!(
+                "the workspace has no lockfile, run `cargo generate-lockfile` first"
+            )
+            .into());
+        }
+        lockfiles.push(lockfile);
+    }
+    let path = args
+        .get_one::<PathBuf>("path")
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("mirror"));
+    ops::mirror(
+        gctx,
+        &ops::MirrorOptions {
+            lockfiles: &lockfiles,
+            destination: &path,
+            url: args.get_one::<String>("url").map(String::as_str),
+        },
+    )?;
+    Ok(())
+}
@@ -18,9 +18,11 @@ pub fn builtin() -> Vec<Command> {
         init::cli(),
         install::cli(),
         locate_project::cli(),
+        lockfile::cli(),
         login::cli(),
         logout::cli(),
         metadata::cli(),
+        mirror::cli(),
         new::cli(),
         owner::cli(),
         package::cli(),
@@ -64,9 +66,11 @@ pub fn builtin_exec(cmd: &str) -> Option<Exec> {
         "init" => init::exec,
         "install" => install::exec,
         "locate-project" => locate_project::exec,
+        "lockfile" => lockfile::exec,
         "login" => login::exec,
         "logout" => logout::exec,
         "metadata" => metadata::exec,
+        "mirror" => mirror::exec,
         "new" => new::exec,
         "owner" => owner::exec,
         "package" => package::exec,
@@ -108,9 +112,11 @@ pub mod info;
 pub mod init;
 pub mod install;
 pub mod locate_project;
+pub mod lockfile;
 pub mod login;
 pub mod logout;
 pub mod metadata;
+pub mod mirror;
 pub mod new;
 pub mod owner;
 pub mod package;
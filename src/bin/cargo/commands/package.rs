@@ -18,6 +18,10 @@ pub fn cli() -> Command {
             "no-verify",
             "Don't verify the contents by building them",
         ))
+        .arg(flag(
+            "verify-reproducible",
+            "Check that packaging again gives the same tarball (unstable)",
+        ))
         .arg(flag(
             "no-metadata",
             "Ignore warnings about a lack of human-usable metadata",
@@ -60,6 +64,12 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
             gctx.cli_unstable().package_workspace,
         )?;
     }
+    if args.flag("verify-reproducible") {
+        gctx.cli_unstable().fail_if_stable_opt_untracked(
+            "--verify-reproducible",
+            "cargo-package---verify-reproducible",
+        )?;
+    }
     let reg_or_index = args.registry_or_index(gctx)?;
     let ws = args.workspace(gctx)?;
     if ws.root_maybe().is_embedded() {
@@ -76,6 +86,7 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         &PackageOpts {
             gctx,
             verify: !args.flag("no-verify"),
+            verify_reproducible: args.flag("verify-reproducible"),
             list: args.flag("list"),
             check_metadata: !args.flag("no-metadata"),
             allow_dirty: args.flag("allow-dirty"),
@@ -17,6 +17,10 @@ pub fn cli() -> Command {
             "allow-dirty",
             "Allow dirty working directories to be packaged",
         ))
+        .arg(flag(
+            "semver-check",
+            "Refuse to publish breaking changes under a compatible version (unstable)",
+        ))
         .arg_silent_suggestion()
         .arg_package("Package to publish")
         .arg_features()
@@ -31,6 +35,10 @@ pub fn cli() -> Command {
 }
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    if args.flag("semver-check") {
+        gctx.cli_unstable()
+            .fail_if_stable_opt_untracked("--semver-check", "cargo-publish---semver-check")?;
+    }
     let reg_or_index = args.registry_or_index(gctx)?;
     let ws = args.workspace(gctx)?;
     if ws.root_maybe().is_embedded() {
@@ -57,6 +65,7 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
             keep_going: args.keep_going(),
             dry_run: args.dry_run(),
             cli_features: args.cli_features()?,
+            semver_check: args.flag("semver-check"),
         },
     )?;
     Ok(())
@@ -1,6 +1,7 @@
 use crate::command_prelude::*;
 use cargo::core::compiler::future_incompat::{OnDiskReports, REPORT_PREAMBLE};
 use cargo::drop_println;
+use cargo::ops::cargo_report::timings::{report_timings, ReportTimingsOptions};
 
 pub fn cli() -> Command {
     subcommand("report")
@@ -23,11 +24,37 @@ pub fn cli() -> Command {
                 )
                 .arg_package("Package to display a report for"),
         )
+        .subcommand(
+            subcommand("timings")
+                .about("Reports build timings saved by `--timings=json-file` (unstable)")
+                .arg(flag("list", "List the saved timing reports"))
+                .arg(
+                    opt(
+                        "id",
+                        "Timestamp id or path of the timing report to display [default: latest]",
+                    )
+                    .value_name("id"),
+                )
+                .arg(
+                    opt(
+                        "compare",
+                        "Timestamp id or path of a timing report to compare against",
+                    )
+                    .value_name("id"),
+                )
+                .arg(
+                    opt("top", "Number of units to display [default: 10]")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg_manifest_path(),
+        )
 }
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     match args.subcommand() {
         Some(("future-incompatibilities", args)) => report_future_incompatibilities(gctx, args),
+        Some(("timings", args)) => report_timings_cmd(gctx, args),
         Some((cmd, _)) => {
             unreachable!("unexpected command {}", cmd)
         }
@@ -49,3 +76,22 @@ fn report_future_incompatibilities(gctx: &GlobalContext, args: &ArgMatches) -> C
     drop(gctx.shell().print_ansi_stdout(report.as_bytes()));
     Ok(())
 }
+
+fn report_timings_cmd(gctx: &GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "report timings",
+        7405,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let ws = args.workspace(gctx)?;
+    let opts = ReportTimingsOptions {
+        list: args.flag("list"),
+        id: args.get_one::<String>("id").map(String::as_str),
+        compare: args.get_one::<String>("compare").map(String::as_str),
+        top: args.get_one::<usize>("top").copied().unwrap_or(10),
+    };
+    report_timings(&ws, &opts)?;
+    Ok(())
+}
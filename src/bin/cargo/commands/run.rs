@@ -41,6 +41,7 @@ pub fn cli() -> Command {
         .arg_lockfile_path()
         .arg_ignore_rust_version()
         .arg_unit_graph()
+        .arg_explain_rebuild()
         .arg_timings()
         .after_help(color_print::cstr!(
             "Run `<cyan,bold>cargo help run</>` for more detailed information.\n"
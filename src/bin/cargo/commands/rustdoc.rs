@@ -43,6 +43,7 @@ pub fn cli() -> Command {
                 .value_parser(OutputFormat::POSSIBLE_VALUES),
         )
         .arg_unit_graph()
+        .arg_explain_rebuild()
         .arg_timings()
         .arg_manifest_path()
         .arg_lockfile_path()
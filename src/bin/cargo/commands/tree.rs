@@ -66,6 +66,14 @@ pub fn cli() -> Command {
             .short('d')
             .alias("duplicate"),
         )
+        .arg(
+            opt(
+                "why-version",
+                "Explain why the given package was resolved to its versions (unstable)",
+            )
+            .value_name("SPEC")
+            .conflicts_with_all(["invert", "duplicates"]),
+        )
         .arg(
             opt("charset", "Character set to use in output")
                 .value_name("CHARSET")
@@ -224,8 +232,14 @@ subtree of the package given to -p.\n\
         graph_features,
         max_display_depth: args.value_of_u32("depth")?.unwrap_or(u32::MAX),
         no_proc_macro,
+        why_version: args.get_one::<String>("why-version").cloned(),
     };
 
+    if opts.why_version.is_some() {
+        gctx.cli_unstable()
+            .fail_if_stable_opt_untracked("--why-version", "tree-why-version")?;
+    }
+
     if opts.graph_features && opts.duplicates {
         return Err(format_err!("the `-e features` flag does not support `--duplicates`").into());
     }
@@ -46,6 +46,7 @@ pub fn cli() -> Command {
             .short('b'),
         )
         .arg_silent_suggestion()
+        .arg_lockfile_message_format()
         .arg(
             flag("workspace", "Only update the workspace packages")
                 .short('w')
@@ -99,6 +100,7 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         to_update,
         dry_run: args.dry_run(),
         workspace: args.flag("workspace"),
+        message_format: args.lockfile_message_format(gctx)?,
         gctx,
     };
 
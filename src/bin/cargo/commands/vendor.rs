@@ -32,10 +32,29 @@ pub fn cli() -> Command {
             "versioned-dirs",
             "Always include version in subdir name",
         ))
+        .arg(flag(
+            "no-dev-deps",
+            "Vendor packages only needed for dev-dependencies as stubs (unstable)",
+        ))
+        .arg(flag(
+            "only-git-deps",
+            "Only vendor git dependencies (unstable)",
+        ))
+        .arg(flag(
+            "disallow-duplicates",
+            "Fail if several versions of a package would be vendored (unstable)",
+        ))
+        .arg(flag(
+            "incremental",
+            "Only copy the packages which changed since they were vendored (unstable)",
+        ))
+        .arg(flag(
+            "save-patches",
+            "Save local changes to vendored packages as patches (unstable)",
+        ))
         .arg(unsupported("no-merge-sources"))
         .arg(unsupported("relative-path"))
-        .arg(unsupported("only-git-deps"))
-        .arg(unsupported("disallow-duplicates"))
+        .arg_target_triple("Vendor packages not needed for the target triple as stubs (unstable)")
         .arg_manifest_path()
         .arg_lockfile_path()
         .after_help(color_print::cstr!(
@@ -64,6 +83,29 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         gctx.values_mut()?.remove("source");
     }
 
+    let targets = args.targets()?;
+    if !targets.is_empty() {
+        gctx.cli_unstable()
+            .fail_if_stable_opt_untracked("--target", "vendor-filtering")?;
+    }
+    for flag in ["no-dev-deps", "only-git-deps", "disallow-duplicates"] {
+        if args.flag(flag) {
+            gctx.cli_unstable()
+                .fail_if_stable_opt_untracked(&format!("--{flag}"), "vendor-filtering")?;
+        }
+    }
+
+    for flag in ["incremental", "save-patches"] {
+        if args.flag(flag) {
+            gctx.cli_unstable().fail_if_stable_opt_untracked_custom_z(
+                &format!("--{flag}"),
+                "vendor-manifest",
+                "vendor-manifest",
+                gctx.cli_unstable().vendor_manifest,
+            )?;
+        }
+    }
+
     let ws = args.workspace(gctx)?;
     let path = args
         .get_one::<PathBuf>("path")
@@ -80,6 +122,13 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
                 .unwrap_or_default()
                 .cloned()
                 .collect(),
+            targets,
+            no_dev_deps: args.flag("no-dev-deps"),
+            only_git_deps: args.flag("only-git-deps"),
+            disallow_duplicates: args.flag("disallow-duplicates"),
+            manifest: gctx.cli_unstable().vendor_manifest,
+            incremental: args.flag("incremental"),
+            save_patches: args.flag("save-patches"),
         },
     )?;
     Ok(())
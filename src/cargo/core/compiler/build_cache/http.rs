@@ -0,0 +1,84 @@
+//! A build cache on an HTTP server.
+
+use std::sync::Mutex;
+
+use anyhow::bail;
+use curl::easy::{Easy, List};
+
+use super::BuildCacheBackend;
+use crate::util::errors::CargoResult;
+use crate::util::network::http::http_handle;
+use crate::util::GlobalContext;
+
+/// Fetches entries with `GET {url}/{key}` and stores them with
+/// `PUT {url}/{key}`.
+///
+/// A `404` response to a `GET` request means there is no such entry, any
+/// other response besides `200` is an error.
+pub struct HttpBackend {
+    url: String,
+    handle: Mutex<Easy>,
+}
+
+impl HttpBackend {
+    pub fn new(gctx: &GlobalContext, url: String) -> CargoResult<HttpBackend> {
+        let url = url.trim_end_matches('/').to_string();
+        let handle = Mutex::new(http_handle(gctx)?);
+        Ok(HttpBackend { url, handle })
+    }
+}
+
+impl BuildCacheBackend for HttpBackend {
+    fn get(&self, key: &str) -> CargoResult<Option<Vec<u8>>> {
+        let url = format!("{}/{key}", self.url);
+        let mut handle = self.handle.lock().unwrap();
+        // The handle is reused, so undo any options set by `put`.
+        handle.get(true)?;
+        handle.http_headers(List::new())?;
+        handle.url(&url)?;
+        let mut body = Vec::new();
+        {
+            let mut transfer = handle.transfer();
+            transfer.write_function(|buf| {
+                body.extend_from_slice(buf);
+                Ok(buf.len())
+            })?;
+            transfer.perform()?;
+        }
+        match handle.response_code()? {
+            200 => Ok(Some(body)),
+            404 => Ok(None),
+            code => bail!("failed to get `{url}`, got status {code}"),
+        }
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> CargoResult<()> {
+        let url = format!("{}/{key}", self.url);
+        let mut handle = self.handle.lock().unwrap();
+        handle.put(true)?;
+        handle.url(&url)?;
+        handle.in_filesize(data.len() as u64)?;
+        let mut headers = List::new();
+        headers.append("Content-Type: application/x-tar")?;
+        // Send the body right away rather than waiting for the server to
+        // acknowledge it.
+        headers.append("Expect:")?;
+        handle.http_headers(headers)?;
+        let mut data = data;
+        {
+            let mut transfer = handle.transfer();
+            transfer.read_function(|buf| {
+                let n = buf.len().min(data.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                data = &data[n..];
+                Ok(n)
+            })?;
+            transfer.write_function(|buf| Ok(buf.len()))?;
+            transfer.perform()?;
+        }
+        match handle.response_code()? {
+            200 | 201 | 204 => Ok(()),
+            code => bail!("failed to put `{url}`, got status {code}"),
+        }
+    }
+}
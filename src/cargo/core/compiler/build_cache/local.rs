@@ -0,0 +1,49 @@
+//! A build cache in a local directory.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use cargo_util::paths;
+
+use super::BuildCacheBackend;
+use crate::util::errors::CargoResult;
+
+/// Stores each entry as a file in a directory, which may be shared by several
+/// workspaces and users.
+pub struct LocalBackend {
+    root: PathBuf,
+}
+
+impl LocalBackend {
+    pub fn new(root: PathBuf) -> LocalBackend {
+        LocalBackend { root }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        // Spread entries over subdirectories to keep directories small.
+        let prefix = &key[..key.len().min(2)];
+        self.root.join(prefix).join(format!("{key}.tar"))
+    }
+}
+
+impl BuildCacheBackend for LocalBackend {
+    fn get(&self, key: &str) -> CargoResult<Option<Vec<u8>>> {
+        let path = self.entry_path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        paths::read_bytes(&path).map(Some)
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> CargoResult<()> {
+        let path = self.entry_path(key);
+        let dir = path.parent().unwrap();
+        paths::create_dir_all(dir)?;
+        // Write to a temporary file first so that concurrent builds never
+        // see a partial entry.
+        let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+        tmp.write_all(data)?;
+        tmp.persist(&path)?;
+        Ok(())
+    }
+}
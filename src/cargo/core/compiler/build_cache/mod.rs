@@ -0,0 +1,221 @@
+//! A cache of compiled units shared between builds, for `-Zbuild-cache`.
+//!
+//! Building the same version of a dependency with the same flags produces the
+//! same artifacts, no matter which workspace it is built for. When enabled,
+//! Cargo looks up the outputs of each cacheable unit in the cache before
+//! running rustc, and stores them there after a successful build.
+//!
+//! The cache is configured with the `[build-cache]` table:
+//!
+//! ```toml
+//! [build-cache]
+//! path = "/path/to/a/directory"  # a local directory, or
+//! url = "http://example.com/cache"  # a server accepting GET and PUT
+//! ```
+//!
+//! Only units whose outputs are fully determined by their fingerprint are
+//! cached, see [`BuildCache::is_cacheable`]. An entry is keyed by the package
+//! name, the [`Metadata`] of the unit, which is also part of its file names,
+//! and the hash of its [`Fingerprint`]. An entry is a tar archive of the
+//! outputs of rustc, along with the dep-info file Cargo keeps in the
+//! fingerprint directory, stored by file name.
+//!
+//! Failing to read or write the cache is never fatal, a warning is shown and
+//! the unit is built as usual.
+//!
+//! [`Metadata`]: super::Metadata
+//! [`Fingerprint`]: super::fingerprint::Fingerprint
+
+mod http;
+mod local;
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{bail, Context as _};
+use cargo_util::paths;
+use serde::Deserialize;
+
+use super::job_queue::Work;
+use super::{BuildRunner, CompileMode, CrateType, Unit};
+use crate::core::compiler::fingerprint;
+use crate::util::context::ConfigRelativePath;
+use crate::util::errors::CargoResult;
+use crate::util::GlobalContext;
+
+/// Storage for the entries of a [`BuildCache`].
+pub trait BuildCacheBackend: Send + Sync {
+    /// Returns the entry stored under `key`, if any.
+    fn get(&self, key: &str) -> CargoResult<Option<Vec<u8>>>;
+    /// Stores `data` under `key`, replacing any previous entry.
+    fn put(&self, key: &str, data: &[u8]) -> CargoResult<()>;
+}
+
+/// The `[build-cache]` configuration table.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct BuildCacheConfig {
+    path: Option<ConfigRelativePath>,
+    url: Option<String>,
+}
+
+/// A cache of compiled units, see the [module-level documentation](self).
+pub struct BuildCache {
+    backend: Arc<dyn BuildCacheBackend>,
+    /// Memoized results of [`BuildCache::is_cacheable`].
+    cacheable: HashMap<Unit, bool>,
+}
+
+impl BuildCache {
+    /// Creates the build cache from the `[build-cache]` configuration.
+    ///
+    /// Returns `None` if `-Zbuild-cache` isn't enabled or no cache is
+    /// configured.
+    pub fn from_gctx(gctx: &GlobalContext) -> CargoResult<Option<BuildCache>> {
+        if !gctx.cli_unstable().build_cache {
+            return Ok(None);
+        }
+        let Some(config) = gctx.get::<Option<BuildCacheConfig>>("build-cache")? else {
+            return Ok(None);
+        };
+        let backend: Arc<dyn BuildCacheBackend> = match (config.path, config.url) {
+            (Some(_), Some(_)) => {
+                bail!("only one of `build-cache.path` and `build-cache.url` may be specified")
+            }
+            (Some(path), None) => Arc::new(local::LocalBackend::new(path.resolve_path(gctx))),
+            (None, Some(url)) => {
+                if gctx.offline() {
+                    gctx.shell()
+                        .warn("build cache at `build-cache.url` is not used when offline")?;
+                    return Ok(None);
+                }
+                Arc::new(http::HttpBackend::new(gctx, url)?)
+            }
+            (None, None) => return Ok(None),
+        };
+        Ok(Some(BuildCache {
+            backend,
+            cacheable: HashMap::new(),
+        }))
+    }
+
+    /// Whether the outputs of `unit` can be shared through the cache.
+    ///
+    /// This is the case for libraries from registry and git sources which
+    /// don't have a build script, and whose dependencies are all cacheable
+    /// as well. Anything else may depend on inputs the fingerprint doesn't
+    /// fully describe, like the contents of local files or the output of a
+    /// build script.
+    pub fn is_cacheable(&mut self, build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> bool {
+        if let Some(&cacheable) = self.cacheable.get(unit) {
+            return cacheable;
+        }
+        let cacheable = !unit.pkg.package_id().source_id().is_path()
+            && matches!(
+                unit.mode,
+                CompileMode::Build | CompileMode::Check { test: false }
+            )
+            && !unit.artifact.is_true()
+            && unit.target.is_lib()
+            && unit.target.rustc_crate_types().iter().all(|kind| {
+                matches!(
+                    kind,
+                    CrateType::Lib | CrateType::Rlib | CrateType::ProcMacro
+                )
+            })
+            && build_runner
+                .unit_deps(unit)
+                .iter()
+                .all(|dep| self.is_cacheable(build_runner, &dep.unit));
+        self.cacheable.insert(unit.clone(), cacheable);
+        cacheable
+    }
+
+    /// Wraps the `work` compiling `unit`, restoring its outputs from the cache
+    /// instead if possible, and storing them otherwise.
+    ///
+    /// The fingerprint of `unit` must have been computed already.
+    pub fn wrap(
+        &mut self,
+        build_runner: &mut BuildRunner<'_, '_>,
+        unit: &Unit,
+        work: Work,
+    ) -> CargoResult<Work> {
+        if !self.is_cacheable(build_runner, unit) {
+            return Ok(work);
+        }
+        let key = format!(
+            "{}-{}-{:016x}",
+            unit.pkg.name(),
+            build_runner.files().metadata(unit),
+            build_runner.fingerprints[unit].hash_u64()
+        );
+        let mut files: Vec<_> = build_runner
+            .outputs(unit)?
+            .iter()
+            .map(|output| output.path.clone())
+            .collect();
+        files.push(fingerprint::dep_info_loc(build_runner, unit));
+        let backend = Arc::clone(&self.backend);
+        let name = unit.pkg.name();
+
+        Ok(Work::new(move |state| {
+            match restore(&*backend, &key, &files) {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(e) => state.warning(format!(
+                    "failed to restore `{name}` from the build cache: {e:#}"
+                ))?,
+            }
+            work.call(state)?;
+            if let Err(e) = store(&*backend, &key, &files) {
+                state.warning(format!(
+                    "failed to store `{name}` in the build cache: {e:#}"
+                ))?;
+            }
+            Ok(())
+        }))
+    }
+}
+
+/// Extracts the entry `key` to `files`, matching them by file name.
+///
+/// Returns `false` if there is no such entry.
+fn restore(backend: &dyn BuildCacheBackend, key: &str, files: &[PathBuf]) -> CargoResult<bool> {
+    let Some(data) = backend.get(key)? else {
+        return Ok(false);
+    };
+    let by_name: HashMap<_, _> = files
+        .iter()
+        .filter_map(|path| Some((path.file_name()?, path)))
+        .collect();
+    let mut archive = tar::Archive::new(&data[..]);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.into_owned();
+        let Some(dst) = entry_path.file_name().and_then(|name| by_name.get(name)) else {
+            bail!("unexpected file `{}` in cache entry", entry_path.display());
+        };
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        if let Some(parent) = dst.parent() {
+            paths::create_dir_all(parent)?;
+        }
+        paths::write(dst, contents)?;
+    }
+    Ok(true)
+}
+
+/// Stores the existing `files` as the entry `key`.
+fn store(backend: &dyn BuildCacheBackend, key: &str, files: &[PathBuf]) -> CargoResult<()> {
+    let mut archive = tar::Builder::new(Vec::new());
+    for path in files.iter().filter(|path| path.exists()) {
+        let name = path.file_name().unwrap();
+        let mut file = std::fs::File::open(path)
+            .with_context(|| format!("failed to open `{}`", path.display()))?;
+        archive.append_file(Path::new(name), &mut file)?;
+    }
+    backend.put(key, &archive.into_inner()?)
+}
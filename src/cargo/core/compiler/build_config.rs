@@ -1,4 +1,5 @@
 use crate::core::compiler::CompileKind;
+use crate::core::gc::parse_human_size;
 use crate::util::context::JobsConfig;
 use crate::util::interning::InternedString;
 use crate::util::{CargoResult, GlobalContext, RustfixDiagnosticServer};
@@ -6,6 +7,7 @@ use anyhow::{bail, Context as _};
 use cargo_util::ProcessBuilder;
 use serde::ser;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::thread::available_parallelism;
@@ -48,6 +50,17 @@ pub struct BuildConfig {
     pub future_incompat_report: bool,
     /// Which kinds of build timings to output (empty if none).
     pub timing_outputs: Vec<TimingOutput>,
+    /// Explain why each unit is rebuilt, tracing changed dependencies back
+    /// to the original change.
+    pub explain_rebuild: bool,
+    /// Memory budget in bytes for the jobs running at once, from
+    /// `build.memory-limit`.
+    pub memory_limit: Option<u64>,
+    /// Maximum number of jobs linking at once, from `build.link-jobs`.
+    pub link_jobs: Option<u32>,
+    /// Expected memory use in bytes of the units of packages, by package
+    /// name, from `build.unit-memory`.
+    pub unit_memory: HashMap<String, u64>,
 }
 
 fn default_parallelism() -> CargoResult<u32> {
@@ -61,6 +74,7 @@ impl BuildConfig {
     /// configured options are:
     ///
     /// * `build.jobs`
+    /// * `build.memory-limit`, `build.link-jobs` and `build.unit-memory`
     /// * `build.target`
     /// * `target.$target.ar`
     /// * `target.$target.linker`
@@ -99,6 +113,33 @@ impl BuildConfig {
             },
         };
 
+        let mut memory_limit = None;
+        let mut link_jobs = None;
+        let mut unit_memory = HashMap::new();
+        if gctx.cli_unstable().memory_limit {
+            memory_limit = cfg
+                .memory_limit
+                .as_deref()
+                .map(parse_human_size)
+                .transpose()
+                .context("failed to parse `build.memory-limit`")?;
+            link_jobs = cfg.link_jobs;
+            if link_jobs == Some(0) {
+                bail!("`build.link-jobs` may not be 0");
+            }
+            for (name, size) in cfg.unit_memory.iter().flatten() {
+                let size = parse_human_size(size)
+                    .with_context(|| format!("failed to parse `build.unit-memory.{name}`"))?;
+                unit_memory.insert(name.clone(), size);
+            }
+        } else if cfg.memory_limit.is_some() || cfg.link_jobs.is_some() || cfg.unit_memory.is_some()
+        {
+            gctx.shell().warn(
+                "ignoring `build.memory-limit`, `build.link-jobs` and `build.unit-memory` \
+                 config without `-Zmemory-limit`",
+            )?;
+        }
+
         if gctx.cli_unstable().build_std.is_some() && requested_kinds[0].is_host() {
             // TODO: This should eventually be fixed.
             anyhow::bail!("-Zbuild-std requires --target");
@@ -120,6 +161,10 @@ impl BuildConfig {
             export_dir: None,
             future_incompat_report: false,
             timing_outputs: Vec::new(),
+            explain_rebuild: false,
+            memory_limit,
+            link_jobs,
+            unit_memory,
         })
     }
 
@@ -275,4 +320,8 @@ pub enum TimingOutput {
     Html,
     /// Machine-readable JSON (unstable)
     Json,
+    /// Machine-readable JSON file with the full timing data (unstable)
+    JsonFile,
+    /// Chrome trace-event file, loadable in Perfetto and similar viewers (unstable)
+    Trace,
 }
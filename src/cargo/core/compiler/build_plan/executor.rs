@@ -0,0 +1,250 @@
+//! A small executor replaying a build plan, see `cargo build --replay-build-plan`.
+//!
+//! This runs the invocations of a plan produced by `--build-plan` one at a
+//! time, in order, applying the output of build scripts the same way Cargo
+//! does. It is meant as a reference for external build systems and to verify
+//! that a plan is complete. It does no fingerprinting, no parallelism and no
+//! pipelining.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use anyhow::{bail, Context as _};
+use cargo_util::{paths, ProcessBuilder};
+use serde::Deserialize;
+
+use super::{BuildScriptRun, BuildScriptUse, BUILD_PLAN_VERSION};
+use crate::core::compiler::{envify, BuildOutput, LinkArgTarget};
+use crate::core::{Edition, Target};
+use crate::util::{CargoResult, GlobalContext};
+
+/// A build plan, as read back from the output of `--build-plan`.
+#[derive(Deserialize)]
+pub struct Plan {
+    pub version: u32,
+    pub invocations: Vec<PlanInvocation>,
+    pub inputs: Vec<PathBuf>,
+}
+
+/// A single invocation of a [`Plan`].
+#[derive(Deserialize)]
+pub struct PlanInvocation {
+    pub package_name: String,
+    pub package_version: String,
+    pub target_kind: Vec<String>,
+    pub target_name: String,
+    pub compile_mode: String,
+    pub deps: Vec<usize>,
+    pub outputs: Vec<PathBuf>,
+    pub links: BTreeMap<PathBuf, PathBuf>,
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub build_script: Option<BuildScriptRun>,
+    #[serde(default)]
+    pub build_script_uses: Vec<BuildScriptUse>,
+}
+
+impl Plan {
+    pub fn from_json(json: &str) -> CargoResult<Plan> {
+        let plan: Plan = serde_json::from_str(json).context("failed to parse build plan")?;
+        if plan.version != BUILD_PLAN_VERSION {
+            bail!(
+                "unsupported build plan version {} (expected {})",
+                plan.version,
+                BUILD_PLAN_VERSION
+            );
+        }
+        Ok(plan)
+    }
+}
+
+impl PlanInvocation {
+    fn has_kind(&self, kind: &str) -> bool {
+        self.target_kind.iter().any(|k| k == kind)
+    }
+
+    /// Same as [`LinkArgTarget::applies_to`], based on the target
+    /// information in the plan.
+    fn link_arg_applies(&self, lt: &LinkArgTarget) -> bool {
+        match lt {
+            LinkArgTarget::All => true,
+            LinkArgTarget::Cdylib => self.has_kind("cdylib"),
+            LinkArgTarget::Bin => self.has_kind("bin"),
+            LinkArgTarget::SingleBin(name) => self.has_kind("bin") && self.target_name == *name,
+            LinkArgTarget::Test => self.has_kind("test"),
+            LinkArgTarget::Bench => self.has_kind("bench"),
+            LinkArgTarget::Example => self.has_kind("example"),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} v{} ({} `{}`)",
+            self.package_name, self.package_version, self.compile_mode, self.target_name
+        )
+    }
+}
+
+/// Runs all invocations of `plan` in order.
+pub fn execute(gctx: &GlobalContext, plan: &Plan) -> CargoResult<()> {
+    let mut script_outputs = HashMap::new();
+    for (i, invocation) in plan.invocations.iter().enumerate() {
+        if let Some(dep) = invocation.deps.iter().find(|&&dep| dep >= i) {
+            bail!(
+                "invocation {i} depends on invocation {dep}, \
+                 which is not ordered before it"
+            );
+        }
+        // Doc tests and overridden build scripts have nothing to run.
+        if invocation.program.is_empty() {
+            continue;
+        }
+        execute_one(gctx, plan, i, &mut script_outputs)
+            .with_context(|| format!("failed to run invocation {i}: {}", invocation.describe()))?;
+    }
+    Ok(())
+}
+
+/// Recreates the targets of a package running a build script, with just
+/// enough information to check the instructions of the script.
+fn script_targets(script: &BuildScriptRun) -> Vec<Target> {
+    let edition = Edition::default();
+    script
+        .targets
+        .iter()
+        .filter_map(|t| {
+            // The checks don't look at the source of targets, so any absolute
+            // path does.
+            let path = script.out_dir.clone();
+            match t.kind.as_str() {
+                "bin" => Some(Target::bin_target(&t.name, None, path, None, edition)),
+                "example" => Some(Target::example_target(
+                    &t.name,
+                    Vec::new(),
+                    path,
+                    None,
+                    edition,
+                )),
+                "integration-test" => Some(Target::test_target(&t.name, path, None, edition)),
+                "bench" => Some(Target::bench_target(&t.name, path, None, edition)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Runs the invocation at index `i`, recording the output of build scripts in
+/// `script_outputs`.
+fn execute_one(
+    gctx: &GlobalContext,
+    plan: &Plan,
+    i: usize,
+    script_outputs: &mut HashMap<usize, BuildOutput>,
+) -> CargoResult<()> {
+    let invocation = &plan.invocations[i];
+    let mut cmd = ProcessBuilder::new(&invocation.program);
+    cmd.args(&invocation.args);
+    for (key, value) in &invocation.env {
+        cmd.env(key, value);
+    }
+    if let Some(cwd) = &invocation.cwd {
+        cmd.cwd(cwd);
+    }
+    for output in invocation.outputs.iter().chain(invocation.links.keys()) {
+        if let Some(parent) = output.parent() {
+            paths::create_dir_all(parent)?;
+        }
+    }
+
+    for script_use in &invocation.build_script_uses {
+        let Some(output) = script_outputs.get(&script_use.invocation) else {
+            bail!(
+                "build script invocation {} has not been run",
+                script_use.invocation
+            );
+        };
+        if script_use.link_search {
+            for path in &output.library_paths {
+                cmd.arg("-L").arg(path);
+            }
+            for (lt, arg) in &output.linker_args {
+                if invocation.link_arg_applies(lt)
+                    && (script_use.link_args || *lt == LinkArgTarget::Cdylib)
+                {
+                    cmd.arg("-C").arg(format!("link-arg={arg}"));
+                }
+            }
+        }
+        if script_use.link_lib {
+            for name in &output.library_links {
+                cmd.arg("-l").arg(name);
+            }
+        }
+        if script_use.flags {
+            for cfg in &output.cfgs {
+                cmd.arg("--cfg").arg(cfg);
+            }
+            for check_cfg in &output.check_cfgs {
+                cmd.arg("--check-cfg").arg(check_cfg);
+            }
+            for (name, value) in &output.env {
+                cmd.env(name, value);
+            }
+        }
+        if script_use.metadata {
+            let links = plan.invocations[script_use.invocation]
+                .build_script
+                .as_ref()
+                .and_then(|script| script.links.as_deref());
+            if let Some(links) = links {
+                for (key, value) in &output.metadata {
+                    cmd.env(&format!("DEP_{}_{}", envify(links), envify(key)), value);
+                }
+            }
+        }
+    }
+
+    gctx.shell().verbose(|s| s.status("Running", &cmd))?;
+    match &invocation.build_script {
+        Some(script) => {
+            paths::create_dir_all(&script.out_dir)?;
+            let output = cmd.exec_with_output()?;
+            if let Some(parent) = script.output_file.parent() {
+                paths::create_dir_all(parent)?;
+            }
+            paths::write(&script.output_file, &output.stdout)?;
+            let parsed = BuildOutput::parse(
+                &output.stdout,
+                None,
+                &invocation.package_name,
+                &script.out_dir,
+                &script.out_dir,
+                gctx.nightly_features_allowed,
+                &script_targets(script),
+                &None,
+            )?;
+            script_outputs.insert(i, parsed);
+        }
+        None => {
+            // The plan asks rustc for JSON diagnostics, which are only
+            // useful to show when the invocation fails.
+            cmd.exec_with_output()?;
+            gctx.shell().status("Built", invocation.describe())?;
+        }
+    }
+
+    for (link, src) in &invocation.links {
+        // Some outputs, like split debuginfo, are optional.
+        if !src.exists() {
+            continue;
+        }
+        if link.exists() {
+            paths::remove_file(link)?;
+        }
+        paths::link_or_copy(src, link)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,388 @@
+//! A graph-like structure used to represent the rustc commands to build the package and the
+//! interdependencies between them.
+//!
+//! The BuildPlan structure is used to store the dependency graph of a dry run so that it can be
+//! shared with an external build system. Each Invocation in the BuildPlan comprises a single
+//! subprocess and defines the build environment, the outputs produced by the subprocess, and the
+//! dependencies on other Invocations.
+//!
+//! Running a build script is an invocation of its own. What a build script
+//! prints can only be known by running it, so the plan describes where the
+//! script writes its output ([`BuildScriptRun`]) and which other invocations
+//! need to apply its `cargo::rustc-*` directives ([`BuildScriptUse`]). The
+//! [`executor`] module contains a small executor replaying a plan, which
+//! serves as a reference for external build systems.
+
+pub mod executor;
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::build_runner::OutputFile;
+use super::custom_build::BuildOutput;
+use super::{BuildRunner, CompileKind, CompileMode, Metadata, Unit};
+use crate::core::TargetKind;
+use crate::util::{internal, CargoResult, GlobalContext};
+use cargo_util::ProcessBuilder;
+
+/// The current version of the build plan format.
+///
+/// This is bumped whenever an incompatible change is made to the format.
+pub const BUILD_PLAN_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+struct Invocation {
+    package_name: String,
+    package_version: semver::Version,
+    target_kind: TargetKind,
+    target_name: String,
+    kind: CompileKind,
+    compile_mode: CompileMode,
+    deps: Vec<usize>,
+    outputs: Vec<PathBuf>,
+    links: BTreeMap<PathBuf, PathBuf>,
+    program: String,
+    args: Vec<String>,
+    env: BTreeMap<String, String>,
+    cwd: Option<PathBuf>,
+    /// Where rustc writes the dep-info of this invocation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dep_info: Option<PathBuf>,
+    /// Files this invocation reads, from the dep-info of a previous build if
+    /// available, otherwise just the root source file.
+    inputs: Vec<PathBuf>,
+    /// Environment variables this invocation depends on, from the dep-info
+    /// of a previous build.
+    input_env: Vec<String>,
+    /// Set if this invocation runs a build script.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build_script: Option<BuildScriptRun>,
+    /// Build scripts whose output affects this invocation.
+    build_script_uses: Vec<BuildScriptUse>,
+}
+
+/// Describes the dynamic outputs of running a build script.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildScriptRun {
+    /// The `OUT_DIR` of the build script.
+    pub out_dir: PathBuf,
+    /// The file the build script's stdout is saved to, containing the
+    /// `cargo::` directives.
+    pub output_file: PathBuf,
+    /// The `links` value of the package, used to name the `DEP_<links>_<key>`
+    /// variables of dependent build scripts.
+    pub links: Option<String>,
+    /// The targets of the package, which instructions like
+    /// `cargo::rustc-link-arg-bins` are checked against.
+    pub targets: Vec<BuildScriptTarget>,
+}
+
+/// A target of a package running a build script.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildScriptTarget {
+    /// The kind of target, as described by [`TargetKind::description`].
+    pub kind: String,
+    pub name: String,
+}
+
+/// Describes how the output of a build script is applied to an invocation.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildScriptUse {
+    /// The invocation running the build script.
+    pub invocation: usize,
+    /// Pass `cargo::rustc-link-search` as `-L` flags.
+    pub link_search: bool,
+    /// Pass `cargo::rustc-link-lib` as `-l` flags.
+    pub link_lib: bool,
+    /// Pass `cargo::rustc-link-arg*` as `-C link-arg` flags, if they apply
+    /// to the target. If `false`, only `cargo::rustc-cdylib-link-arg` is
+    /// passed, for compatibility.
+    pub link_args: bool,
+    /// Pass `cargo::rustc-cfg` and `cargo::rustc-check-cfg` as flags, and set
+    /// `cargo::rustc-env` variables.
+    pub flags: bool,
+    /// Set `DEP_<links>_<key>` variables for `cargo::KEY=VALUE` metadata.
+    pub metadata: bool,
+}
+
+#[derive(Debug)]
+pub struct BuildPlan {
+    invocation_map: BTreeMap<String, usize>,
+    /// Invocations running a build script, by the metadata of their unit.
+    build_script_map: HashMap<Metadata, usize>,
+    plan: SerializedBuildPlan,
+}
+
+#[derive(Debug, Serialize)]
+struct SerializedBuildPlan {
+    version: u32,
+    invocations: Vec<Invocation>,
+    inputs: Vec<PathBuf>,
+}
+
+impl Invocation {
+    pub fn new(unit: &Unit, deps: Vec<usize>) -> Invocation {
+        let id = unit.pkg.package_id();
+        Invocation {
+            package_name: id.name().to_string(),
+            package_version: id.version().clone(),
+            kind: unit.kind,
+            target_kind: unit.target.kind().clone(),
+            target_name: unit.target.name().to_string(),
+            compile_mode: unit.mode,
+            deps,
+            outputs: Vec::new(),
+            links: BTreeMap::new(),
+            program: String::new(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            cwd: None,
+            dep_info: None,
+            inputs: Vec::new(),
+            input_env: Vec::new(),
+            build_script: None,
+            build_script_uses: Vec::new(),
+        }
+    }
+
+    pub fn add_output(&mut self, path: &Path, link: &Option<PathBuf>) {
+        self.outputs.push(path.to_path_buf());
+        if let Some(ref link) = *link {
+            self.links.insert(link.clone(), path.to_path_buf());
+        }
+    }
+
+    pub fn update_cmd(&mut self, cmd: &ProcessBuilder) -> CargoResult<()> {
+        self.program = cmd
+            .get_program()
+            .to_str()
+            .ok_or_else(|| anyhow::format_err!("unicode program string required"))?
+            .to_string();
+        self.cwd = Some(cmd.get_cwd().unwrap().to_path_buf());
+        for arg in cmd.get_args() {
+            self.args.push(
+                arg.to_str()
+                    .ok_or_else(|| anyhow::format_err!("unicode argument string required"))?
+                    .to_string(),
+            );
+        }
+        for (var, value) in cmd.get_envs() {
+            let Some(value) = value else { continue };
+            self.env.insert(
+                var.clone(),
+                value
+                    .to_str()
+                    .ok_or_else(|| anyhow::format_err!("unicode environment value required"))?
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl BuildPlan {
+    pub fn new() -> BuildPlan {
+        BuildPlan {
+            invocation_map: BTreeMap::new(),
+            build_script_map: HashMap::new(),
+            plan: SerializedBuildPlan::new(),
+        }
+    }
+
+    pub fn add(&mut self, build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<()> {
+        let id = self.plan.invocations.len();
+        self.invocation_map.insert(unit.buildkey(), id);
+        let deps = build_runner
+            .unit_deps(unit)
+            .iter()
+            .map(|dep| self.invocation_map[&dep.unit.buildkey()])
+            .collect();
+        let mut invocation = Invocation::new(unit, deps);
+        if unit.mode.is_run_custom_build() {
+            self.add_build_script_run(build_runner, unit, id, &mut invocation)?;
+        } else if !unit.mode.is_doc_test() {
+            self.add_compile(build_runner, unit, &mut invocation)?;
+        }
+        self.plan.invocations.push(invocation);
+        Ok(())
+    }
+
+    /// Fills in the details of an invocation running a build script.
+    fn add_build_script_run(
+        &mut self,
+        build_runner: &BuildRunner<'_, '_>,
+        unit: &Unit,
+        id: usize,
+        invocation: &mut Invocation,
+    ) -> CargoResult<()> {
+        let metadata = build_runner.get_run_build_script_metadata(unit);
+        self.build_script_map.insert(metadata, id);
+
+        let files = build_runner.files();
+        let out_dir = files.build_script_out_dir(unit);
+        let output_file = files.build_script_run_dir(unit).join("output");
+        // Report what the script depended on the last time it ran.
+        if output_file.exists() {
+            let gctx = build_runner.bcx.gctx;
+            let output = BuildOutput::parse_file(
+                &output_file,
+                unit.pkg.library().map(|t| t.crate_name()),
+                &unit.pkg.to_string(),
+                &out_dir,
+                &out_dir,
+                gctx.nightly_features_allowed,
+                unit.pkg.targets(),
+                &unit.pkg.rust_version().cloned(),
+            )?;
+            invocation.inputs = output
+                .rerun_if_changed
+                .iter()
+                .map(|path| unit.pkg.root().join(path))
+                .collect();
+            invocation.input_env = output.rerun_if_env_changed;
+        }
+        invocation.build_script = Some(BuildScriptRun {
+            out_dir,
+            output_file,
+            links: unit.pkg.manifest().links().map(str::to_string),
+            targets: unit
+                .pkg
+                .targets()
+                .iter()
+                .map(|t| BuildScriptTarget {
+                    kind: t.kind().description().to_string(),
+                    name: t.name().to_string(),
+                })
+                .collect(),
+        });
+
+        // Build scripts of dependencies with `links` pass their metadata.
+        for dep in build_runner.unit_deps(unit) {
+            if !dep.unit.mode.is_run_custom_build() {
+                continue;
+            }
+            let dep_metadata = build_runner.get_run_build_script_metadata(&dep.unit);
+            invocation.build_script_uses.push(BuildScriptUse {
+                invocation: self.build_script_invocation(dep_metadata)?,
+                link_search: false,
+                link_lib: false,
+                link_args: false,
+                flags: false,
+                metadata: true,
+            });
+        }
+        Ok(())
+    }
+
+    /// Fills in the details of an invocation running rustc or rustdoc.
+    fn add_compile(
+        &mut self,
+        build_runner: &BuildRunner<'_, '_>,
+        unit: &Unit,
+        invocation: &mut Invocation,
+    ) -> CargoResult<()> {
+        if !unit.mode.is_doc() && !unit.mode.is_doc_scrape() {
+            invocation.dep_info = Some(super::rustc_dep_info_loc(build_runner, unit));
+        }
+        // Cargo's own copy of the dep-info from a previous build, if any.
+        let dep_info_loc = build_runner.files().fingerprint_file_path(unit, "dep-");
+        let target_root = build_runner.bcx.ws.target_dir().into_path_unlocked();
+        match super::fingerprint::parse_dep_info(unit.pkg.root(), &target_root, &dep_info_loc)? {
+            Some(dep_info) => {
+                invocation.inputs = dep_info.files;
+                invocation.input_env = dep_info.env.into_iter().map(|(name, _)| name).collect();
+            }
+            None => {
+                if let Some(path) = unit.target.src_path().path() {
+                    invocation.inputs.push(path.to_path_buf());
+                }
+            }
+        }
+
+        let own_script = build_runner.find_build_script_metadata(unit);
+        let Some(build_scripts) = build_runner.build_scripts.get(unit) else {
+            return Ok(());
+        };
+        // If we are a binary and the package also contains a library, then we
+        // don't pass the `-l` flags.
+        let pass_l_flag = unit.target.is_lib() || !unit.pkg.targets().iter().any(|t| t.is_lib());
+        let current_id = unit.pkg.package_id();
+        for (pkg_id, metadata) in &build_scripts.to_link {
+            let Some(&script) = self.build_script_map.get(metadata) else {
+                // Overridden build scripts are not run.
+                continue;
+            };
+            let is_own = *pkg_id == current_id;
+            invocation.build_script_uses.push(BuildScriptUse {
+                invocation: script,
+                link_search: true,
+                link_lib: is_own && pass_l_flag,
+                link_args: is_own,
+                flags: Some(*metadata) == own_script,
+                metadata: false,
+            });
+        }
+        if let Some(own_script) = own_script {
+            let linked = build_scripts.to_link.iter().any(|(_, m)| *m == own_script);
+            if let (false, Some(&script)) = (linked, self.build_script_map.get(&own_script)) {
+                invocation.build_script_uses.push(BuildScriptUse {
+                    invocation: script,
+                    link_search: false,
+                    link_lib: false,
+                    link_args: false,
+                    flags: true,
+                    metadata: false,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn build_script_invocation(&self, metadata: Metadata) -> CargoResult<usize> {
+        self.build_script_map
+            .get(&metadata)
+            .copied()
+            .ok_or_else(|| internal(format!("couldn't find build script invocation {metadata}")))
+    }
+
+    pub fn update(
+        &mut self,
+        invocation_name: &str,
+        cmd: &ProcessBuilder,
+        outputs: &[OutputFile],
+    ) -> CargoResult<()> {
+        let id = self.invocation_map[invocation_name];
+        let invocation =
+            self.plan.invocations.get_mut(id).ok_or_else(|| {
+                internal(format!("couldn't find invocation for {}", invocation_name))
+            })?;
+
+        invocation.update_cmd(cmd)?;
+        for output in outputs.iter() {
+            invocation.add_output(&output.path, &output.hardlink);
+        }
+
+        Ok(())
+    }
+
+    pub fn set_inputs(&mut self, inputs: Vec<PathBuf>) {
+        self.plan.inputs = inputs;
+    }
+
+    pub fn output_plan(self, gctx: &GlobalContext) {
+        let encoded = serde_json::to_string(&self.plan).unwrap();
+        crate::drop_println!(gctx, "{}", encoded);
+    }
+}
+
+impl SerializedBuildPlan {
+    pub fn new() -> SerializedBuildPlan {
+        SerializedBuildPlan {
+            version: BUILD_PLAN_VERSION,
+            invocations: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+}
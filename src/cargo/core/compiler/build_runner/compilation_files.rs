@@ -101,6 +101,12 @@ pub struct CompilationFiles<'a, 'gctx> {
     pub(super) host: Layout,
     /// The target directory layout for the target (if different from then host).
     pub(super) target: HashMap<CompileTarget, Layout>,
+    /// The layouts in the directory shared by all workspaces with
+    /// `-Zshared-deps`, keyed by the kind and the hash of the `RUSTFLAGS` of
+    /// the units placed in them.
+    pub(super) shared: HashMap<SharedLayoutKey, Layout>,
+    /// Units placed in one of the [`CompilationFiles::shared`] layouts.
+    shared_units: HashMap<Unit, SharedLayoutKey>,
     /// Additional directory to include a copy of the outputs.
     export_dir: Option<PathBuf>,
     /// The root targets requested by the user on the command line (does not
@@ -113,6 +119,9 @@ pub struct CompilationFiles<'a, 'gctx> {
     outputs: HashMap<Unit, LazyCell<Arc<Vec<OutputFile>>>>,
 }
 
+/// Identifies one of the layouts of the directory shared with `-Zshared-deps`.
+type SharedLayoutKey = (CompileKind, String);
+
 /// Info about a single file emitted by the compiler.
 #[derive(Debug)]
 pub struct OutputFile {
@@ -156,6 +165,8 @@ impl<'a, 'gctx: 'a> CompilationFiles<'a, 'gctx> {
             ws: build_runner.bcx.ws,
             host,
             target,
+            shared: HashMap::new(),
+            shared_units: HashMap::new(),
             export_dir: build_runner.bcx.build_config.export_dir.clone(),
             roots: build_runner.bcx.roots.clone(),
             metas,
@@ -163,6 +174,45 @@ impl<'a, 'gctx: 'a> CompilationFiles<'a, 'gctx> {
         }
     }
 
+    /// Places the units of dependencies which can be shared between
+    /// workspaces in the shared directory, with `-Zshared-deps`.
+    ///
+    /// This locks the shared layouts, in a consistent order so that concurrent
+    /// builds can't deadlock.
+    pub(super) fn prepare_shared(
+        &mut self,
+        build_runner: &BuildRunner<'a, 'gctx>,
+    ) -> CargoResult<()> {
+        let bcx = build_runner.bcx;
+        if !bcx.gctx.cli_unstable().shared_deps {
+            return Ok(());
+        }
+        let mut shareable = HashMap::new();
+        for unit in self.metas.keys() {
+            is_shareable(unit, build_runner, &self.metas, &mut shareable);
+        }
+        self.shared_units = shareable
+            .into_iter()
+            .filter(|(_, shareable)| *shareable)
+            .map(|(unit, _)| {
+                let key = (unit.kind, util::short_hash(&unit.rustflags));
+                (unit, key)
+            })
+            .collect();
+
+        let mut keys: Vec<_> = self.shared_units.values().cloned().collect();
+        keys.sort_by(|(a_kind, a_hash), (b_kind, b_hash)| {
+            let target_data = &bcx.target_data;
+            (target_data.short_name(a_kind), a_hash).cmp(&(target_data.short_name(b_kind), b_hash))
+        });
+        keys.dedup();
+        for key in keys {
+            let layout = Layout::shared(bcx.gctx, bcx.target_data.short_name(&key.0), &key.1)?;
+            self.shared.insert(key, layout);
+        }
+        Ok(())
+    }
+
     /// Returns the appropriate directory layout for either a plugin or not.
     pub fn layout(&self, kind: CompileKind) -> &Layout {
         match kind {
@@ -171,6 +221,26 @@ impl<'a, 'gctx: 'a> CompilationFiles<'a, 'gctx> {
         }
     }
 
+    /// Returns the layout the outputs of `unit` are placed in, which is the
+    /// one for `kind`, unless the unit is in the shared directory.
+    fn unit_layout(&self, unit: &Unit, kind: CompileKind) -> &Layout {
+        match self.shared_units.get(unit) {
+            Some(key) => &self.shared[key],
+            None => self.layout(kind),
+        }
+    }
+
+    /// Whether `unit` is placed in the directory shared by all workspaces with
+    /// `-Zshared-deps`.
+    pub fn is_shared(&self, unit: &Unit) -> bool {
+        self.shared_units.contains_key(unit)
+    }
+
+    /// Returns the units placed in the directory shared by all workspaces.
+    pub fn shared_units(&self) -> impl Iterator<Item = &Unit> {
+        self.shared_units.keys()
+    }
+
     /// Gets the metadata for the given unit.
     ///
     /// See [`Metadata`] and [`fingerprint`] module for more.
@@ -250,13 +320,29 @@ impl<'a, 'gctx: 'a> CompilationFiles<'a, 'gctx> {
     /// Returns the directories where Rust crate dependencies are found for the
     /// specified unit.
     pub fn deps_dir(&self, unit: &Unit) -> &Path {
-        self.layout(unit.kind).deps()
+        self.unit_layout(unit, unit.kind).deps()
+    }
+
+    /// Returns the `deps` directories of the shared layouts in use, in which
+    /// dependencies may be found in addition to [`CompilationFiles::deps_dir`].
+    pub fn shared_deps_dirs(&self) -> Vec<&Path> {
+        let mut dirs: Vec<_> = self.shared.values().map(|layout| layout.deps()).collect();
+        dirs.sort();
+        dirs
+    }
+
+    /// Returns the destination directories of the shared layouts in use.
+    pub fn shared_dests(&self) -> Vec<PathBuf> {
+        self.shared
+            .values()
+            .map(|layout| layout.dest().to_path_buf())
+            .collect()
     }
 
     /// Directory where the fingerprint for the given unit should go.
     pub fn fingerprint_dir(&self, unit: &Unit) -> PathBuf {
         let dir = self.pkg_dir(unit);
-        self.layout(unit.kind).fingerprint().join(dir)
+        self.unit_layout(unit, unit.kind).fingerprint().join(dir)
     }
 
     /// Returns the path for a file in the fingerprint directory.
@@ -291,7 +377,7 @@ impl<'a, 'gctx: 'a> CompilationFiles<'a, 'gctx> {
         assert!(!unit.mode.is_run_custom_build());
         assert!(self.metas.contains_key(unit));
         let dir = self.pkg_dir(unit);
-        self.layout(CompileKind::Host).build().join(dir)
+        self.unit_layout(unit, CompileKind::Host).build().join(dir)
     }
 
     /// Returns the directory for compiled artifacts files.
@@ -325,7 +411,7 @@ impl<'a, 'gctx: 'a> CompilationFiles<'a, 'gctx> {
         assert!(unit.target.is_custom_build());
         assert!(unit.mode.is_run_custom_build());
         let dir = self.pkg_dir(unit);
-        self.layout(unit.kind).build().join(dir)
+        self.unit_layout(unit, unit.kind).build().join(dir)
     }
 
     /// Returns the "OUT_DIR" directory for running a build script.
@@ -570,6 +656,45 @@ fn metadata_of<'a>(
     &metas[unit]
 }
 
+/// Whether the outputs of `unit` can be placed in the directory shared by all
+/// workspaces, with `-Zshared-deps`.
+///
+/// This is the case for libraries and build scripts from registry and git
+/// sources, whose dependencies can all be shared as well. Their metadata hash
+/// is the same in every workspace, since it doesn't depend on the location of
+/// the workspace, and it is used in the name of all their files.
+fn is_shareable(
+    unit: &Unit,
+    build_runner: &BuildRunner<'_, '_>,
+    metas: &HashMap<Unit, MetaInfo>,
+    memo: &mut HashMap<Unit, bool>,
+) -> bool {
+    if let Some(&shareable) = memo.get(unit) {
+        return shareable;
+    }
+    let shareable = !unit.pkg.package_id().source_id().is_path()
+        && !unit.is_std
+        && !unit.artifact.is_true()
+        && metas[unit].use_extra_filename
+        && matches!(
+            unit.mode,
+            CompileMode::Build | CompileMode::Check { test: false } | CompileMode::RunCustomBuild
+        )
+        && (unit.target.is_custom_build()
+            || unit.target.rustc_crate_types().iter().all(|kind| {
+                matches!(
+                    kind,
+                    CrateType::Lib | CrateType::Rlib | CrateType::ProcMacro
+                )
+            }))
+        && build_runner
+            .unit_deps(unit)
+            .iter()
+            .all(|dep| is_shareable(&dep.unit, build_runner, metas, memo));
+    memo.insert(unit.clone(), shareable);
+    shareable
+}
+
 /// Computes the metadata hash for the given [`Unit`].
 fn compute_metadata(
     unit: &Unit,
@@ -10,10 +10,12 @@ use crate::core::PackageId;
 use crate::util::cache_lock::CacheLockMode;
 use crate::util::errors::CargoResult;
 use anyhow::{bail, Context as _};
+use cargo_util::paths;
 use filetime::FileTime;
 use itertools::Itertools;
 use jobserver::Client;
 
+use super::build_cache::BuildCache;
 use super::build_plan::BuildPlan;
 use super::custom_build::{self, BuildDeps, BuildScriptOutputs, BuildScripts};
 use super::fingerprint::Fingerprint;
@@ -85,6 +87,9 @@ pub struct BuildRunner<'a, 'gctx> {
     /// because the target has a type error. This is in an Arc<Mutex<..>>
     /// because it is continuously updated as the job progresses.
     pub failed_scrape_units: Arc<Mutex<HashSet<Metadata>>>,
+
+    /// The cache of compiled units enabled with `-Zbuild-cache`, if any.
+    pub(crate) build_cache: Option<BuildCache>,
 }
 
 impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
@@ -123,6 +128,7 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
             lto: HashMap::new(),
             metadata_for_doc_units: HashMap::new(),
             failed_scrape_units: Arc::new(Mutex::new(HashSet::new())),
+            build_cache: BuildCache::from_gctx(bcx.gctx)?,
         })
     }
 
@@ -364,7 +370,8 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
 
         self.record_units_requiring_metadata();
 
-        let files = CompilationFiles::new(self, host_layout, targets);
+        let mut files = CompilationFiles::new(self, host_layout, targets);
+        files.prepare_shared(self)?;
         self.files = Some(files);
         Ok(())
     }
@@ -384,6 +391,22 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
                 .prepare()
                 .context("couldn't prepare build directories")?;
         }
+        for shared in self.files.as_mut().unwrap().shared.values_mut() {
+            shared
+                .prepare()
+                .context("couldn't prepare shared build directories")?;
+        }
+        // The last use of units in the shared build directory is tracked with
+        // the modification time of their fingerprint directory, which is used
+        // to clean them up once unused.
+        let files = self.files.as_ref().unwrap();
+        let now = FileTime::now();
+        for unit in files.shared_units() {
+            let dir = files.fingerprint_dir(unit);
+            paths::create_dir_all(&dir)?;
+            paths::set_file_time_no_err(&dir, now);
+        }
+        self.compilation.shared_outputs = files.shared_dests();
 
         let files = self.files.as_ref().unwrap();
         for &kind in self.bcx.all_kinds.iter() {
@@ -93,6 +93,10 @@ pub struct Compilation<'gctx> {
     /// May be for the host or for a specific target.
     pub deps_output: HashMap<CompileKind, PathBuf>,
 
+    /// Output directories of the dependencies placed in the build directory
+    /// shared by all workspaces, with `-Zshared-deps`.
+    pub shared_outputs: Vec<PathBuf>,
+
     /// The path to libstd for each target
     sysroot_target_libdir: HashMap<CompileKind, PathBuf>,
 
@@ -143,6 +147,7 @@ impl<'gctx> Compilation<'gctx> {
             native_dirs: BTreeSet::new(),
             root_output: HashMap::new(),
             deps_output: HashMap::new(),
+            shared_outputs: Vec::new(),
             sysroot_target_libdir: get_sysroot_target_libdir(bcx)?,
             tests: Vec::new(),
             binaries: Vec::new(),
@@ -302,6 +307,7 @@ impl<'gctx> Compilation<'gctx> {
                 search_path.extend(super::filter_dynamic_search_path(
                     self.native_dirs.iter(),
                     &self.root_output[&CompileKind::Host],
+                    &self.shared_outputs,
                 ));
             }
             search_path.push(self.deps_output[&CompileKind::Host].clone());
@@ -309,6 +315,7 @@ impl<'gctx> Compilation<'gctx> {
             search_path.extend(super::filter_dynamic_search_path(
                 self.native_dirs.iter(),
                 &self.root_output[&kind],
+                &self.shared_outputs,
             ));
             search_path.push(self.deps_output[&kind].clone());
             search_path.push(self.root_output[&kind].clone());
@@ -388,6 +388,7 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
     let err_file = script_run_dir.join("stderr");
     let root_output_file = script_run_dir.join("root-output");
     let host_target_root = build_runner.files().host_dest().to_path_buf();
+    let shared_outputs = build_runner.files().shared_dests();
     let all = (
         id,
         library_name.clone(),
@@ -461,6 +462,7 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
                     &build_script_outputs,
                     &build_scripts,
                     &host_target_root,
+                    &shared_outputs,
                 )?;
             }
         }
@@ -0,0 +1,149 @@
+//! Content checksums of the inputs of a unit, for `-Zchecksum-freshness`.
+//!
+//! Normally an input file is considered changed whenever its mtime is newer
+//! than the reference file of the unit (see [`find_stale_file`]). This causes
+//! rebuilds whenever mtimes are reset without changing the contents, like on a
+//! fresh `git checkout` or when a CI cache of the `target` directory is
+//! restored.
+//!
+//! With `-Zchecksum-freshness`, after a unit is built Cargo records the size
+//! and SHA-256 checksum of every file listed in its dep-info and of every
+//! `rerun-if-changed` path of a build script. The mtime comparison stays the
+//! fast path: only files that look newer than the reference are read, and they
+//! are considered changed only if their contents differ from what was
+//! recorded.
+//!
+//! Files modified after the build started are not recorded, since Cargo can't
+//! know whether the build saw the old or new contents. Those files fall back
+//! to the mtime comparison, and so does anything that isn't a regular file,
+//! like a directory passed to `rerun-if-changed`.
+//!
+//! [`find_stale_file`]: super::find_stale_file
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use cargo_util::{paths, Sha256};
+use filetime::FileTime;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::{parse_dep_info, LocalFingerprint};
+use crate::util::errors::CargoResult;
+
+/// The checksums of the inputs of a unit, as stored in the fingerprint
+/// directory.
+///
+/// Paths inside the package root are stored relative to it, so that renaming
+/// the project directory keeps the checksums valid.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Checksums {
+    files: HashMap<PathBuf, FileChecksum>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct FileChecksum {
+    size: u64,
+    sha256: String,
+}
+
+impl FileChecksum {
+    fn compute(path: &Path) -> CargoResult<FileChecksum> {
+        let size = paths::metadata(path)?.len();
+        let sha256 = Sha256::new().update_path(path)?.finish_hex();
+        Ok(FileChecksum { size, sha256 })
+    }
+}
+
+impl Checksums {
+    /// Loads the checksums previously saved to `path`.
+    ///
+    /// Returns `None` if there are none, or they can't be read, in which case
+    /// only mtimes are compared.
+    pub fn load(path: &Path) -> Option<Checksums> {
+        let contents = paths::read(path).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(checksums) => Some(checksums),
+            Err(e) => {
+                tracing::warn!("failed to parse checksums at {:?}: {e}", path);
+                None
+            }
+        }
+    }
+
+    /// Records the checksums of the inputs described by `local`, and saves
+    /// them to `path`.
+    pub fn record(
+        path: &Path,
+        local: &[LocalFingerprint],
+        pkg_root: &Path,
+        target_root: &Path,
+    ) -> CargoResult<()> {
+        let mut checksums = Checksums::default();
+        for local in local {
+            match local {
+                LocalFingerprint::CheckDepInfo { dep_info } => {
+                    let dep_info = target_root.join(dep_info);
+                    let Some(info) = parse_dep_info(pkg_root, target_root, &dep_info)? else {
+                        continue;
+                    };
+                    checksums.add(&dep_info, &info.files, pkg_root);
+                }
+                LocalFingerprint::RerunIfChanged { output, paths } => {
+                    let paths: Vec<_> = paths.iter().map(|p| pkg_root.join(p)).collect();
+                    checksums.add(&target_root.join(output), &paths, pkg_root);
+                }
+                LocalFingerprint::RerunIfEnvChanged { .. }
+                | LocalFingerprint::Precalculated(..) => {}
+            }
+        }
+        paths::write(path, serde_json::to_string(&checksums)?)
+    }
+
+    /// Adds the checksums of `files`, skipping those modified after
+    /// `reference`, which marks the start of the build.
+    fn add(&mut self, reference: &Path, files: &[PathBuf], pkg_root: &Path) {
+        let Ok(reference_mtime) = paths::mtime(reference) else {
+            return;
+        };
+        for file in files {
+            let Ok(metadata) = paths::metadata(file) else {
+                continue;
+            };
+            if !metadata.is_file()
+                || FileTime::from_last_modification_time(&metadata) > reference_mtime
+            {
+                continue;
+            }
+            match FileChecksum::compute(file) {
+                Ok(checksum) => {
+                    self.files.insert(key(file, pkg_root), checksum);
+                }
+                Err(e) => debug!("failed to compute checksum of {:?}: {e}", file),
+            }
+        }
+    }
+
+    /// Whether `path` still has the contents recorded for it.
+    ///
+    /// Returns `false` if nothing was recorded for `path`.
+    pub fn is_unchanged(&self, path: &Path, pkg_root: &Path) -> bool {
+        let Some(recorded) = self.files.get(&key(path, pkg_root)) else {
+            return false;
+        };
+        // Compare the size first to avoid reading files which obviously
+        // changed.
+        match paths::metadata(path) {
+            Ok(metadata) if metadata.len() == recorded.size => {}
+            _ => return false,
+        }
+        match FileChecksum::compute(path) {
+            Ok(current) => current == *recorded,
+            Err(_) => false,
+        }
+    }
+}
+
+fn key(path: &Path, pkg_root: &Path) -> PathBuf {
+    path.strip_prefix(pkg_root).unwrap_or(path).to_path_buf()
+}
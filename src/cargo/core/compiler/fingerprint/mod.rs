@@ -121,6 +121,8 @@
 //!   the Unit is built. This is used for capturing the time when the build
 //!   starts, to detect if files are changed in the middle of the build. See
 //!   below for more details.
+//! - With `-Zchecksum-freshness`, a "checksums" file with the size and
+//!   checksum of every input of the Unit. See [`checksum`] for more details.
 //!
 //! Note that some units are a little different. A Unit for *running* a build
 //! script or for `rustdoc` does not have a dep-info file (it's not
@@ -263,6 +265,15 @@
 //! to the time just before the build script is executed to handle mid-build
 //! modifications.
 //!
+//! #### Checksum freshness
+//!
+//! With `-Zchecksum-freshness`, an input file that is newer than the
+//! reference file for [`CheckDepInfo`] or [`RerunIfChanged`] is only
+//! considered stale if its contents differ from the contents recorded after
+//! the last build. See the [`checksum`] module for how this works. The
+//! comparison between the outputs of a unit and its dependencies is still
+//! purely mtime based.
+//!
 //! ## Considerations for inclusion in a fingerprint
 //!
 //! Over time we've realized a few items which historically were included in
@@ -353,6 +364,7 @@
 //! [`CompileMode::RunCustomBuild`]: crate::core::compiler::CompileMode::RunCustomBuild
 //! [`A-rebuild-detection`]: https://github.com/rust-lang/cargo/issues?q=is%3Aissue+is%3Aopen+label%3AA-rebuild-detection
 
+mod checksum;
 mod dirty_reason;
 
 use std::collections::hash_map::{Entry, HashMap};
@@ -384,6 +396,7 @@ use crate::{GlobalContext, CARGO_ENV};
 use super::custom_build::BuildDeps;
 use super::{BuildContext, BuildRunner, FileFlavor, Job, Unit, Work};
 
+use checksum::Checksums;
 pub use dirty_reason::DirtyReason;
 
 /// Determines if a [`Unit`] is up-to-date, and if not prepares necessary work to
@@ -475,6 +488,21 @@ pub fn prepare_target(
         paths::write(&loc, b"")?;
     }
 
+    // Checksums are recorded again once the unit is built, the old ones
+    // describe inputs that have changed.
+    let checksums_loc = bcx.gctx.cli_unstable().checksum_freshness.then(|| {
+        build_runner
+            .files()
+            .fingerprint_file_path(unit, "checksums-")
+    });
+    if let Some(checksums_loc) = &checksums_loc {
+        if checksums_loc.exists() {
+            paths::remove_file(checksums_loc)?;
+        }
+    }
+    let pkg_root = unit.pkg.root().to_path_buf();
+    let target_root = target_root(build_runner);
+
     let write_fingerprint = if unit.mode.is_run_custom_build() {
         // For build scripts the `local` field of the fingerprint may change
         // while we're executing it. For example it could be in the legacy
@@ -506,10 +534,22 @@ pub fn prepare_target(
                 *fingerprint.local.lock().unwrap() = new_local;
             }
 
-            write_fingerprint(&loc, &fingerprint)
+            write_fingerprint(&loc, &fingerprint)?;
+            if let Some(checksums_loc) = &checksums_loc {
+                let local = fingerprint.local.lock().unwrap();
+                Checksums::record(checksums_loc, &local, &pkg_root, &target_root)?;
+            }
+            Ok(())
         })
     } else {
-        Work::new(move |_| write_fingerprint(&loc, &fingerprint))
+        Work::new(move |_| {
+            write_fingerprint(&loc, &fingerprint)?;
+            if let Some(checksums_loc) = &checksums_loc {
+                let local = fingerprint.local.lock().unwrap();
+                Checksums::record(checksums_loc, &local, &pkg_root, &target_root)?;
+            }
+            Ok(())
+        })
     };
 
     Ok(Job::new_dirty(write_fingerprint, dirty_reason))
@@ -793,6 +833,7 @@ impl LocalFingerprint {
     fn find_stale_item(
         &self,
         mtime_cache: &mut HashMap<PathBuf, FileTime>,
+        checksums: Option<&Checksums>,
         pkg_root: &Path,
         target_root: &Path,
         cargo_exe: &Path,
@@ -835,13 +876,21 @@ impl LocalFingerprint {
                         current,
                     }));
                 }
-                Ok(find_stale_file(mtime_cache, &dep_info, info.files.iter()))
+                Ok(find_stale_file(
+                    mtime_cache,
+                    checksums,
+                    pkg_root,
+                    &dep_info,
+                    info.files.iter(),
+                ))
             }
 
             // We need to verify that no paths listed in `paths` are newer than
             // the `output` path itself, or the last time the build script ran.
             LocalFingerprint::RerunIfChanged { output, paths } => Ok(find_stale_file(
                 mtime_cache,
+                checksums,
+                pkg_root,
                 &target_root.join(output),
                 paths.iter().map(|p| pkg_root.join(p)),
             )),
@@ -895,7 +944,7 @@ impl Fingerprint {
         *self.memoized_hash.lock().unwrap() = None;
     }
 
-    fn hash_u64(&self) -> u64 {
+    pub(crate) fn hash_u64(&self) -> u64 {
         if let Some(s) = *self.memoized_hash.lock().unwrap() {
             return s;
         }
@@ -1077,6 +1126,7 @@ impl Fingerprint {
     fn check_filesystem(
         &mut self,
         mtime_cache: &mut HashMap<PathBuf, FileTime>,
+        checksums: Option<&Checksums>,
         pkg_root: &Path,
         target_root: &Path,
         cargo_exe: &Path,
@@ -1181,9 +1231,14 @@ impl Fingerprint {
         // files for this package itself. If we do find something log a helpful
         // message and bail out so we stay stale.
         for local in self.local.get_mut().unwrap().iter() {
-            if let Some(item) =
-                local.find_stale_item(mtime_cache, pkg_root, target_root, cargo_exe, gctx)?
-            {
+            if let Some(item) = local.find_stale_item(
+                mtime_cache,
+                checksums,
+                pkg_root,
+                target_root,
+                cargo_exe,
+                gctx,
+            )? {
                 item.log();
                 self.fs_status = FsStatus::StaleItem(item);
                 return Ok(());
@@ -1345,8 +1400,18 @@ fn calculate(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResult
     // `fs_status` field of it.
     let target_root = target_root(build_runner);
     let cargo_exe = build_runner.bcx.gctx.cargo_exe()?;
+    let checksums = if build_runner.bcx.gctx.cli_unstable().checksum_freshness {
+        Checksums::load(
+            &build_runner
+                .files()
+                .fingerprint_file_path(unit, "checksums-"),
+        )
+    } else {
+        None
+    };
     fingerprint.check_filesystem(
         &mut build_runner.mtime_cache,
+        checksums.as_ref(),
         unit.pkg.root(),
         &target_root,
         cargo_exe,
@@ -1398,7 +1463,13 @@ fn calculate_normal(
         vec![LocalFingerprint::Precalculated(fingerprint)]
     } else {
         let dep_info = dep_info_loc(build_runner, unit);
-        let dep_info = dep_info.strip_prefix(&target_root).unwrap().to_path_buf();
+        // Units in the build directory shared with `-Zshared-deps` are outside
+        // of the target directory. They keep an absolute path, which is the
+        // same for all workspaces.
+        let dep_info = match dep_info.strip_prefix(&target_root) {
+            Ok(path) => path.to_path_buf(),
+            Err(_) => dep_info,
+        };
         vec![LocalFingerprint::CheckDepInfo { dep_info }]
     };
 
@@ -1681,10 +1752,12 @@ fn local_fingerprints_deps(
         // Note that like the module comment above says we are careful to never
         // store an absolute path in `LocalFingerprint`, so ensure that we strip
         // absolute prefixes from them.
+        // Build scripts in the build directory shared with `-Zshared-deps`
+        // keep an absolute path.
         let output = deps
             .build_script_output
             .strip_prefix(target_root)
-            .unwrap()
+            .unwrap_or(&deps.build_script_output)
             .to_path_buf();
         let paths = deps
             .rerun_if_changed
@@ -1865,8 +1938,13 @@ fn pkg_fingerprint(bcx: &BuildContext<'_, '_>, pkg: &Package) -> CargoResult<Str
 }
 
 /// The `reference` file is considered as "stale" if any file from `paths` has a newer mtime.
+///
+/// With `-Zchecksum-freshness`, files with a newer mtime whose contents match
+/// `checksums` are not considered stale.
 fn find_stale_file<I>(
     mtime_cache: &mut HashMap<PathBuf, FileTime>,
+    checksums: Option<&Checksums>,
+    pkg_root: &Path,
     reference: &Path,
     paths: I,
 ) -> Option<StaleItem>
@@ -1931,6 +2009,10 @@ where
         if path_mtime <= reference_mtime {
             continue;
         }
+        if checksums.is_some_and(|checksums| checksums.is_unchanged(path, pkg_root)) {
+            debug!("{:?} is newer than {:?} but unchanged", path, reference);
+            continue;
+        }
 
         return Some(StaleItem::ChangedFile {
             reference: reference.to_path_buf(),
@@ -0,0 +1,289 @@
+//! Memory-aware limits on the jobs running at once, see `-Zmemory-limit`.
+//!
+//! Jobserver tokens limit how many jobs run at once, regardless of how much
+//! memory they need. With `build.memory-limit`, each unit also has an
+//! estimated memory use, and a job isn't started while the estimates of the
+//! running jobs plus its own exceed the limit, unless nothing else is running.
+//! The estimate of a unit is, in order of preference:
+//!
+//! 1. the size given to its package in `build.unit-memory`,
+//! 2. the peak memory recorded for the unit in the timing data of the last
+//!    build saved with `--timings=json-file`,
+//! 3. the average peak memory of the units in that timing data,
+//!
+//! and zero if none of these is known. Independently, `build.link-jobs` caps
+//! the number of running jobs producing linked artifacts, like binaries and
+//! tests, which tend to be the ones needing the most memory.
+//!
+//! The peak memory of each job is measured on Linux, by sampling the peak
+//! resident set size of the processes spawned by the thread running the job,
+//! as reported by `/proc`. It is saved in the timing data, for the estimates
+//! of the next build.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+use super::{Job, JobId};
+use crate::core::compiler::timings::{self, TimingData};
+use crate::core::compiler::{BuildContext, CompileMode, Unit};
+
+/// How often the memory of running jobs is sampled at most.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Tracks the estimated memory use and number of link jobs of the running
+/// jobs, to decide whether more jobs may start.
+pub struct MemoryLimits {
+    /// From `build.memory-limit`, in bytes.
+    limit: Option<u64>,
+    /// From `build.link-jobs`.
+    link_jobs: Option<u32>,
+    /// Peak memory of the units of a previous build, keyed by
+    /// [`timings::unit_key`].
+    history: HashMap<(String, String, String, String), u64>,
+    /// The estimate of units without a recorded peak.
+    default_estimate: u64,
+    /// The estimated memory use of running jobs, and whether they link.
+    running: HashMap<JobId, (u64, bool)>,
+    /// The sum of the estimates of `running`.
+    in_use: u64,
+    /// The number of `running` jobs which link.
+    running_links: u32,
+}
+
+impl MemoryLimits {
+    pub fn new(bcx: &BuildContext<'_, '_>, previous: Option<&TimingData>) -> MemoryLimits {
+        let mut limits = MemoryLimits {
+            limit: bcx.build_config.memory_limit,
+            link_jobs: bcx.build_config.link_jobs,
+            history: HashMap::new(),
+            default_estimate: 0,
+            running: HashMap::new(),
+            in_use: 0,
+            running_links: 0,
+        };
+        if let Some(previous) = previous {
+            for unit in &previous.units {
+                let Some(peak_memory) = unit.peak_memory else {
+                    continue;
+                };
+                let (package_id, target, mode, kind) = unit.key();
+                let key = (
+                    package_id.to_string(),
+                    target.to_string(),
+                    mode.to_string(),
+                    kind.to_string(),
+                );
+                let estimate = limits.history.entry(key).or_default();
+                *estimate = (*estimate).max(peak_memory);
+            }
+            if !limits.history.is_empty() {
+                let total: u64 = limits.history.values().sum();
+                limits.default_estimate = total / limits.history.len() as u64;
+            }
+            debug!(
+                "loaded {} unit memory estimates, default estimate {}",
+                limits.history.len(),
+                limits.default_estimate
+            );
+        }
+        limits
+    }
+
+    /// Whether the timing data of the previous build is used.
+    pub fn uses_timing_data(bcx: &BuildContext<'_, '_>) -> bool {
+        bcx.build_config.memory_limit.is_some()
+    }
+
+    /// The estimated memory use of `unit`, in bytes.
+    fn estimate(&self, bcx: &BuildContext<'_, '_>, unit: &Unit) -> u64 {
+        if let Some(&estimate) = bcx.build_config.unit_memory.get(unit.pkg.name().as_str()) {
+            return estimate;
+        }
+        self.history
+            .get(&timings::unit_key(bcx, unit))
+            .copied()
+            .unwrap_or(self.default_estimate)
+    }
+
+    /// Whether `job` may start without exceeding the limits.
+    pub fn fits(&self, bcx: &BuildContext<'_, '_>, unit: &Unit, job: &Job) -> bool {
+        if job.freshness().is_fresh() {
+            return true;
+        }
+        if let Some(link_jobs) = self.link_jobs {
+            if is_link(unit) && self.running_links >= link_jobs {
+                return false;
+            }
+        }
+        match self.limit {
+            // Always allow one job, so that the build can make progress
+            // even if a unit is expected to need more than the limit.
+            Some(limit) => self.in_use == 0 || self.in_use + self.estimate(bcx, unit) <= limit,
+            None => true,
+        }
+    }
+
+    /// Records that `job` started as `id`.
+    pub fn start(&mut self, id: JobId, bcx: &BuildContext<'_, '_>, unit: &Unit, job: &Job) {
+        if job.freshness().is_fresh() {
+            return;
+        }
+        let estimate = match self.limit {
+            Some(_) => self.estimate(bcx, unit),
+            None => 0,
+        };
+        let link = is_link(unit);
+        self.in_use += estimate;
+        if link {
+            self.running_links += 1;
+        }
+        self.running.insert(id, (estimate, link));
+    }
+
+    /// Records that the job `id` finished.
+    pub fn finish(&mut self, id: JobId) {
+        if let Some((estimate, link)) = self.running.remove(&id) {
+            self.in_use -= estimate;
+            if link {
+                self.running_links -= 1;
+            }
+        }
+    }
+}
+
+/// Whether `unit` produces a linked artifact.
+fn is_link(unit: &Unit) -> bool {
+    matches!(
+        unit.mode,
+        CompileMode::Build | CompileMode::Test | CompileMode::Bench
+    ) && unit.requires_upstream_objects()
+}
+
+/// Measures the peak memory used by the processes of running jobs.
+pub struct PeakMemory {
+    threads: JobThreads,
+    /// The highest memory use sampled for each running job.
+    peaks: HashMap<JobId, u64>,
+    last_sample: Instant,
+}
+
+/// The threads running jobs, which register themselves with
+/// [`JobThreads::register_current`].
+#[derive(Clone, Default)]
+pub struct JobThreads(Arc<Mutex<HashMap<JobId, u32>>>);
+
+impl JobThreads {
+    /// Registers the current thread as running the job `id`.
+    pub fn register_current(&self, id: JobId) {
+        if let Some(tid) = imp::current_thread() {
+            self.0.lock().unwrap().insert(id, tid);
+        }
+    }
+}
+
+impl PeakMemory {
+    pub fn new() -> PeakMemory {
+        PeakMemory {
+            threads: JobThreads::default(),
+            peaks: HashMap::new(),
+            last_sample: Instant::now(),
+        }
+    }
+
+    /// The threads to register the jobs with.
+    pub fn threads(&self) -> JobThreads {
+        self.threads.clone()
+    }
+
+    /// Samples the memory used by the processes of all running jobs.
+    pub fn sample(&mut self) {
+        if self.last_sample.elapsed() < SAMPLE_INTERVAL {
+            return;
+        }
+        self.last_sample = Instant::now();
+        let threads = self.threads.0.lock().unwrap();
+        for (&id, &tid) in threads.iter() {
+            let Some(memory) = imp::peak_memory(tid) else {
+                continue;
+            };
+            let peak = self.peaks.entry(id).or_default();
+            *peak = (*peak).max(memory);
+        }
+    }
+
+    /// Stops sampling the job `id`, returning its peak memory use, if any
+    /// was measured.
+    pub fn finish(&mut self, id: JobId) -> Option<u64> {
+        self.threads.0.lock().unwrap().remove(&id);
+        self.peaks.remove(&id)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::fs;
+    use std::path::Path;
+
+    pub fn current_thread() -> Option<u32> {
+        // This links to `<pid>/task/<tid>`.
+        let link = fs::read_link("/proc/thread-self").ok()?;
+        link.file_name()?.to_str()?.parse().ok()
+    }
+
+    /// The sum of the peak resident set sizes of the processes spawned by
+    /// thread `tid` of this process, and of their descendants.
+    pub fn peak_memory(tid: u32) -> Option<u64> {
+        let mut pids = children(Path::new(&format!("/proc/self/task/{tid}/children")));
+        if pids.is_empty() {
+            return None;
+        }
+        let mut total = 0;
+        while let Some(pid) = pids.pop() {
+            total += peak_rss(pid).unwrap_or(0);
+            let Ok(tasks) = fs::read_dir(format!("/proc/{pid}/task")) else {
+                continue;
+            };
+            for task in tasks.flatten() {
+                pids.extend(children(&task.path().join("children")));
+            }
+        }
+        Some(total)
+    }
+
+    fn children(path: &Path) -> Vec<u32> {
+        fs::read_to_string(path)
+            .map(|s| {
+                s.split_whitespace()
+                    .filter_map(|p| p.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn peak_rss(pid: u32) -> Option<u64> {
+        let status = fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+        let kb = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmHWM:"))?
+            .trim()
+            .strip_suffix("kB")?
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+        Some(kb * 1024)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    pub fn current_thread() -> Option<u32> {
+        None
+    }
+
+    pub fn peak_memory(_tid: u32) -> Option<u64> {
+        None
+    }
+}
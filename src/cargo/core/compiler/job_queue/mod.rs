@@ -57,18 +57,20 @@
 //!
 //! ## Scheduling
 //!
-//! The current scheduling algorithm is not really polished. It is simply based
-//! on a dependency graph [`DependencyQueue`]. We continue adding nodes onto
-//! the graph until we finalize it. When the graph gets finalized, it finds the
-//! sum of the cost of each dependencies of each node, including transitively.
-//! The sum of dependency cost turns out to be the cost of each given node.
+//! The scheduling algorithm is based on a dependency graph [`DependencyQueue`].
+//! We continue adding nodes onto the graph until we finalize it. Each node has
+//! a cost, and when the graph gets finalized a priority is computed for each
+//! node from the costs of the nodes depending on it. Whenever a jobserver token
+//! is available, the ready node with the highest priority is started.
 //!
-//! At the time being, the cost is just passed as a fixed placeholder in
-//! [`JobQueue::enqueue`]. In the future, we could explore more possibilities
-//! around it. For instance, we start persisting timing information for each
-//! build somewhere. For a subsequent build, we can look into the historical
-//! data and perform a PGO-like optimization to prioritize jobs, making a build
-//! fully pipelined.
+//! How costs and priorities are computed depends on the [`SchedulingPolicy`]
+//! selected with `-Zscheduling`. By default every unit has the same cost, and
+//! the priority of a unit is the sum of the costs of the units depending on
+//! it, including transitively. With the `critical-path` policy, the cost of a
+//! unit is how long it took in the timing data saved by a previous build, and
+//! the priority is the cost of the longest chain of units starting with it, so
+//! that long chains, like a slow crate everything else waits on, start as
+//! early as possible. See [`scheduling`] for more.
 //!
 //! ## Message queue
 //!
@@ -112,6 +114,8 @@
 
 mod job;
 mod job_state;
+mod memory;
+mod scheduling;
 
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
@@ -131,8 +135,12 @@ use tracing::{debug, trace};
 pub use self::job::Freshness::{self, Dirty, Fresh};
 pub use self::job::{Job, Work};
 pub use self::job_state::JobState;
+use self::memory::{MemoryLimits, PeakMemory};
+pub use self::scheduling::SchedulingPolicy;
+use self::scheduling::UnitCosts;
 use super::build_runner::OutputFile;
-use super::timings::Timings;
+use super::fingerprint::DirtyReason;
+use super::timings::{self, Timings};
 use super::{BuildContext, BuildPlan, BuildRunner, CompileMode, Unit};
 use crate::core::compiler::descriptive_pkg_name;
 use crate::core::compiler::future_incompat::{
@@ -155,6 +163,11 @@ pub struct JobQueue<'gctx> {
     queue: DependencyQueue<Unit, Artifact, Job>,
     counts: HashMap<PackageId, usize>,
     timings: Timings<'gctx>,
+    /// Why each dirty unit is rebuilt, only recorded for `--explain-rebuild`.
+    dirty_reasons: HashMap<Unit, DirtyReason>,
+    /// The costs of units according to the scheduling policy.
+    costs: UnitCosts,
+    memory: MemoryLimits,
 }
 
 /// This structure is backed by the `DependencyQueue` type and manages the
@@ -181,6 +194,7 @@ struct DrainState<'gctx> {
     progress: Progress<'gctx>,
     next_id: u32,
     timings: Timings<'gctx>,
+    dirty_reasons: HashMap<Unit, DirtyReason>,
 
     /// Tokens that are currently owned by this Cargo, and may be "associated"
     /// with a rustc process. They may also be unused, though if so will be
@@ -195,6 +209,12 @@ struct DrainState<'gctx> {
     /// retrieved from the `queue`. We eagerly pull jobs off the main queue to
     /// allow us to request jobserver tokens pretty early.
     pending_queue: Vec<(Unit, Job, usize)>,
+    /// Whether jobs in `pending_queue` are held back by the memory limits,
+    /// even though there are tokens to run them.
+    memory_held: bool,
+    memory: MemoryLimits,
+    /// Measures the memory of running jobs, only with `-Zmemory-limit`.
+    peak_memory: Option<PeakMemory>,
     print: DiagnosticPrinter<'gctx>,
 
     /// How many jobs we've finished
@@ -376,10 +396,22 @@ enum Message {
 
 impl<'gctx> JobQueue<'gctx> {
     pub fn new(bcx: &BuildContext<'_, 'gctx>) -> JobQueue<'gctx> {
+        let previous = if UnitCosts::uses_timing_data(bcx) || MemoryLimits::uses_timing_data(bcx) {
+            timings::load_previous(bcx)
+        } else {
+            None
+        };
+        let costs = UnitCosts::new(bcx, previous.as_ref());
+        let memory = MemoryLimits::new(bcx, previous.as_ref());
+        let mut queue = DependencyQueue::new();
+        queue.set_priority_mode(costs.priority_mode());
         JobQueue {
-            queue: DependencyQueue::new(),
+            queue,
             counts: HashMap::new(),
             timings: Timings::new(bcx, &bcx.roots),
+            dirty_reasons: HashMap::new(),
+            costs,
+            memory,
         }
     }
 
@@ -453,11 +485,16 @@ impl<'gctx> JobQueue<'gctx> {
             }
         }
 
-        // For now we use a fixed placeholder value for the cost of each unit, but
-        // in the future this could be used to allow users to provide hints about
-        // relative expected costs of units, or this could be automatically set in
-        // a smarter way using timing data from a previous compilation.
-        self.queue.queue(unit.clone(), job, queue_deps, 100);
+        // Units built for the first time have nothing to explain.
+        if build_runner.bcx.build_config.explain_rebuild {
+            if let Dirty(reason) = job.freshness() {
+                if !reason.is_fresh_build() {
+                    self.dirty_reasons.insert(unit.clone(), reason.clone());
+                }
+            }
+        }
+        let cost = self.costs.cost(build_runner.bcx, unit, &job);
+        self.queue.queue(unit.clone(), job, queue_deps, cost);
         *self.counts.entry(unit.pkg.package_id()).or_insert(0) += 1;
         Ok(())
     }
@@ -495,8 +532,17 @@ impl<'gctx> JobQueue<'gctx> {
             progress,
             next_id: 0,
             timings: self.timings,
+            dirty_reasons: self.dirty_reasons,
             tokens: Vec::new(),
             pending_queue: Vec::new(),
+            memory_held: false,
+            memory: self.memory,
+            peak_memory: build_runner
+                .bcx
+                .gctx
+                .cli_unstable()
+                .memory_limit
+                .then(PeakMemory::new),
             print: DiagnosticPrinter::new(
                 build_runner.bcx.gctx,
                 &build_runner.bcx.rustc().workspace_wrapper,
@@ -569,15 +615,24 @@ impl<'gctx> DrainState<'gctx> {
         // we're able to perform some parallel work.
         // The `pending_queue` is sorted in ascending priority order, and we
         // remove items from its end to schedule the highest priority items
-        // sooner.
+        // sooner. Items which don't fit in the memory limits are skipped.
         while self.has_extra_tokens() && !self.pending_queue.is_empty() {
-            let (unit, job, _) = self.pending_queue.pop().unwrap();
+            let bcx = build_runner.bcx;
+            let Some(idx) = self
+                .pending_queue
+                .iter()
+                .rposition(|(unit, job, _)| self.memory.fits(bcx, unit, job))
+            else {
+                break;
+            };
+            let (unit, job, _) = self.pending_queue.remove(idx);
             *self.counts.get_mut(&unit.pkg.package_id()).unwrap() -= 1;
             if !build_runner.bcx.build_config.build_plan {
                 // Print out some nice progress information.
                 // NOTE: An error here will drop the job without starting it.
                 // That should be OK, since we want to exit as soon as
                 // possible during an error.
+                self.explain_rebuild(build_runner, &unit)?;
                 self.note_working_on(
                     build_runner.bcx.gctx,
                     build_runner.bcx.ws.root(),
@@ -587,6 +642,7 @@ impl<'gctx> DrainState<'gctx> {
             }
             self.run(&unit, job, build_runner, scope);
         }
+        self.memory_held = self.has_extra_tokens() && !self.pending_queue.is_empty();
 
         Ok(())
     }
@@ -658,6 +714,10 @@ impl<'gctx> DrainState<'gctx> {
                     Artifact::All => {
                         trace!("end: {:?}", id);
                         self.finished += 1;
+                        self.memory.finish(id);
+                        if let Some(peak) = self.peak_memory.as_mut().and_then(|p| p.finish(id)) {
+                            self.timings.unit_peak_memory(id, peak);
+                        }
                         self.report_warning_count(
                             build_runner.bcx.gctx,
                             id,
@@ -716,11 +776,18 @@ impl<'gctx> DrainState<'gctx> {
         // to run above to calculate CPU usage over time. To do this we
         // listen for a message with a timeout, and on timeout we run the
         // previous parts of the loop again.
+        if let Some(peak_memory) = &mut self.peak_memory {
+            peak_memory.sample();
+        }
         let mut events = self.messages.try_pop_all();
         if events.is_empty() {
             loop {
                 self.tick_progress();
-                self.tokens.truncate(self.active.len() - 1);
+                // Keep the tokens of jobs waiting for memory, so that they
+                // can start as soon as enough running jobs finish.
+                if !self.memory_held {
+                    self.tokens.truncate(self.active.len() - 1);
+                }
                 match self.messages.pop(Duration::from_millis(500)) {
                     Some(message) => {
                         events.push(message);
@@ -879,6 +946,9 @@ impl<'gctx> DrainState<'gctx> {
             self.queue.len(),
         );
         self.timings.record_cpu();
+        if let Some(peak_memory) = &mut self.peak_memory {
+            peak_memory.sample();
+        }
 
         let active_names = self
             .active
@@ -938,12 +1008,21 @@ impl<'gctx> DrainState<'gctx> {
         debug!("start {}: {:?}", id, unit);
 
         assert!(self.active.insert(id, unit.clone()).is_none());
+        self.memory.start(id, build_runner.bcx, unit, &job);
 
         let messages = self.messages.clone();
         let is_fresh = job.freshness().is_fresh();
         let rmeta_required = build_runner.rmeta_required(unit);
+        // Fresh jobs run on this thread and don't spawn any process.
+        let job_threads = match &self.peak_memory {
+            Some(peak_memory) if !is_fresh => Some(peak_memory.threads()),
+            _ => None,
+        };
 
         let doit = move |diag_dedupe| {
+            if let Some(job_threads) = job_threads {
+                job_threads.register_current(id);
+            }
             let state = JobState::new(id, messages, diag_dedupe, rmeta_required);
             state.run_to_finish(job);
         };
@@ -1138,7 +1217,8 @@ impl<'gctx> DrainState<'gctx> {
             // Any dirty stage which runs at least one command gets printed as
             // being a compiled package.
             Dirty(dirty_reason) => {
-                if !dirty_reason.is_fresh_build() {
+                // With `--explain-rebuild` this has already been printed.
+                if !dirty_reason.is_fresh_build() && !self.dirty_reasons.contains_key(unit) {
                     gctx.shell()
                         .verbose(|shell| dirty_reason.present_to(shell, unit, ws_root))?;
                 }
@@ -1173,6 +1253,88 @@ impl<'gctx> DrainState<'gctx> {
         Ok(())
     }
 
+    /// Explains why `unit` is rebuilt for `--explain-rebuild`, following
+    /// rebuilt dependencies back to the change that caused the rebuild.
+    fn explain_rebuild(&self, build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<()> {
+        let Some(reason) = self.dirty_reasons.get(unit) else {
+            return Ok(());
+        };
+        let mut chain = Vec::new();
+        let mut current = (unit, reason);
+        while let Some(name) = current.1.dependency_name() {
+            let Some(next) = build_runner
+                .unit_deps(current.0)
+                .iter()
+                .filter(|dep| dep.extern_crate_name == name)
+                .find_map(|dep| Some((&dep.unit, self.dirty_reasons.get(&dep.unit)?)))
+            else {
+                break;
+            };
+            // Fingerprints can't be cyclic, but be defensive about looping
+            // forever.
+            if chain.iter().any(|(u, _)| *u == next.0) {
+                break;
+            }
+            chain.push(next);
+            current = next;
+        }
+
+        let gctx = build_runner.bcx.gctx;
+        let ws_root = build_runner.bcx.ws.root();
+        if build_runner.bcx.build_config.emit_json() {
+            fn cause<'a>(
+                reason: &'a DirtyReason,
+                ws_root: &Path,
+            ) -> machine_message::RebuildCause<'a> {
+                machine_message::RebuildCause {
+                    kind: reason.kind(),
+                    message: reason.description(ws_root),
+                    dependency: reason.dependency_name().map(|name| name.as_str()),
+                    path: reason.changed_path(),
+                    env_var: reason.env_var(),
+                }
+            }
+            let caused_by = chain
+                .iter()
+                .map(|(dep, dep_reason)| machine_message::RebuildCauseOfDep {
+                    package_id: dep.pkg.package_id().to_spec(),
+                    target: &dep.target,
+                    mode: dep.mode,
+                    cause: cause(dep_reason, ws_root),
+                })
+                .collect();
+            let msg = machine_message::RebuildReason {
+                package_id: unit.pkg.package_id().to_spec(),
+                target: &unit.target,
+                mode: unit.mode,
+                cause: cause(reason, ws_root),
+                caused_by,
+            }
+            .to_json_string();
+            crate::drop_println!(gctx, "{}", msg);
+        } else {
+            let mut shell = gctx.shell();
+            shell.status(
+                "Dirty",
+                format_args!(
+                    "{}{}: {}",
+                    unit.pkg,
+                    unit.target_description(),
+                    reason.description(ws_root)
+                ),
+            )?;
+            for (dep, dep_reason) in &chain {
+                shell.note(format_args!(
+                    "caused by {}{}: {}",
+                    dep.pkg,
+                    dep.target_description(),
+                    dep_reason.description(ws_root)
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
     fn back_compat_notice(
         &self,
         build_runner: &BuildRunner<'_, '_>,
@@ -0,0 +1,143 @@
+//! Scheduling policies of the job queue, see [`SchedulingPolicy`].
+//!
+//! The policy decides the cost of each unit, and how the priority of a unit
+//! is derived from the costs of the units depending on it. Whenever a
+//! jobserver token becomes available, the ready unit with the highest priority
+//! is started.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::bail;
+use serde::Deserialize;
+use tracing::debug;
+
+use super::{Fresh, Job};
+use crate::core::compiler::timings::{self, TimingData};
+use crate::core::compiler::{BuildContext, Unit};
+use crate::util::{CargoResult, PriorityMode};
+
+/// The cost of a unit when nothing better is known.
+const DEFAULT_COST: usize = 100;
+
+/// How the job queue prioritizes units that are ready to be built, selected
+/// with `-Zscheduling`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SchedulingPolicy {
+    /// Every unit has the same cost, and units with the most dependents,
+    /// including transitively, are started first.
+    #[default]
+    Dependents,
+    /// The cost of a unit is how long it took to build according to the
+    /// timing data saved by the last build with `--timings=json-file`, and
+    /// units starting the longest chain of builds are started first.
+    CriticalPath,
+}
+
+impl SchedulingPolicy {
+    /// The accepted values, as shown in error messages.
+    pub const EXPECTED: &'static str = "one of `dependents` or `critical-path`";
+}
+
+impl FromStr for SchedulingPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> CargoResult<SchedulingPolicy> {
+        match s {
+            "dependents" => Ok(SchedulingPolicy::Dependents),
+            "critical-path" => Ok(SchedulingPolicy::CriticalPath),
+            _ => bail!(
+                "unknown scheduling policy `{s}`, expected {}",
+                SchedulingPolicy::EXPECTED
+            ),
+        }
+    }
+}
+
+/// The costs of units for the selected [`SchedulingPolicy`].
+pub struct UnitCosts {
+    policy: SchedulingPolicy,
+    /// Durations in milliseconds of the units of a previous build, keyed by
+    /// [`timings::unit_key`].
+    durations: HashMap<(String, String, String, String), usize>,
+    /// The cost of units that weren't built by the previous build.
+    default_cost: usize,
+}
+
+impl UnitCosts {
+    /// Creates the costs for the policy selected with `-Zscheduling`, using
+    /// the timing data of a `previous` build for the `critical-path` policy.
+    pub fn new(bcx: &BuildContext<'_, '_>, previous: Option<&TimingData>) -> UnitCosts {
+        let policy = bcx.gctx.cli_unstable().scheduling.unwrap_or_default();
+        let mut costs = UnitCosts {
+            policy,
+            durations: HashMap::new(),
+            default_cost: DEFAULT_COST,
+        };
+        if policy == SchedulingPolicy::CriticalPath {
+            if let Some(previous) = previous {
+                costs.load_durations(previous);
+            }
+        }
+        costs
+    }
+
+    /// Whether the timing data of the previous build is used.
+    pub fn uses_timing_data(bcx: &BuildContext<'_, '_>) -> bool {
+        bcx.gctx.cli_unstable().scheduling == Some(SchedulingPolicy::CriticalPath)
+    }
+
+    /// Loads the unit durations from the timing data of a previous build.
+    fn load_durations(&mut self, data: &TimingData) {
+        for unit in &data.units {
+            let (package_id, target, mode, kind) = unit.key();
+            let duration = (unit.duration * 1000.0).round() as usize;
+            let key = (
+                package_id.to_string(),
+                target.to_string(),
+                mode.to_string(),
+                kind.to_string(),
+            );
+            // Units with different features have the same key, keep the
+            // slowest one.
+            let cost = self.durations.entry(key).or_default();
+            *cost = (*cost).max(duration);
+        }
+        if !self.durations.is_empty() {
+            let total: usize = self.durations.values().sum();
+            self.default_cost = total / self.durations.len();
+        }
+        debug!(
+            "loaded {} unit durations, default cost {}",
+            self.durations.len(),
+            self.default_cost
+        );
+    }
+
+    /// How priorities are derived from the costs of units.
+    pub fn priority_mode(&self) -> PriorityMode {
+        match self.policy {
+            SchedulingPolicy::Dependents => PriorityMode::TotalCost,
+            SchedulingPolicy::CriticalPath => PriorityMode::LongestPath,
+        }
+    }
+
+    /// The expected cost of running `job` for `unit`.
+    pub fn cost(&self, bcx: &BuildContext<'_, '_>, unit: &Unit, job: &Job) -> usize {
+        match self.policy {
+            SchedulingPolicy::Dependents => DEFAULT_COST,
+            SchedulingPolicy::CriticalPath => {
+                // Fresh units finish right away.
+                if let Fresh = job.freshness() {
+                    return 0;
+                }
+                self.durations
+                    .get(&timings::unit_key(bcx, unit))
+                    .copied()
+                    .unwrap_or(self.default_cost)
+                    .max(1)
+            }
+        }
+    }
+}
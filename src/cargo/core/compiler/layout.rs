@@ -100,10 +100,18 @@
 //!
 //! When cross-compiling, the layout is the same, except it appears in
 //! `target/$TRIPLE`.
+//!
+//! With `-Zshared-deps`, units of registry and git dependencies are placed in
+//! a directory shared by all workspaces instead, `$CARGO_HOME/shared-deps` by
+//! default. It has a layout like the above for each combination of target and
+//! `RUSTFLAGS`, in `shared-deps/$TRIPLE/$FLAGS_HASH`, but only uses the
+//! `.fingerprint`, `deps` and `build` directories. All files in there are named
+//! after the metadata hash of their unit, so units built for different
+//! workspaces, profiles or features don't collide. See [`shared_deps_root`].
 
 use crate::core::compiler::CompileTarget;
 use crate::core::Workspace;
-use crate::util::{CargoResult, FileLock};
+use crate::util::{CargoResult, FileLock, Filesystem, GlobalContext};
 use cargo_util::paths;
 use std::path::{Path, PathBuf};
 
@@ -154,6 +162,26 @@ impl Layout {
             root.push(target.short_name());
         }
         let dest = root.join(dest);
+        Layout::at(ws.gctx(), root, dest, "build directory")
+    }
+
+    /// Calculate the paths for build output in the directory shared by all
+    /// workspaces with `-Zshared-deps`, and lock it.
+    ///
+    /// `target` is the short name of the target, and `flags_hash` a hash of
+    /// the `RUSTFLAGS` used for it.
+    pub fn shared(gctx: &GlobalContext, target: &str, flags_hash: &str) -> CargoResult<Layout> {
+        let root = shared_deps_root(gctx)?.join(target);
+        let dest = root.join(flags_hash);
+        Layout::at(gctx, root, dest, "shared build directory")
+    }
+
+    fn at(
+        gctx: &GlobalContext,
+        root: Filesystem,
+        dest: Filesystem,
+        description: &str,
+    ) -> CargoResult<Layout> {
         // If the root directory doesn't already exist go ahead and create it
         // here. Use this opportunity to exclude it from backups as well if the
         // system supports it since this is a freshly created folder.
@@ -166,7 +194,7 @@ impl Layout {
         // For now we don't do any more finer-grained locking on the artifact
         // directory, so just lock the entire thing for the duration of this
         // compile.
-        let lock = dest.open_rw_exclusive_create(".cargo-lock", ws.gctx(), "build directory")?;
+        let lock = dest.open_rw_exclusive_create(".cargo-lock", gctx, description)?;
         let root = root.into_path_unlocked();
         let dest = dest.into_path_unlocked();
         let deps = dest.join("deps");
@@ -240,3 +268,14 @@ impl Layout {
         Ok(&self.tmp)
     }
 }
+
+/// Returns the directory shared by all workspaces for the units of
+/// dependencies, with `-Zshared-deps`.
+///
+/// This is `build.shared-deps-dir` if set, or `$CARGO_HOME/shared-deps`.
+pub fn shared_deps_root(gctx: &GlobalContext) -> CargoResult<Filesystem> {
+    Ok(match &gctx.build_config()?.shared_deps_dir {
+        Some(dir) => Filesystem::new(dir.resolve_path(gctx)),
+        None => gctx.home().join("shared-deps"),
+    })
+}
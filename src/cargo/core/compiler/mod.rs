@@ -32,9 +32,10 @@
 //! [`ops::cargo_compile::compile`]: crate::ops::compile
 
 pub mod artifact;
+mod build_cache;
 mod build_config;
 pub(crate) mod build_context;
-mod build_plan;
+pub mod build_plan;
 pub(crate) mod build_runner;
 mod compilation;
 mod compile_kind;
@@ -49,7 +50,7 @@ mod lto;
 mod output_depinfo;
 pub mod rustdoc;
 pub mod standard_lib;
-mod timings;
+pub mod timings;
 mod unit;
 pub mod unit_dependencies;
 pub mod unit_graph;
@@ -79,7 +80,7 @@ pub use self::crate_type::CrateType;
 pub use self::custom_build::LinkArgTarget;
 pub use self::custom_build::{BuildOutput, BuildScriptOutputs, BuildScripts};
 pub(crate) use self::fingerprint::DirtyReason;
-pub use self::job_queue::Freshness;
+pub use self::job_queue::{Freshness, SchedulingPolicy};
 use self::job_queue::{Job, JobQueue, JobState, Work};
 pub(crate) use self::layout::Layout;
 pub use self::lto::Lto;
@@ -195,7 +196,15 @@ fn compile<'gctx>(
             let work = if unit.mode.is_doc() || unit.mode.is_doc_scrape() {
                 rustdoc(build_runner, unit)?
             } else {
-                rustc(build_runner, unit, exec)?
+                let work = rustc(build_runner, unit, exec)?;
+                match build_runner.build_cache.take() {
+                    Some(mut cache) => {
+                        let work = cache.wrap(build_runner, unit, work);
+                        build_runner.build_cache = Some(cache);
+                        work?
+                    }
+                    None => work,
+                }
             };
             work.then(link_targets(build_runner, unit, false)?)
         } else {
@@ -275,16 +284,7 @@ fn rustc(
     // don't pass the `-l` flags.
     let pass_l_flag = unit.target.is_lib() || !unit.pkg.targets().iter().any(|t| t.is_lib());
 
-    let dep_info_name = if build_runner.files().use_extra_filename(unit) {
-        format!(
-            "{}-{}.d",
-            unit.target.crate_name(),
-            build_runner.files().metadata(unit)
-        )
-    } else {
-        format!("{}.d", unit.target.crate_name())
-    };
-    let rustc_dep_info_loc = root.join(dep_info_name);
+    let rustc_dep_info_loc = rustc_dep_info_loc(build_runner, unit);
     let dep_info_loc = fingerprint::dep_info_loc(build_runner, unit);
 
     let mut output_options = OutputOptions::new(build_runner, unit);
@@ -296,6 +296,7 @@ fn rustc(
     let exec = exec.clone();
 
     let root_output = build_runner.files().host_dest().to_path_buf();
+    let shared_outputs = build_runner.files().shared_dests();
     let target_dir = build_runner.bcx.ws.target_dir().into_path_unlocked();
     let pkg_root = unit.pkg.root().to_path_buf();
     let cwd = rustc
@@ -356,7 +357,13 @@ fn rustc(
                     &target,
                     current_id,
                 )?;
-                add_plugin_deps(&mut rustc, &script_outputs, &build_scripts, &root_output)?;
+                add_plugin_deps(
+                    &mut rustc,
+                    &script_outputs,
+                    &build_scripts,
+                    &root_output,
+                    &shared_outputs,
+                )?;
             }
             add_custom_flags(&mut rustc, &script_outputs, script_metadata)?;
         }
@@ -530,6 +537,20 @@ fn verbose_if_simple_exit_code(err: Error) -> Error {
     }
 }
 
+/// Returns the path of the dep-info file rustc writes when compiling `unit`.
+fn rustc_dep_info_loc(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> PathBuf {
+    let dep_info_name = if build_runner.files().use_extra_filename(unit) {
+        format!(
+            "{}-{}.d",
+            unit.target.crate_name(),
+            build_runner.files().metadata(unit)
+        )
+    } else {
+        format!("{}.d", unit.target.crate_name())
+    };
+    build_runner.files().out_dir(unit).join(dep_info_name)
+}
+
 /// Link the compiled target (often of form `foo-{metadata_hash}`) to the
 /// final target. This must happen during both "Fresh" and "Compile".
 fn link_targets(
@@ -629,6 +650,7 @@ fn add_plugin_deps(
     build_script_outputs: &BuildScriptOutputs,
     build_scripts: &BuildScripts,
     root_output: &Path,
+    shared_outputs: &[PathBuf],
 ) -> CargoResult<()> {
     let var = paths::dylib_path_envvar();
     let search_path = rustc.get_env(var).unwrap_or_default();
@@ -640,6 +662,7 @@ fn add_plugin_deps(
         search_path.append(&mut filter_dynamic_search_path(
             output.library_paths.iter(),
             root_output,
+            shared_outputs,
         ));
     }
     let search_path = paths::join_paths(&search_path, var)?;
@@ -651,8 +674,13 @@ fn add_plugin_deps(
 //
 // Strip off prefixes like "native=" or "framework=" and filter out directories
 // **not** inside our output directory since they are likely spurious and can cause
-// clashes with system shared libraries (issue #3366).
-fn filter_dynamic_search_path<'a, I>(paths: I, root_output: &Path) -> Vec<PathBuf>
+// clashes with system shared libraries (issue #3366). Directories of the build
+// directory shared with `-Zshared-deps` are kept as well.
+fn filter_dynamic_search_path<'a, I>(
+    paths: I,
+    root_output: &Path,
+    shared_outputs: &[PathBuf],
+) -> Vec<PathBuf>
 where
     I: Iterator<Item = &'a PathBuf>,
 {
@@ -662,7 +690,7 @@ where
             Some(("native" | "crate" | "dependency" | "framework" | "all", path)) => path.into(),
             _ => dir.clone(),
         };
-        if dir.starts_with(&root_output) {
+        if dir.starts_with(&root_output) || shared_outputs.iter().any(|out| dir.starts_with(out)) {
             search_path.push(dir);
         } else {
             debug!(
@@ -1462,6 +1490,15 @@ fn build_deps_args(
         });
     }
 
+    // Transitive dependencies may be in the shared build directory.
+    for dir in build_runner.files().shared_deps_dirs() {
+        if dir != build_runner.files().deps_dir(unit) {
+            let mut deps = OsString::from("dependency=");
+            deps.push(dir);
+            cmd.arg("-L").arg(deps);
+        }
+    }
+
     let deps = build_runner.unit_deps(unit);
 
     // If there is not one linkable target but should, rustc fails later
@@ -0,0 +1,226 @@
+//! Critical path analysis of a finished build.
+//!
+//! The build is modeled as a graph of units where each unit can start as soon
+//! as all of its dependencies are available, assuming unlimited parallelism.
+//! A dependency is available either when it finishes, or, for pipelined
+//! builds where the dependent only needs metadata, when its `.rmeta` file is
+//! generated.
+//!
+//! The critical path is the longest chain of dependencies through this graph.
+//! Units on it have no slack: making any of them slower makes the whole build
+//! slower. Units off the critical path can take up to their slack longer
+//! without affecting the build time.
+
+/// A unit of the analyzed build.
+pub struct Node {
+    /// Total time to build the unit.
+    pub duration: f64,
+    /// When the `.rmeta` file was generated, as an offset from the start of
+    /// the unit.
+    pub rmeta_time: Option<f64>,
+    /// Indices of the dependencies of this unit, and whether only their
+    /// `.rmeta` file is needed.
+    pub deps: Vec<(usize, bool)>,
+}
+
+/// The result of [`analyze`].
+pub struct CriticalPath {
+    /// The length of the critical path in seconds.
+    pub duration: f64,
+    /// Indices of the units on the critical path, in build order.
+    pub units: Vec<usize>,
+    /// How much longer each unit could take without making the build slower.
+    pub slack: Vec<f64>,
+    /// For units on the critical path, how much shorter the critical path
+    /// would be if the unit took no time at all. This is an upper bound on the
+    /// speedup from making that unit faster.
+    pub max_speedup: Vec<Option<f64>>,
+}
+
+/// Slack below this is considered zero, to account for rounding errors.
+const EPSILON: f64 = 1e-6;
+
+/// Analyzes the critical path of a build.
+///
+/// `nodes` must not contain dependency cycles.
+pub fn analyze(nodes: &[Node]) -> CriticalPath {
+    let order = topological_order(nodes);
+    let durations: Vec<f64> = nodes.iter().map(|n| n.duration).collect();
+    let (earliest_start, length) = earliest_starts(nodes, &order, &durations);
+
+    // Walk backwards from the unit finishing last, following the dependency
+    // that determined each unit's start time.
+    let mut units = Vec::new();
+    let last = (0..nodes.len()).max_by(|&a, &b| {
+        let a = earliest_start[a] + durations[a];
+        let b = earliest_start[b] + durations[b];
+        a.total_cmp(&b)
+    });
+    let mut current = last;
+    while let Some(i) = current {
+        units.push(i);
+        current = nodes[i]
+            .deps
+            .iter()
+            .map(|&(dep, rmeta)| {
+                (
+                    dep,
+                    available_at(nodes, &earliest_start, &durations, dep, rmeta),
+                )
+            })
+            .filter(|&(_, t)| (t - earliest_start[i]).abs() < EPSILON)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(dep, _)| dep);
+    }
+    units.reverse();
+
+    // Compute the latest start of each unit that doesn't delay the build.
+    let mut latest_start: Vec<f64> = nodes.iter().map(|n| length - n.duration).collect();
+    for &i in order.iter().rev() {
+        for &(dep, rmeta) in &nodes[i].deps {
+            let needed = match (rmeta, nodes[dep].rmeta_time) {
+                (true, Some(rmeta_time)) => rmeta_time,
+                _ => nodes[dep].duration,
+            };
+            latest_start[dep] = latest_start[dep].min(latest_start[i] - needed);
+        }
+    }
+    let slack = (0..nodes.len())
+        .map(|i| {
+            let slack = latest_start[i] - earliest_start[i];
+            if slack < EPSILON {
+                0.0
+            } else {
+                slack
+            }
+        })
+        .collect();
+
+    let mut max_speedup = vec![None; nodes.len()];
+    for &i in &units {
+        let mut durations = durations.clone();
+        durations[i] = 0.0;
+        let (_, shortened) = earliest_starts(nodes, &order, &durations);
+        max_speedup[i] = Some((length - shortened).max(0.0));
+    }
+
+    CriticalPath {
+        duration: length,
+        units,
+        slack,
+        max_speedup,
+    }
+}
+
+/// Returns when the dependency `dep` is available to its dependents.
+///
+/// `durations` overrides the durations of the nodes. If a node's duration is
+/// overridden, its `.rmeta` file is assumed to be generated proportionally
+/// earlier.
+fn available_at(
+    nodes: &[Node],
+    earliest_start: &[f64],
+    durations: &[f64],
+    dep: usize,
+    rmeta: bool,
+) -> f64 {
+    let node = &nodes[dep];
+    let needed = match (rmeta, node.rmeta_time) {
+        (true, Some(rmeta_time)) if node.duration > 0.0 => {
+            rmeta_time * durations[dep] / node.duration
+        }
+        _ => durations[dep],
+    };
+    earliest_start[dep] + needed
+}
+
+/// Computes the earliest start of every unit, and the length of the build.
+fn earliest_starts(nodes: &[Node], order: &[usize], durations: &[f64]) -> (Vec<f64>, f64) {
+    let mut earliest_start = vec![0.0; nodes.len()];
+    let mut length: f64 = 0.0;
+    for &i in order {
+        let start = nodes[i]
+            .deps
+            .iter()
+            .map(|&(dep, rmeta)| available_at(nodes, &earliest_start, durations, dep, rmeta))
+            .fold(0.0, f64::max);
+        earliest_start[i] = start;
+        length = length.max(start + durations[i]);
+    }
+    (earliest_start, length)
+}
+
+/// Orders the nodes so that every node comes after its dependencies.
+fn topological_order(nodes: &[Node]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(nodes.len());
+    let mut visited = vec![false; nodes.len()];
+    for root in 0..nodes.len() {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        // Iterative depth-first search, with the index of the next dependency
+        // to visit for each node on the stack.
+        let mut stack = vec![(root, 0)];
+        while let Some((i, next)) = stack.pop() {
+            match nodes[i].deps.get(next) {
+                Some(&(dep, _)) => {
+                    stack.push((i, next + 1));
+                    if !visited[dep] {
+                        visited[dep] = true;
+                        stack.push((dep, 0));
+                    }
+                }
+                None => order.push(i),
+            }
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(duration: f64, rmeta_time: Option<f64>, deps: &[(usize, bool)]) -> Node {
+        Node {
+            duration,
+            rmeta_time,
+            deps: deps.to_vec(),
+        }
+    }
+
+    #[test]
+    fn chain_and_branch() {
+        // 0 -> 1 -> 3
+        // 0 -> 2 -> 3
+        let nodes = [
+            node(2.0, None, &[]),
+            node(5.0, None, &[(0, false)]),
+            node(1.0, None, &[(0, false)]),
+            node(1.0, None, &[(1, false), (2, false)]),
+        ];
+        let cp = analyze(&nodes);
+        assert_eq!(cp.duration, 8.0);
+        assert_eq!(cp.units, [0, 1, 3]);
+        assert_eq!(cp.slack, [0.0, 0.0, 4.0, 0.0]);
+        assert_eq!(cp.max_speedup, [Some(2.0), Some(4.0), None, Some(1.0)]);
+    }
+
+    #[test]
+    fn pipelined() {
+        // 1 only needs the metadata of 0.
+        let nodes = [node(4.0, Some(1.0), &[]), node(2.0, None, &[(0, true)])];
+        let cp = analyze(&nodes);
+        assert_eq!(cp.duration, 4.0);
+        assert_eq!(cp.units, [0]);
+        assert_eq!(cp.slack, [0.0, 1.0]);
+    }
+
+    #[test]
+    fn empty() {
+        let cp = analyze(&[]);
+        assert_eq!(cp.duration, 0.0);
+        assert!(cp.units.is_empty());
+    }
+}
@@ -2,7 +2,11 @@
 //!
 //! This module implements some simple tracking information for timing of how
 //! long it takes for different units to compile.
-use super::{CompileMode, Unit};
+
+mod critical_path;
+
+use self::critical_path::CriticalPath;
+use super::Unit;
 use crate::core::compiler::job_queue::JobId;
 use crate::core::compiler::{BuildContext, BuildRunner, TimingOutput};
 use crate::core::PackageId;
@@ -10,10 +14,11 @@ use crate::util::cpu::State;
 use crate::util::machine_message::{self, Message};
 use crate::util::style;
 use crate::util::{CargoResult, GlobalContext};
-use anyhow::Context as _;
+use anyhow::{bail, Context as _};
 use cargo_util::paths;
 use std::collections::HashMap;
 use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::thread::available_parallelism;
 use std::time::{Duration, Instant, SystemTime};
 
@@ -32,6 +37,10 @@ pub struct Timings<'gctx> {
     report_html: bool,
     /// If true, emits JSON information with timing information.
     report_json: bool,
+    /// If true, saves the full timing data as a JSON file to disk.
+    report_json_file: bool,
+    /// If true, saves a Chrome trace-event file to disk.
+    report_trace: bool,
     /// When Cargo started.
     start: Instant,
     /// A rendered string of when compilation started.
@@ -61,6 +70,8 @@ pub struct Timings<'gctx> {
     /// recording was taken and second element is percentage usage of the
     /// system.
     cpu_usage: Vec<(f64, f64)>,
+    /// Critical path analysis, computed when the build is finished.
+    critical_path: Option<CriticalPath>,
 }
 
 /// Tracking information for an individual unit.
@@ -79,20 +90,179 @@ struct UnitTime {
     unlocked_units: Vec<Unit>,
     /// Same as `unlocked_units`, but unlocked by rmeta.
     unlocked_rmeta_units: Vec<Unit>,
+    /// Peak memory used by the processes of this unit, in bytes, if measured.
+    peak_memory: Option<u64>,
 }
 
 /// Periodic concurrency tracking information.
-#[derive(serde::Serialize)]
-struct Concurrency {
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Concurrency {
     /// Time as an offset in seconds from `Timings::start`.
-    t: f64,
+    pub t: f64,
     /// Number of units currently running.
-    active: usize,
+    pub active: usize,
     /// Number of units that could run, but are waiting for a jobserver token.
-    waiting: usize,
+    pub waiting: usize,
     /// Number of units that are not yet ready, because they are waiting for
     /// dependencies to finish.
-    inactive: usize,
+    pub inactive: usize,
+}
+
+/// The current version of [`TimingData`].
+pub const TIMING_DATA_VERSION: u32 = 1;
+
+/// The complete timing data of a build, as saved by `--timings=json-file`.
+///
+/// All times are in seconds. Unit start times and concurrency/CPU samples are
+/// offsets from the start of the build.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct TimingData {
+    /// Format version, see [`TIMING_DATA_VERSION`].
+    pub version: u32,
+    /// When compilation started, in RFC 3339 format.
+    pub start: String,
+    /// The build profile.
+    pub profile: String,
+    /// Total wall-clock time of the build.
+    pub duration: f64,
+    /// The root packages and their targets.
+    pub roots: Vec<TimingRoot>,
+    /// Total number of fresh units.
+    pub fresh_units: u32,
+    /// Total number of dirty units.
+    pub dirty_units: u32,
+    /// The number of jobs the build was allowed to run in parallel.
+    pub jobs: u32,
+    /// The first line of `rustc -vV`.
+    pub rustc: String,
+    /// The host triple.
+    pub host: String,
+    /// The error the build failed with, if any.
+    pub error: Option<String>,
+    /// Every unit that was built, sorted by start time.
+    pub units: Vec<TimingUnit>,
+    /// Concurrency samples taken while the build progressed.
+    pub concurrency: Vec<Concurrency>,
+    /// CPU usage samples as `(time, percent busy)`.
+    pub cpu_usage: Vec<(f64, f64)>,
+    /// Length of the critical path, assuming unlimited parallelism.
+    #[serde(default)]
+    pub critical_path_duration: f64,
+    /// Indices of the units on the critical path, in build order.
+    #[serde(default)]
+    pub critical_path: Vec<usize>,
+}
+
+/// A root package of a build in [`TimingData`].
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct TimingRoot {
+    /// Package name and version.
+    pub package: String,
+    /// Descriptions of the requested targets.
+    pub targets: Vec<String>,
+}
+
+/// Timing information for a single unit in [`TimingData`].
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct TimingUnit {
+    /// Index of this unit in [`TimingData::units`].
+    pub i: usize,
+    pub package_id: String,
+    pub name: String,
+    pub version: String,
+    /// Description of the target and mode, empty for plain libraries.
+    pub target: String,
+    pub mode: String,
+    pub kind: String,
+    pub features: Vec<String>,
+    pub start: f64,
+    pub duration: f64,
+    /// When the `.rmeta` file was generated, as an offset from `start`.
+    pub rmeta_time: Option<f64>,
+    /// Indices of units that were unlocked when this unit finished.
+    pub unlocked_units: Vec<usize>,
+    /// Indices of units that were unlocked when this unit's `.rmeta` was generated.
+    pub unlocked_rmeta_units: Vec<usize>,
+    /// How much longer this unit could have taken without delaying the build.
+    #[serde(default)]
+    pub slack: f64,
+    /// For units on the critical path, how much faster the build could have
+    /// been at most if this unit took no time.
+    #[serde(default)]
+    pub max_speedup: Option<f64>,
+    /// Peak memory used by the processes of this unit in bytes, only
+    /// measured with `-Zmemory-limit` on Linux.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory: Option<u64>,
+}
+
+impl TimingData {
+    /// Loads the timing data saved to `path` by a previous build.
+    pub fn load(path: &Path) -> CargoResult<TimingData> {
+        let contents = paths::read(path)?;
+        let data: TimingData = serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse timing data `{}`", path.display()))?;
+        if data.version != TIMING_DATA_VERSION {
+            bail!(
+                "timing data `{}` has unsupported version {} (expected {})",
+                path.display(),
+                data.version,
+                TIMING_DATA_VERSION
+            );
+        }
+        Ok(data)
+    }
+}
+
+impl TimingUnit {
+    /// A key identifying the same unit across builds.
+    pub fn key(&self) -> (&str, &str, &str, &str) {
+        (&self.package_id, &self.target, &self.mode, &self.kind)
+    }
+}
+
+/// Loads the timing data saved by the last build with `--timings=json-file`,
+/// if there is any.
+///
+/// Data that can't be read is ignored with a warning.
+pub fn load_previous(bcx: &BuildContext<'_, '_>) -> Option<TimingData> {
+    let path = bcx
+        .ws
+        .target_dir()
+        .as_path_unlocked()
+        .join("cargo-timings")
+        .join("cargo-timing.json");
+    if !path.exists() {
+        tracing::debug!("no timing data at {:?}", path);
+        return None;
+    }
+    match TimingData::load(&path) {
+        Ok(data) => Some(data),
+        Err(e) => {
+            let _ = bcx
+                .gctx
+                .shell()
+                .warn(format!("ignoring timing data of the previous build: {e:#}"));
+            None
+        }
+    }
+}
+
+/// The values of [`TimingUnit::key`] for `unit`.
+pub fn unit_key(bcx: &BuildContext<'_, '_>, unit: &Unit) -> (String, String, String, String) {
+    (
+        unit.pkg.package_id().to_spec().to_string(),
+        unit.target_description(),
+        mode_name(unit),
+        bcx.target_data.short_name(&unit.kind).to_string(),
+    )
+}
+
+fn mode_name(unit: &Unit) -> String {
+    serde_json::to_value(unit.mode)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
 }
 
 impl<'gctx> Timings<'gctx> {
@@ -100,7 +270,9 @@ impl<'gctx> Timings<'gctx> {
         let has_report = |what| bcx.build_config.timing_outputs.contains(&what);
         let report_html = has_report(TimingOutput::Html);
         let report_json = has_report(TimingOutput::Json);
-        let enabled = report_html | report_json;
+        let report_json_file = has_report(TimingOutput::JsonFile);
+        let report_trace = has_report(TimingOutput::Trace);
+        let enabled = report_html | report_json | report_json_file | report_trace;
 
         let mut root_map: HashMap<PackageId, Vec<String>> = HashMap::new();
         for unit in root_units {
@@ -136,6 +308,8 @@ impl<'gctx> Timings<'gctx> {
             enabled,
             report_html,
             report_json,
+            report_json_file,
+            report_trace,
             start: bcx.gctx.creation_time(),
             start_str,
             root_targets,
@@ -148,6 +322,7 @@ impl<'gctx> Timings<'gctx> {
             last_cpu_state,
             last_cpu_recording: Instant::now(),
             cpu_usage: Vec::new(),
+            critical_path: None,
         }
     }
 
@@ -156,24 +331,7 @@ impl<'gctx> Timings<'gctx> {
         if !self.enabled {
             return;
         }
-        let mut target = if unit.target.is_lib() && unit.mode == CompileMode::Build {
-            // Special case for brevity, since most dependencies hit
-            // this path.
-            "".to_string()
-        } else {
-            format!(" {}", unit.target.description_named())
-        };
-        match unit.mode {
-            CompileMode::Test => target.push_str(" (test)"),
-            CompileMode::Build => {}
-            CompileMode::Check { test: true } => target.push_str(" (check-test)"),
-            CompileMode::Check { test: false } => target.push_str(" (check)"),
-            CompileMode::Bench => target.push_str(" (bench)"),
-            CompileMode::Doc { .. } => target.push_str(" (doc)"),
-            CompileMode::Doctest => target.push_str(" (doc test)"),
-            CompileMode::Docscrape => target.push_str(" (doc scrape)"),
-            CompileMode::RunCustomBuild => target.push_str(" (run)"),
-        }
+        let target = unit.target_description();
         let unit_time = UnitTime {
             unit,
             target,
@@ -182,10 +340,18 @@ impl<'gctx> Timings<'gctx> {
             rmeta_time: None,
             unlocked_units: Vec::new(),
             unlocked_rmeta_units: Vec::new(),
+            peak_memory: None,
         };
         assert!(self.active.insert(id, unit_time).is_none());
     }
 
+    /// Record the peak memory used by the processes of a unit.
+    pub fn unit_peak_memory(&mut self, id: JobId, bytes: u64) {
+        if let Some(unit_time) = self.active.get_mut(&id) {
+            unit_time.peak_memory = Some(bytes);
+        }
+    }
+
     /// Mark that the `.rmeta` file as generated.
     pub fn unit_rmeta_finished(&mut self, id: JobId, unlocked: Vec<&Unit>) {
         if !self.enabled {
@@ -297,13 +463,279 @@ impl<'gctx> Timings<'gctx> {
         self.mark_concurrency(0, 0, 0);
         self.unit_times
             .sort_unstable_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
+        self.critical_path = Some(self.analyze_critical_path(build_runner));
+        if self.report_json {
+            self.emit_critical_path();
+        }
         if self.report_html {
             self.report_html(build_runner, error)
                 .context("failed to save timing report")?;
         }
+        if self.report_json_file {
+            self.report_json_file(build_runner, error)
+                .context("failed to save timing data")?;
+        }
+        if self.report_trace {
+            self.report_trace(build_runner)
+                .context("failed to save timing trace")?;
+        }
+        Ok(())
+    }
+
+    /// Computes the critical path through the units that were built.
+    ///
+    /// Dependencies that were fresh are treated as available from the start
+    /// of the build.
+    fn analyze_critical_path(&self, build_runner: &BuildRunner<'_, '_>) -> CriticalPath {
+        let unit_map = self.unit_map();
+        let nodes: Vec<_> = self
+            .unit_times
+            .iter()
+            .map(|ut| {
+                let deps = build_runner
+                    .bcx
+                    .unit_graph
+                    .get(&ut.unit)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|dep| {
+                        let i = *unit_map.get(&dep.unit)?;
+                        Some((i, build_runner.only_requires_rmeta(&ut.unit, &dep.unit)))
+                    })
+                    .collect();
+                critical_path::Node {
+                    duration: ut.duration,
+                    rmeta_time: ut.rmeta_time,
+                    deps,
+                }
+            })
+            .collect();
+        critical_path::analyze(&nodes)
+    }
+
+    /// Emits the critical path analysis as a JSON message.
+    fn emit_critical_path(&self) {
+        let Some(cp) = &self.critical_path else {
+            return;
+        };
+        let units = self
+            .unit_times
+            .iter()
+            .enumerate()
+            .map(|(i, ut)| machine_message::TimingCriticalPathUnit {
+                package_id: ut.unit.pkg.package_id().to_spec(),
+                target: &ut.unit.target,
+                mode: ut.unit.mode,
+                critical: cp.units.contains(&i),
+                slack: cp.slack[i],
+                max_speedup: cp.max_speedup[i],
+            })
+            .collect();
+        let msg = machine_message::TimingCriticalPath {
+            duration: cp.duration,
+            units,
+        }
+        .to_json_string();
+        crate::drop_println!(self.gctx, "{}", msg);
+    }
+
+    /// Creates the `cargo-timings` directory, returning it along with the
+    /// timestamp used to name the reports of this build.
+    fn timings_dir(&self, build_runner: &BuildRunner<'_, '_>) -> CargoResult<(PathBuf, String)> {
+        let timestamp = self.start_str.replace(&['-', ':'][..], "");
+        let timings_path = build_runner.files().host_root().join("cargo-timings");
+        paths::create_dir_all(&timings_path)?;
+        Ok((timings_path, timestamp))
+    }
+
+    /// Links `filename` to its unstamped name and tells the user where the
+    /// report was saved.
+    fn report_saved(
+        &self,
+        filename: &Path,
+        unstamped_filename: &Path,
+        what: &str,
+    ) -> CargoResult<()> {
+        paths::link_or_copy(filename, unstamped_filename)?;
+
+        let mut shell = self.gctx.shell();
+        let timing_path = std::env::current_dir().unwrap_or_default().join(filename);
+        let link = shell.err_file_hyperlink(&timing_path);
+        let msg = format!("{what} saved to {link}{}{link:#}", timing_path.display(),);
+        shell.status_with_color("Timing", msg, &style::NOTE)?;
         Ok(())
     }
 
+    /// Builds the complete timing data of this build.
+    fn timing_data(&self, bcx: &BuildContext<'_, '_>, error: &Option<anyhow::Error>) -> TimingData {
+        let unit_map = self.unit_map();
+        let units = self
+            .unit_times
+            .iter()
+            .enumerate()
+            .map(|(i, ut)| {
+                let (unlocked_units, unlocked_rmeta_units) = ut.unlocked_indices(&unit_map);
+                TimingUnit {
+                    i,
+                    package_id: ut.unit.pkg.package_id().to_spec().to_string(),
+                    name: ut.unit.pkg.name().to_string(),
+                    version: ut.unit.pkg.version().to_string(),
+                    target: ut.target.clone(),
+                    mode: mode_name(&ut.unit),
+                    kind: bcx.target_data.short_name(&ut.unit.kind).to_string(),
+                    features: ut.unit.features.iter().map(|f| f.to_string()).collect(),
+                    start: ut.start,
+                    duration: ut.duration,
+                    rmeta_time: ut.rmeta_time,
+                    unlocked_units,
+                    unlocked_rmeta_units,
+                    slack: self.critical_path.as_ref().map_or(0.0, |cp| cp.slack[i]),
+                    max_speedup: self.critical_path.as_ref().and_then(|cp| cp.max_speedup[i]),
+                    peak_memory: ut.peak_memory,
+                }
+            })
+            .collect();
+        TimingData {
+            version: TIMING_DATA_VERSION,
+            start: self.start_str.clone(),
+            profile: self.profile.clone(),
+            duration: self.start.elapsed().as_secs_f64(),
+            roots: self
+                .root_targets
+                .iter()
+                .map(|(package, targets)| TimingRoot {
+                    package: package.clone(),
+                    targets: targets.clone(),
+                })
+                .collect(),
+            fresh_units: self.total_fresh,
+            dirty_units: self.total_dirty,
+            jobs: bcx.jobs(),
+            rustc: bcx
+                .rustc()
+                .verbose_version
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            host: bcx.rustc().host.to_string(),
+            error: error.as_ref().map(|e| format!("{e:#}")),
+            units,
+            concurrency: self.concurrency.clone(),
+            cpu_usage: self.cpu_usage.clone(),
+            critical_path_duration: self.critical_path.as_ref().map_or(0.0, |cp| cp.duration),
+            critical_path: self
+                .critical_path
+                .as_ref()
+                .map(|cp| cp.units.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Save the full timing data as JSON to disk.
+    fn report_json_file(
+        &self,
+        build_runner: &BuildRunner<'_, '_>,
+        error: &Option<anyhow::Error>,
+    ) -> CargoResult<()> {
+        let (timings_path, timestamp) = self.timings_dir(build_runner)?;
+        let filename = timings_path.join(format!("cargo-timing-{}.json", timestamp));
+        let data = self.timing_data(build_runner.bcx, error);
+        paths::write(&filename, serde_json::to_string(&data)?)?;
+        self.report_saved(&filename, &timings_path.join("cargo-timing.json"), "data")
+    }
+
+    /// Save a Chrome trace-event file to disk.
+    ///
+    /// Each unit is a complete (`X`) event. Units are spread over as few
+    /// "threads" as possible without overlapping, so the trace looks similar
+    /// to the unit graph of the HTML report. Concurrency and CPU usage are
+    /// written as counter (`C`) events.
+    ///
+    /// See <https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU>
+    /// for a description of the format.
+    fn report_trace(&self, build_runner: &BuildRunner<'_, '_>) -> CargoResult<()> {
+        let (timings_path, timestamp) = self.timings_dir(build_runner)?;
+        let filename = timings_path.join(format!("cargo-timing-{}.trace.json", timestamp));
+        let micros = |t: f64| (t * 1_000_000.0).round() as u64;
+        let mut events = Vec::new();
+        // End times of the units currently placed on each lane.
+        let mut lanes: Vec<f64> = Vec::new();
+        for ut in &self.unit_times {
+            let lane = match lanes.iter().position(|&end| end <= ut.start) {
+                Some(lane) => lane,
+                None => {
+                    lanes.push(0.0);
+                    lanes.len() - 1
+                }
+            };
+            lanes[lane] = ut.start + ut.duration;
+            let name = format!("{}{}", ut.name_ver(), ut.target);
+            events.push(serde_json::json!({
+                "name": name,
+                "cat": if ut.unit.mode.is_run_custom_build() { "build-script" } else { "unit" },
+                "ph": "X",
+                "ts": micros(ut.start),
+                "dur": micros(ut.duration),
+                "pid": 1,
+                "tid": lane,
+                "args": {
+                    "package_id": ut.unit.pkg.package_id().to_spec().to_string(),
+                    "mode": ut.unit.mode,
+                    "features": ut.unit.features,
+                    "rmeta_time": ut.rmeta_time,
+                },
+            }));
+            if let Some(rmeta_time) = ut.rmeta_time {
+                events.push(serde_json::json!({
+                    "name": format!("{name} rmeta"),
+                    "cat": "rmeta",
+                    "ph": "i",
+                    "s": "t",
+                    "ts": micros(ut.start + rmeta_time),
+                    "pid": 1,
+                    "tid": lane,
+                }));
+            }
+        }
+        for c in &self.concurrency {
+            events.push(serde_json::json!({
+                "name": "concurrency",
+                "ph": "C",
+                "ts": micros(c.t),
+                "pid": 1,
+                "args": {
+                    "active": c.active,
+                    "waiting": c.waiting,
+                    "inactive": c.inactive,
+                },
+            }));
+        }
+        for &(t, usage) in &self.cpu_usage {
+            events.push(serde_json::json!({
+                "name": "cpu usage",
+                "ph": "C",
+                "ts": micros(t),
+                "pid": 1,
+                "args": { "percent": usage },
+            }));
+        }
+        let trace = serde_json::json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+            "otherData": {
+                "start": self.start_str,
+                "profile": self.profile,
+            },
+        });
+        paths::write(&filename, serde_json::to_string(&trace)?)?;
+        self.report_saved(
+            &filename,
+            &timings_path.join("cargo-timing.trace.json"),
+            "trace",
+        )
+    }
+
     /// Save HTML report to disk.
     fn report_html(
         &self,
@@ -311,9 +743,7 @@ impl<'gctx> Timings<'gctx> {
         error: &Option<anyhow::Error>,
     ) -> CargoResult<()> {
         let duration = self.start.elapsed().as_secs_f64();
-        let timestamp = self.start_str.replace(&['-', ':'][..], "");
-        let timings_path = build_runner.files().host_root().join("cargo-timings");
-        paths::create_dir_all(&timings_path)?;
+        let (timings_path, timestamp) = self.timings_dir(build_runner)?;
         let filename = timings_path.join(format!("cargo-timing-{}.html", timestamp));
         let mut f = BufWriter::new(paths::create(&filename)?);
         let roots: Vec<&str> = self
@@ -344,16 +774,7 @@ impl<'gctx> Timings<'gctx> {
         )?;
         drop(f);
 
-        let unstamped_filename = timings_path.join("cargo-timing.html");
-        paths::link_or_copy(&filename, &unstamped_filename)?;
-
-        let mut shell = self.gctx.shell();
-        let timing_path = std::env::current_dir().unwrap_or_default().join(&filename);
-        let link = shell.err_file_hyperlink(&timing_path);
-        let msg = format!("report saved to {link}{}{link:#}", timing_path.display(),);
-        shell.status_with_color("Timing", msg, &style::NOTE)?;
-
-        Ok(())
+        self.report_saved(&filename, &timings_path.join("cargo-timing.html"), "report")
     }
 
     /// Render the summary table.
@@ -380,6 +801,10 @@ impl<'gctx> Timings<'gctx> {
         let num_cpus = available_parallelism()
             .map(|x| x.get().to_string())
             .unwrap_or_else(|_| "n/a".into());
+        let critical_path = match &self.critical_path {
+            Some(cp) => format!("{:.1}s ({} units)", cp.duration, cp.units.len()),
+            None => "n/a".to_string(),
+        };
         let rustc_info = render_rustc_info(bcx);
         let error_msg = match error {
             Some(e) => format!(r#"<tr><td class="error-text">Error:</td><td>{e}</td></tr>"#),
@@ -413,6 +838,9 @@ impl<'gctx> Timings<'gctx> {
   <tr>
     <td>Total time:</td><td>{}</td>
   </tr>
+  <tr>
+    <td>Critical path:</td><td>{}</td>
+  </tr>
   <tr>
     <td>rustc:</td><td>{}</td>
   </tr>
@@ -429,6 +857,7 @@ impl<'gctx> Timings<'gctx> {
             num_cpus,
             self.start_str,
             total_time,
+            critical_path,
             rustc_info,
             error_msg,
         )?;
@@ -438,13 +867,7 @@ impl<'gctx> Timings<'gctx> {
     /// Write timing data in JavaScript. Primarily for `timings.js` to put data
     /// in a `<script>` HTML element to draw graphs.
     fn write_js_data(&self, f: &mut impl Write) -> CargoResult<()> {
-        // Create a map to link indices of unlocked units.
-        let unit_map: HashMap<Unit, usize> = self
-            .unit_times
-            .iter()
-            .enumerate()
-            .map(|(i, ut)| (ut.unit.clone(), i))
-            .collect();
+        let unit_map = self.unit_map();
         #[derive(serde::Serialize)]
         struct UnitData {
             i: usize,
@@ -457,6 +880,7 @@ impl<'gctx> Timings<'gctx> {
             rmeta_time: Option<f64>,
             unlocked_units: Vec<usize>,
             unlocked_rmeta_units: Vec<usize>,
+            critical: bool,
         }
         let round = |x: f64| (x * 100.0).round() / 100.0;
         let unit_data: Vec<UnitData> = self
@@ -470,20 +894,7 @@ impl<'gctx> Timings<'gctx> {
                     "todo"
                 }
                 .to_string();
-
-                // These filter on the unlocked units because not all unlocked
-                // units are actually "built". For example, Doctest mode units
-                // don't actually generate artifacts.
-                let unlocked_units: Vec<usize> = ut
-                    .unlocked_units
-                    .iter()
-                    .filter_map(|unit| unit_map.get(unit).copied())
-                    .collect();
-                let unlocked_rmeta_units: Vec<usize> = ut
-                    .unlocked_rmeta_units
-                    .iter()
-                    .filter_map(|unit| unit_map.get(unit).copied())
-                    .collect();
+                let (unlocked_units, unlocked_rmeta_units) = ut.unlocked_indices(&unit_map);
                 UnitData {
                     i,
                     name: ut.unit.pkg.name().to_string(),
@@ -495,6 +906,7 @@ impl<'gctx> Timings<'gctx> {
                     rmeta_time: ut.rmeta_time.map(round),
                     unlocked_units,
                     unlocked_rmeta_units,
+                    critical: self.is_critical(i),
                 }
             })
             .collect();
@@ -516,6 +928,22 @@ impl<'gctx> Timings<'gctx> {
         Ok(())
     }
 
+    /// Returns whether the unit at `i` in `unit_times` is on the critical path.
+    fn is_critical(&self, i: usize) -> bool {
+        self.critical_path
+            .as_ref()
+            .is_some_and(|cp| cp.units.contains(&i))
+    }
+
+    /// Create a map to link indices of unlocked units.
+    fn unit_map(&self) -> HashMap<Unit, usize> {
+        self.unit_times
+            .iter()
+            .enumerate()
+            .map(|(i, ut)| (ut.unit.clone(), i))
+            .collect()
+    }
+
     /// Render the table of all units.
     fn write_unit_table(&self, f: &mut impl Write) -> CargoResult<()> {
         write!(
@@ -528,36 +956,52 @@ impl<'gctx> Timings<'gctx> {
       <th>Unit</th>
       <th>Total</th>
       <th>Codegen</th>
+      <th>Slack</th>
       <th>Features</th>
     </tr>
   </thead>
   <tbody>
 "#
         )?;
-        let mut units: Vec<&UnitTime> = self.unit_times.iter().collect();
-        units.sort_unstable_by(|a, b| b.duration.partial_cmp(&a.duration).unwrap());
-        for (i, unit) in units.iter().enumerate() {
+        let mut units: Vec<(usize, &UnitTime)> = self.unit_times.iter().enumerate().collect();
+        units.sort_unstable_by(|(_, a), (_, b)| b.duration.partial_cmp(&a.duration).unwrap());
+        for (i, (unit_idx, unit)) in units.iter().enumerate() {
             let codegen = match unit.codegen_time() {
                 None => "".to_string(),
                 Some((_rt, ctime, cent)) => format!("{:.1}s ({:.0}%)", ctime, cent),
             };
+            let slack = match &self.critical_path {
+                None => "".to_string(),
+                Some(cp) => match cp.max_speedup[*unit_idx] {
+                    Some(speedup) => format!("critical (up to {:.1}s faster)", speedup),
+                    None => format!("{:.1}s", cp.slack[*unit_idx]),
+                },
+            };
+            let class = if self.is_critical(*unit_idx) {
+                r#" class="critical-path""#
+            } else {
+                ""
+            };
             let features = unit.unit.features.join(", ");
             write!(
                 f,
                 r#"
-<tr>
+<tr{}>
   <td>{}.</td>
   <td>{}{}</td>
   <td>{:.1}s</td>
   <td>{}</td>
   <td>{}</td>
+  <td>{}</td>
 </tr>
 "#,
+                class,
                 i + 1,
                 unit.name_ver(),
                 unit.target,
                 unit.duration,
                 codegen,
+                slack,
                 features,
             )?;
         }
@@ -579,6 +1023,24 @@ impl UnitTime {
     fn name_ver(&self) -> String {
         format!("{} v{}", self.unit.pkg.name(), self.unit.pkg.version())
     }
+
+    /// Returns the indices of the unlocked units and unlocked rmeta units.
+    fn unlocked_indices(&self, unit_map: &HashMap<Unit, usize>) -> (Vec<usize>, Vec<usize>) {
+        // These filter on the unlocked units because not all unlocked
+        // units are actually "built". For example, Doctest mode units
+        // don't actually generate artifacts.
+        let unlocked_units = self
+            .unlocked_units
+            .iter()
+            .filter_map(|unit| unit_map.get(unit).copied())
+            .collect();
+        let unlocked_rmeta_units = self
+            .unlocked_rmeta_units
+            .iter()
+            .filter_map(|unit| unit_map.get(unit).copied())
+            .collect();
+        (unlocked_units, unlocked_rmeta_units)
+    }
 }
 
 fn render_rustc_info(bcx: &BuildContext<'_, '_>) -> String {
@@ -633,6 +1095,7 @@ static HTML_TMPL: &str = r#"
   --canvas-dep-line: #ddd;
   --canvas-dep-line-highlighted: #000;
   --canvas-cpu: rgba(250, 119, 0, 0.2);
+  --canvas-critical-path: #e80000;
 }
 
 @media (prefers-color-scheme: dark) {
@@ -660,6 +1123,7 @@ static HTML_TMPL: &str = r#"
     --canvas-dep-line: #444;
     --canvas-dep-line-highlighted: #fff;
     --canvas-cpu: rgba(250, 119, 0, 0.2);
+    --canvas-critical-path: #ff5c5c;
   }
 }
 
@@ -757,6 +1221,10 @@ h1 {
   text-align: right;
 }
 
+.my-table tr.critical-path td:first-child {
+  border-left: 4px solid var(--canvas-critical-path);
+}
+
 .input-table td {
   text-align: center;
 }
@@ -36,6 +36,7 @@ const NOT_CUSTOM_BUILD_COLOR = getCssColor('--canvas-not-custom-build');
 const DEP_LINE_COLOR = getCssColor('--canvas-dep-line');
 const DEP_LINE_HIGHLIGHTED_COLOR = getCssColor('--canvas-dep-line-highlighted');
 const CPU_COLOR = getCssColor('--canvas-cpu');
+const CRITICAL_PATH_COLOR = getCssColor('--canvas-critical-path');
 
 for (let n=0; n<UNIT_DATA.length; n++) {
   let unit = UNIT_DATA[n];
@@ -128,6 +129,15 @@ function render_pipeline_graph() {
       roundedRect(ctx, rmeta_x, y, px_per_sec * ctime, BOX_HEIGHT, RADIUS);
       ctx.fill();
     }
+    if (unit.critical) {
+      ctx.save();
+      ctx.beginPath();
+      ctx.strokeStyle = CRITICAL_PATH_COLOR;
+      ctx.lineWidth = 2;
+      roundedRect(ctx, x, y, width, BOX_HEIGHT, RADIUS);
+      ctx.stroke();
+      ctx.restore();
+    }
     ctx.fillStyle = TEXT_COLOR;
     ctx.textAlign = 'start';
     ctx.textBaseline = 'middle';
@@ -137,6 +137,29 @@ impl UnitInner {
     pub fn show_warnings(&self, gctx: &GlobalContext) -> bool {
         self.is_local() || gctx.extra_verbose()
     }
+
+    /// Describes the target and mode of this unit, for example
+    /// ` build script (run)`, with a leading space. This is empty for
+    /// libraries being built normally, which is by far the most common case.
+    pub fn target_description(&self) -> String {
+        let mut target = if self.target.is_lib() && self.mode == CompileMode::Build {
+            String::new()
+        } else {
+            format!(" {}", self.target.description_named())
+        };
+        match self.mode {
+            CompileMode::Test => target.push_str(" (test)"),
+            CompileMode::Build => {}
+            CompileMode::Check { test: true } => target.push_str(" (check-test)"),
+            CompileMode::Check { test: false } => target.push_str(" (check)"),
+            CompileMode::Bench => target.push_str(" (bench)"),
+            CompileMode::Doc { .. } => target.push_str(" (doc)"),
+            CompileMode::Doctest => target.push_str(" (doc test)"),
+            CompileMode::Docscrape => target.push_str(" (doc scrape)"),
+            CompileMode::RunCustomBuild => target.push_str(" (run)"),
+        }
+        target
+    }
 }
 
 impl Unit {
@@ -66,7 +66,9 @@
 //!    include `(unstable)` to note that this is an unstable option.
 //! 2. Where the CLI option is loaded, be sure to call
 //!    [`CliUnstable::fail_if_stable_opt`]. This will return an error if `-Z
-//!    unstable options` was not passed.
+//!    unstable options` was not passed. Until the option has a tracking
+//!    issue, use [`CliUnstable::fail_if_stable_opt_untracked`] to point to
+//!    its section of `unstable.md` instead.
 //!
 //! ## `-Z` options
 //!
@@ -127,6 +129,7 @@ use anyhow::{bail, Error};
 use cargo_util::ProcessBuilder;
 use serde::{Deserialize, Serialize};
 
+use crate::core::compiler::SchedulingPolicy;
 use crate::core::resolver::ResolveBehavior;
 use crate::util::errors::CargoResult;
 use crate::util::indented_lines;
@@ -752,14 +755,17 @@ unstable_cli_options!(
     // All other unstable features.
     // Please keep this list lexicographically ordered.
     advanced_env: bool,
+    advisory_db: bool = ("Avoid dependency versions affected by the advisories of the `resolver.advisory-db` config option"),
     asymmetric_token: bool = ("Allows authenticating with asymmetric tokens"),
     avoid_dev_deps: bool = ("Avoid installing dev-dependencies if possible"),
     binary_dep_depinfo: bool = ("Track changes to dependency artifacts"),
     bindeps: bool = ("Allow Cargo packages to depend on bin, cdylib, and staticlib crates, and use the artifacts built by those crates"),
+    build_cache: bool = ("Share compiled dependencies between builds through the `[build-cache]` configured in .cargo/config.toml"),
     #[serde(deserialize_with = "deserialize_build_std")]
     build_std: Option<Vec<String>>  = ("Enable Cargo to compile the standard library itself as part of a crate graph compilation"),
     build_std_features: Option<Vec<String>>  = ("Configure features enabled for the standard library itself when building the standard library"),
     cargo_lints: bool = ("Enable the `[lints.cargo]` table"),
+    checksum_freshness: bool = ("Use content checksums of source files to detect changes when their mtime changes"),
     codegen_backend: bool = ("Enable the `codegen-backend` option in profiles in .cargo/config.toml file"),
     config_include: bool = ("Enable the `include` key in config files"),
     direct_minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum (direct dependencies only)"),
@@ -772,7 +778,11 @@ unstable_cli_options!(
     #[serde(deserialize_with = "deserialize_gitoxide_features")]
     gitoxide: Option<GitoxideFeatures> = ("Use gitoxide for the given git interactions, or all of them if no argument is given"),
     host_config: bool = ("Enable the `[host]` section in the .cargo/config.toml file"),
+    incremental_resolve: bool = ("Resolve again only the locked packages affected by changed dependency declarations"),
+    memory_limit: bool = ("Limit the memory used by the jobs running at once with the `build.memory-limit` and `build.link-jobs` config options"),
+    min_publish_age: bool = ("Avoid recently published dependency versions with the `resolver.min-publish-age` config option"),
     minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum"),
+    minimize_duplicates: bool = ("Prefer dependency versions which avoid duplicate versions of a package"),
     msrv_policy: bool = ("Enable rust-version aware policy within cargo"),
     mtime_on_use: bool = ("Configure Cargo to update the mtime of used files"),
     next_lockfile_bump: bool,
@@ -784,12 +794,16 @@ unstable_cli_options!(
     publish_timeout: bool = ("Enable the `publish.timeout` key in .cargo/config.toml file"),
     rustdoc_map: bool = ("Allow passing external documentation mappings to rustdoc"),
     rustdoc_scrape_examples: bool = ("Allows Rustdoc to scrape code examples from reverse-dependencies"),
+    scheduling: Option<SchedulingPolicy> = ("Select how ready units are prioritized: `dependents` (default) or `critical-path`, using the timing data of a previous build"),
     script: bool = ("Enable support for single-file, `.rs` packages"),
     separate_nightlies: bool,
+    shared_deps: bool = ("Build registry and git dependencies in a directory shared by all workspaces"),
     skip_rustdoc_fingerprint: bool,
     target_applies_to_host: bool = ("Enable the `target-applies-to-host` key in the .cargo/config.toml file"),
     trim_paths: bool = ("Enable the `trim-paths` option in profiles"),
     unstable_options: bool = ("Allow the usage of unstable options"),
+    update_policy: bool = ("Apply the `[update-policy]` config table to `cargo update`"),
+    vendor_manifest: bool = ("Record the provenance of vendored packages, and keep local changes to them as patches"),
 );
 
 const STABILIZED_COMPILE_PROGRESS: &str = "The progress bar is now always \
@@ -1246,15 +1260,18 @@ impl CliUnstable {
             // Unstable features
             // Sorted alphabetically:
             "advanced-env" => self.advanced_env = parse_empty(k, v)?,
+            "advisory-db" => self.advisory_db = parse_empty(k, v)?,
             "asymmetric-token" => self.asymmetric_token = parse_empty(k, v)?,
             "avoid-dev-deps" => self.avoid_dev_deps = parse_empty(k, v)?,
             "binary-dep-depinfo" => self.binary_dep_depinfo = parse_empty(k, v)?,
             "bindeps" => self.bindeps = parse_empty(k, v)?,
+            "build-cache" => self.build_cache = parse_empty(k, v)?,
             "build-std" => {
                 self.build_std = Some(crate::core::compiler::standard_lib::parse_unstable_flag(v))
             }
             "build-std-features" => self.build_std_features = Some(parse_features(v)),
             "cargo-lints" => self.cargo_lints = parse_empty(k, v)?,
+            "checksum-freshness" => self.checksum_freshness = parse_empty(k, v)?,
             "codegen-backend" => self.codegen_backend = parse_empty(k, v)?,
             "config-include" => self.config_include = parse_empty(k, v)?,
             "direct-minimal-versions" => self.direct_minimal_versions = parse_empty(k, v)?,
@@ -1274,8 +1291,12 @@ impl CliUnstable {
                 )?
             }
             "host-config" => self.host_config = parse_empty(k, v)?,
+            "incremental-resolve" => self.incremental_resolve = parse_empty(k, v)?,
             "next-lockfile-bump" => self.next_lockfile_bump = parse_empty(k, v)?,
+            "memory-limit" => self.memory_limit = parse_empty(k, v)?,
+            "min-publish-age" => self.min_publish_age = parse_empty(k, v)?,
             "minimal-versions" => self.minimal_versions = parse_empty(k, v)?,
+            "minimize-duplicates" => self.minimize_duplicates = parse_empty(k, v)?,
             "msrv-policy" => self.msrv_policy = parse_empty(k, v)?,
             // can also be set in .cargo/config or with and ENV
             "mtime-on-use" => self.mtime_on_use = parse_empty(k, v)?,
@@ -1288,11 +1309,24 @@ impl CliUnstable {
             "publish-timeout" => self.publish_timeout = parse_empty(k, v)?,
             "rustdoc-map" => self.rustdoc_map = parse_empty(k, v)?,
             "rustdoc-scrape-examples" => self.rustdoc_scrape_examples = parse_empty(k, v)?,
+            "scheduling" => {
+                let Some(v) = v else {
+                    bail!(
+                        "flag -Z{} requires a value, expected {}",
+                        k,
+                        SchedulingPolicy::EXPECTED
+                    );
+                };
+                self.scheduling = Some(v.parse()?);
+            }
             "separate-nightlies" => self.separate_nightlies = parse_empty(k, v)?,
+            "shared-deps" => self.shared_deps = parse_empty(k, v)?,
             "skip-rustdoc-fingerprint" => self.skip_rustdoc_fingerprint = parse_empty(k, v)?,
             "script" => self.script = parse_empty(k, v)?,
             "target-applies-to-host" => self.target_applies_to_host = parse_empty(k, v)?,
             "unstable-options" => self.unstable_options = parse_empty(k, v)?,
+            "update-policy" => self.update_policy = parse_empty(k, v)?,
+            "vendor-manifest" => self.vendor_manifest = parse_empty(k, v)?,
             _ => bail!("\
             unknown `-Z` flag specified: {k}\n\n\
             For available unstable features, see https://doc.rust-lang.org/nightly/cargo/reference/unstable.html\n\
@@ -1314,12 +1348,48 @@ impl CliUnstable {
         issue: u32,
         z_name: &str,
         enabled: bool,
+    ) -> CargoResult<()> {
+        let see = format!(
+            "See https://github.com/rust-lang/cargo/issues/{issue} for more \
+             information about the `{flag}` flag."
+        );
+        self.fail_if_stable_opt_with_see(flag, &see, z_name, enabled)
+    }
+
+    /// Like [`CliUnstable::fail_if_stable_opt`], for a flag without a
+    /// tracking issue, pointing to its `section` of the unstable docs
+    /// instead.
+    pub fn fail_if_stable_opt_untracked(&self, flag: &str, section: &str) -> CargoResult<()> {
+        self.fail_if_stable_opt_untracked_custom_z(
+            flag,
+            section,
+            "unstable-options",
+            self.unstable_options,
+        )
+    }
+
+    pub fn fail_if_stable_opt_untracked_custom_z(
+        &self,
+        flag: &str,
+        section: &str,
+        z_name: &str,
+        enabled: bool,
+    ) -> CargoResult<()> {
+        let see = format!(
+            "See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#{section} \
+             for more information about the `{flag}` flag."
+        );
+        self.fail_if_stable_opt_with_see(flag, &see, z_name, enabled)
+    }
+
+    fn fail_if_stable_opt_with_see(
+        &self,
+        flag: &str,
+        see: &str,
+        z_name: &str,
+        enabled: bool,
     ) -> CargoResult<()> {
         if !enabled {
-            let see = format!(
-                "See https://github.com/rust-lang/cargo/issues/{issue} for more \
-                 information about the `{flag}` flag."
-            );
             // NOTE: a `config` isn't available here, check the channel directly
             let channel = channel();
             if channel == "nightly" || channel == "dev" {
@@ -1349,14 +1419,43 @@ impl CliUnstable {
         z_name: &str,
         enabled: bool,
     ) -> CargoResult<()> {
-        if enabled {
-            return Ok(());
-        }
         let see = format!(
             "See https://github.com/rust-lang/cargo/issues/{} for more \
             information about the `cargo {}` command.",
             issue, command
         );
+        self.fail_if_stable_command_with_see(gctx, command, &see, z_name, enabled)
+    }
+
+    /// Like [`CliUnstable::fail_if_stable_command`], for a subcommand without
+    /// a tracking issue, pointing to its `section` of the unstable docs
+    /// instead.
+    pub fn fail_if_stable_command_untracked(
+        &self,
+        gctx: &GlobalContext,
+        command: &str,
+        section: &str,
+        z_name: &str,
+        enabled: bool,
+    ) -> CargoResult<()> {
+        let see = format!(
+            "See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#{section} \
+             for more information about the `cargo {command}` command."
+        );
+        self.fail_if_stable_command_with_see(gctx, command, &see, z_name, enabled)
+    }
+
+    fn fail_if_stable_command_with_see(
+        &self,
+        gctx: &GlobalContext,
+        command: &str,
+        see: &str,
+        z_name: &str,
+        enabled: bool,
+    ) -> CargoResult<()> {
+        if enabled {
+            return Ok(());
+        }
         if gctx.nightly_features_allowed {
             bail!(
                 "the `cargo {command}` command is unstable, pass `-Z {z_name}` \
@@ -18,14 +18,23 @@
 //! implemented in the [`crate::core::global_cache_tracker`] module. See that
 //! module documentation for an in-depth explanation of how global cache
 //! tracking works.
+//!
+//! The build directory shared by all workspaces with `-Zshared-deps` is
+//! cleaned as well. The last use of each unit in there is the modification
+//! time of its fingerprint directory, which is updated by every build using
+//! it.
 
+use crate::core::compiler::layout::shared_deps_root;
 use crate::core::global_cache_tracker::{self, GlobalCacheTracker};
 use crate::ops::CleanContext;
 use crate::util::cache_lock::{CacheLock, CacheLockMode};
+use crate::util::Filesystem;
 use crate::{CargoResult, GlobalContext};
 use anyhow::{format_err, Context as _};
+use cargo_util::paths;
 use serde::Deserialize;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 /// Default max age to auto-clean extracted sources, which can be recovered
 /// without downloading anything.
@@ -33,6 +42,9 @@ const DEFAULT_MAX_AGE_EXTRACTED: &str = "1 month";
 /// Default max ago to auto-clean cache data, which must be downloaded to
 /// recover.
 const DEFAULT_MAX_AGE_DOWNLOADED: &str = "3 months";
+/// Default max age to auto-clean units from the shared build directory, which
+/// can be recovered by building them again.
+const DEFAULT_MAX_AGE_SHARED_DEPS: &str = "1 month";
 /// How often auto-gc will run by default unless overridden in the config.
 const DEFAULT_AUTO_FREQUENCY: &str = "1 day";
 
@@ -112,6 +124,8 @@ struct AutoConfig {
     max_git_co_age: Option<String>,
     /// Any git clone older than this duration will be deleted from the git cache.
     max_git_db_age: Option<String>,
+    /// Any unit older than this duration will be deleted from the shared build directory.
+    max_shared_deps_age: Option<String>,
 }
 
 /// Options to use for garbage collection.
@@ -135,6 +149,8 @@ pub struct GcOpts {
     pub max_git_size: Option<u64>,
     /// The `--max-download-size` CLI option.
     pub max_download_size: Option<u64>,
+    /// The `--max-shared-deps-age` CLI option.
+    pub max_shared_deps_age: Option<Duration>,
 }
 
 impl GcOpts {
@@ -220,6 +236,14 @@ impl GcOpts {
                 .as_deref()
                 .unwrap_or(DEFAULT_MAX_AGE_DOWNLOADED),
         )?;
+        self.max_shared_deps_age = newer_time_span_for_config(
+            self.max_shared_deps_age,
+            "gc.auto.max-shared-deps-age",
+            auto_config
+                .max_shared_deps_age
+                .as_deref()
+                .unwrap_or(DEFAULT_MAX_AGE_SHARED_DEPS),
+        )?;
         Ok(())
     }
 }
@@ -289,11 +313,86 @@ impl<'a, 'gctx> Gc<'a, 'gctx> {
     /// Performs garbage collection based on the given options.
     pub fn gc(&mut self, clean_ctx: &mut CleanContext<'gctx>, gc_opts: &GcOpts) -> CargoResult<()> {
         self.global_cache_tracker.clean(clean_ctx, gc_opts)?;
+        if let Some(max_age) = gc_opts.max_shared_deps_age {
+            clean_shared_deps(clean_ctx, max_age)?;
+        }
         // In the future, other gc operations go here, such as target cleaning.
         Ok(())
     }
 }
 
+/// Deletes the units in the shared build directory which haven't been used
+/// for `max_age`.
+///
+/// Directories locked by a running build are skipped.
+fn clean_shared_deps(clean_ctx: &mut CleanContext<'_>, max_age: Duration) -> CargoResult<()> {
+    let gctx = clean_ctx.gctx;
+    let root = shared_deps_root(gctx)?.into_path_unlocked();
+    let Some(cutoff) = SystemTime::now().checked_sub(max_age) else {
+        return Ok(());
+    };
+    // The layout is `$ROOT/$TRIPLE/$FLAGS_HASH`.
+    for target in subdirs(&root)? {
+        for dest in subdirs(&target)? {
+            let Some(_lock) =
+                Filesystem::new(dest.clone()).try_open_rw_exclusive_create(".cargo-lock")?
+            else {
+                tracing::debug!(target: "gc", "skipping locked {}", dest.display());
+                continue;
+            };
+            let mut unused = Vec::new();
+            for fingerprint in subdirs(&dest.join(".fingerprint"))? {
+                let last_use = paths::metadata(&fingerprint)?.modified()?;
+                if last_use >= cutoff {
+                    continue;
+                }
+                let name = fingerprint.file_name().unwrap().to_string_lossy();
+                if let Some((_, hash)) = name.rsplit_once('-') {
+                    unused.push(format!("-{hash}"));
+                }
+            }
+            if unused.is_empty() {
+                continue;
+            }
+            // All files of a unit are named after its metadata hash, like
+            // `libfoo-$HASH.rlib` or `foo-$HASH/`.
+            for dir in [".fingerprint", "deps", "build"] {
+                for entry in read_dir(&dest.join(dir))? {
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    let stem = name.split('.').next().unwrap();
+                    if unused.iter().any(|hash| stem.ends_with(hash.as_str())) {
+                        clean_ctx.rm_rf(&entry.path())?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the entries of `dir`, or nothing if it doesn't exist.
+fn read_dir(dir: &Path) -> CargoResult<Vec<std::fs::DirEntry>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = std::fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("failed to read directory `{}`", dir.display()))?;
+    Ok(entries)
+}
+
+/// Returns the subdirectories of `dir`, or nothing if it doesn't exist.
+fn subdirs(dir: &Path) -> CargoResult<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    for entry in read_dir(dir)? {
+        if entry.file_type()?.is_dir() {
+            dirs.push(entry.path());
+        }
+    }
+    Ok(dirs)
+}
+
 /// Returns the shorter duration from `cur_span` versus `config_span`.
 ///
 /// This is used because the user may specify multiple options which overlap,
@@ -131,6 +131,9 @@ pub struct PackageRegistry<'gctx> {
     /// This is constructed during calls to [`PackageRegistry::patch`],
     /// along with the `patches` field, thoough these entries never get locked.
     patches_available: HashMap<CanonicalUrl, Vec<PackageId>>,
+    /// Decides which summaries of registry packages may be used, see
+    /// [`PackageRegistry::set_version_filter`].
+    version_filter: Option<Box<dyn Fn(&Summary) -> bool + 'gctx>>,
 }
 
 /// A map of all "locked packages" which is filled in when parsing a lock file
@@ -209,9 +212,18 @@ impl<'gctx> PackageRegistry<'gctx> {
             patches: HashMap::new(),
             patches_locked: false,
             patches_available: HashMap::new(),
+            version_filter: None,
         })
     }
 
+    /// Hides the summaries of registry packages for which `filter` returns
+    /// `false` from queries, or stops hiding any if `None`.
+    ///
+    /// Patches and overrides are never hidden.
+    pub fn set_version_filter(&mut self, filter: Option<Box<dyn Fn(&Summary) -> bool + 'gctx>>) {
+        self.version_filter = filter;
+    }
+
     pub fn get(self, package_ids: &[PackageId]) -> CargoResult<PackageSet<'gctx>> {
         trace!("getting packages; sources={}", self.sources.len());
         PackageSet::new(package_ids, self.sources, self.gctx)
@@ -726,6 +738,7 @@ impl<'gctx> Registry for PackageRegistry<'gctx> {
                 // then we skip this `summary`.
                 let locked = &self.locked;
                 let all_patches = &self.patches_available;
+                let version_filter = &self.version_filter;
                 let callback = &mut |summary: IndexSummary| {
                     for patch in patches.iter() {
                         let patch = patch.package_id().version();
@@ -734,6 +747,11 @@ impl<'gctx> Registry for PackageRegistry<'gctx> {
                         }
                     }
                     let summary = summary.into_summary();
+                    if let Some(filter) = version_filter {
+                        if summary.source_id().is_registry() && !filter(&summary) {
+                            return;
+                        }
+                    }
                     f(IndexSummary::Candidate(lock(locked, all_patches, summary)))
                 };
                 return source.query(dep, kind, callback);
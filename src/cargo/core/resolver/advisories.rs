@@ -0,0 +1,179 @@
+//! A local database of security advisories, in the format of the [RustSec
+//! advisory database], whose affected versions the resolver avoids, see
+//! `-Zadvisory-db`.
+//!
+//! Each advisory is a Markdown file named after its id, like
+//! `crates/foo/RUSTSEC-2024-0001.md`, anywhere in the database directory,
+//! starting with a fenced TOML block:
+//!
+//! ````markdown
+//! ```toml
+//! [advisory]
+//! id = "RUSTSEC-2024-0001"
+//! package = "foo"
+//!
+//! [versions]
+//! patched = [">= 1.2.3"]
+//! unaffected = ["< 1.0.0"]
+//! ```
+//!
+//! # Description of the advisory
+//! ````
+//!
+//! The TOML block may also be a TOML file of its own, like the advisories of
+//! older versions of the database.
+//!
+//! A version is affected unless it matches a `patched` or `unaffected`
+//! requirement. Withdrawn and informational advisories, like unmaintained
+//! crates, don't affect any version.
+//!
+//! [RustSec advisory database]: https://github.com/rustsec/advisory-db
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Context as _;
+use cargo_util::paths;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+
+use crate::core::PackageId;
+use crate::util::interning::InternedString;
+use crate::util::CargoResult;
+
+#[derive(Deserialize)]
+struct AdvisoryFile {
+    advisory: AdvisoryMetadata,
+    #[serde(default)]
+    versions: AdvisoryVersions,
+}
+
+#[derive(Deserialize)]
+struct AdvisoryMetadata {
+    id: String,
+    package: String,
+    informational: Option<String>,
+    withdrawn: Option<toml::Value>,
+}
+
+#[derive(Deserialize, Default)]
+struct AdvisoryVersions {
+    #[serde(default)]
+    patched: Vec<VersionReq>,
+    #[serde(default)]
+    unaffected: Vec<VersionReq>,
+}
+
+/// A security advisory for a package from crates.io.
+#[derive(Debug)]
+pub struct Advisory {
+    id: String,
+    patched: Vec<VersionReq>,
+    unaffected: Vec<VersionReq>,
+}
+
+impl Advisory {
+    /// The identifier of the advisory, like `RUSTSEC-2024-0001`.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Whether `version` is affected by this advisory.
+    pub fn affects(&self, version: &Version) -> bool {
+        !self
+            .patched
+            .iter()
+            .chain(&self.unaffected)
+            .any(|req| req.matches(version))
+    }
+}
+
+/// The advisories of a local advisory database, by package name.
+#[derive(Debug, Default)]
+pub struct AdvisoryDatabase {
+    advisories: HashMap<InternedString, Vec<Advisory>>,
+}
+
+impl AdvisoryDatabase {
+    /// Loads the advisories of all Markdown and TOML advisory files in the
+    /// directory `path`.
+    pub fn load(path: &Path) -> CargoResult<AdvisoryDatabase> {
+        if !path.is_dir() {
+            anyhow::bail!("advisory database `{}` is not a directory", path.display());
+        }
+        let mut db = AdvisoryDatabase::default();
+        let mut found = false;
+        for entry in walkdir::WalkDir::new(path).sort_by_file_name() {
+            let entry = entry?;
+            let file = entry.path();
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let contents = match file.extension().and_then(|ext| ext.to_str()) {
+                // Only advisories are named after their id, unlike the
+                // `README.md` of the database.
+                Some("md") if entry.file_name().to_string_lossy().starts_with("RUSTSEC-") => {
+                    let contents = paths::read(file)?;
+                    front_matter(&contents)
+                        .with_context(|| {
+                            format!(
+                                "advisory `{}` doesn't start with a ```toml block",
+                                file.display()
+                            )
+                        })?
+                        .to_owned()
+                }
+                Some("toml") => paths::read(file)?,
+                _ => continue,
+            };
+            let advisory: AdvisoryFile = toml::from_str(&contents)
+                .with_context(|| format!("failed to parse advisory `{}`", file.display()))?;
+            found = true;
+            if advisory.advisory.informational.is_some() || advisory.advisory.withdrawn.is_some() {
+                continue;
+            }
+            db.advisories
+                .entry(advisory.advisory.package.as_str().into())
+                .or_default()
+                .push(Advisory {
+                    id: advisory.advisory.id,
+                    patched: advisory.versions.patched,
+                    unaffected: advisory.versions.unaffected,
+                });
+        }
+        if !found {
+            anyhow::bail!("no advisories found in `{}`", path.display());
+        }
+        Ok(db)
+    }
+
+    /// The advisories affecting the package `pkg_id`.
+    ///
+    /// Advisories only apply to packages from crates.io.
+    pub fn affecting(&self, pkg_id: PackageId) -> impl Iterator<Item = &Advisory> {
+        let advisories = if pkg_id.source_id().is_crates_io() {
+            self.advisories
+                .get(&pkg_id.name())
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+        } else {
+            &[]
+        };
+        advisories
+            .iter()
+            .filter(move |advisory| advisory.affects(pkg_id.version()))
+    }
+
+    /// Whether any advisory affects the package `pkg_id`.
+    pub fn is_affected(&self, pkg_id: PackageId) -> bool {
+        self.affecting(pkg_id).next().is_some()
+    }
+}
+
+/// The contents of the fenced TOML block starting an advisory in Markdown.
+fn front_matter(contents: &str) -> Option<&str> {
+    let rest = contents.trim_start().strip_prefix("```toml")?;
+    let rest = rest.strip_prefix('\r').unwrap_or(rest).strip_prefix('\n')?;
+    let end = rest.find("\n```")?;
+    Some(&rest[..end])
+}
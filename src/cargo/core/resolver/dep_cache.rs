@@ -64,6 +64,10 @@ impl<'a> RegistryQueryer<'a> {
         }
     }
 
+    pub fn version_prefs(&self) -> &'a VersionPreferences {
+        self.version_prefs
+    }
+
     pub fn reset_pending(&mut self) -> bool {
         let mut all_ready = true;
         self.registry_cache.retain(|_, r| {
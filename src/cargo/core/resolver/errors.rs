@@ -9,6 +9,7 @@ use anyhow::Error;
 
 use super::context::ResolverContext;
 use super::types::{ConflictMap, ConflictReason};
+use super::VersionPreferences;
 
 /// Error during resolution providing a path of `PackageId`s.
 pub struct ResolveError {
@@ -72,6 +73,7 @@ impl From<(PackageId, ConflictReason)> for ActivateError {
 pub(super) fn activation_error(
     resolver_ctx: &ResolverContext,
     registry: &mut dyn Registry,
+    version_prefs: &VersionPreferences,
     parent: &Summary,
     dep: &Dependency,
     conflicting_activations: &ConflictMap,
@@ -193,6 +195,24 @@ pub(super) fn activation_error(
                     );
                     // p == parent so the full path is redundant.
                 }
+                ConflictReason::SecurityAdvisories => {
+                    let excluded = candidates
+                        .iter()
+                        .filter(|c| version_prefs.is_excluded_by_advisories(c))
+                        .map(|c| {
+                            let advisories = version_prefs.advisories_affecting(c);
+                            let ids = advisories.iter().map(|a| a.id()).collect::<Vec<_>>();
+                            format!("{} ({})", c.version(), ids.join(", "))
+                        })
+                        .collect::<Vec<_>>();
+                    msg.push_str("\n\nthe package `");
+                    msg.push_str(&*p.name());
+                    msg.push_str("` depends on `");
+                    msg.push_str(&*dep.package_name());
+                    msg.push_str("`, but versions affected by security advisories are excluded: ");
+                    msg.push_str(&excluded.join(", "));
+                    // p == parent so the full path is redundant.
+                }
             }
         }
 
@@ -295,7 +315,18 @@ pub(super) fn activation_error(
             );
         }
 
-        if registry.is_replaced(dep.source_id()) {
+        let too_recent = candidates
+            .iter()
+            .filter(|c| dep.matches(c) && version_prefs.is_denied_as_too_recent(c))
+            .map(|c| c.version().to_string())
+            .collect::<Vec<_>>();
+        if !too_recent.is_empty() {
+            msg.push_str(&format!(
+                "\nversions published less than `resolver.min-publish-age` ago \
+                 are denied: {}",
+                too_recent.join(", ")
+            ));
+        } else if registry.is_replaced(dep.source_id()) {
             msg.push_str("\nperhaps a crate was updated and forgotten to be re-vendored?");
         }
 
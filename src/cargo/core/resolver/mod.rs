@@ -77,6 +77,7 @@ use self::features::RequestedFeatures;
 use self::types::{ConflictMap, ConflictReason, DepsFrame};
 use self::types::{FeaturesSet, RcVecIter, RemainingDeps, ResolverProgress};
 
+pub use self::advisories::{Advisory, AdvisoryDatabase};
 pub use self::encode::Metadata;
 pub use self::encode::{EncodableDependency, EncodablePackageId, EncodableResolve};
 pub use self::errors::{ActivateError, ActivateResult, ResolveError};
@@ -85,6 +86,7 @@ pub use self::resolve::{Resolve, ResolveVersion};
 pub use self::types::{ResolveBehavior, ResolveOpts};
 pub use self::version_prefs::{VersionOrdering, VersionPreferences};
 
+mod advisories;
 mod conflict_cache;
 mod context;
 mod dep_cache;
@@ -277,7 +279,12 @@ fn activate_deps_loop(
         let mut backtracked = false;
 
         loop {
-            let next = remaining_candidates.next(&mut conflicting_activations, &resolver_ctx);
+            let next = remaining_candidates.next(
+                &mut conflicting_activations,
+                &resolver_ctx,
+                &parent,
+                registry.version_prefs(),
+            );
 
             let (candidate, has_another) = next.ok_or(()).or_else(|_| {
                 // If we get here then our `remaining_candidates` was just
@@ -326,6 +333,7 @@ fn activate_deps_loop(
                     &resolver_ctx,
                     &mut backtrack_stack,
                     &parent,
+                    registry.version_prefs(),
                     backtracked,
                     generalize_conflicting_activations
                         .as_ref()
@@ -346,9 +354,11 @@ fn activate_deps_loop(
                     }
                     None => {
                         debug!("no candidates found");
+                        let version_prefs = registry.version_prefs();
                         Err(errors::activation_error(
                             &resolver_ctx,
                             registry.registry,
+                            version_prefs,
                             &parent,
                             &dep,
                             &conflicting_activations,
@@ -525,6 +535,7 @@ fn activate_deps_loop(
                                 &resolver_ctx,
                                 &mut backtrack_stack.clone(),
                                 &parent,
+                                registry.version_prefs(),
                                 backtracked,
                                 &conflicting_activations,
                             )
@@ -749,9 +760,20 @@ impl RemainingCandidates {
         &mut self,
         conflicting_prev_active: &mut ConflictMap,
         cx: &ResolverContext,
+        parent: &Summary,
+        version_prefs: &VersionPreferences,
     ) -> Option<(Summary, bool)> {
         for b in self.remaining.by_ref() {
             let b_id = b.package_id();
+            // Versions affected by a security advisory can't be activated,
+            // whatever else is activated, so this is blamed on `parent`.
+            if version_prefs.is_excluded_by_advisories(&b) {
+                conflicting_prev_active
+                    .entry(parent.package_id())
+                    .or_insert(ConflictReason::SecurityAdvisories);
+                continue;
+            }
+
             // The `links` key in the manifest dictates that there's only one
             // package in a dependency graph, globally, with that particular
             // `links` key. If this candidate links to something that's already
@@ -933,6 +955,7 @@ fn find_candidate(
     cx: &ResolverContext,
     backtrack_stack: &mut Vec<BacktrackFrame>,
     parent: &Summary,
+    version_prefs: &VersionPreferences,
     backtracked: bool,
     conflicting_activations: &ConflictMap,
 ) -> Option<(Summary, bool, BacktrackFrame)> {
@@ -955,9 +978,12 @@ fn find_candidate(
     };
 
     while let Some(mut frame) = backtrack_stack.pop() {
-        let next = frame
-            .remaining_candidates
-            .next(&mut frame.conflicting_activations, &frame.context);
+        let next = frame.remaining_candidates.next(
+            &mut frame.conflicting_activations,
+            &frame.context,
+            &frame.parent,
+            version_prefs,
+        );
         let Some((candidate, has_another)) = next else {
             continue;
         };
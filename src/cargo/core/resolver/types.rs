@@ -300,6 +300,11 @@ pub enum ConflictReason {
     /// A dependency listed a feature for an optional dependency, but that
     /// optional dependency is "hidden" using namespaced `dep:` syntax.
     NonImplicitDependencyAsFeature(InternedString),
+
+    /// A candidate is affected by a security advisory, and excluded with
+    /// `-Zadvisory-db`. This is blamed on the package depending on it, since
+    /// no other package needs to be deactivated to avoid the conflict.
+    SecurityAdvisories,
 }
 
 impl ConflictReason {
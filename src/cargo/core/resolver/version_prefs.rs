@@ -3,9 +3,12 @@
 
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
 use cargo_util_schemas::core::PartialVersion;
+use time::OffsetDateTime;
 
+use super::{Advisory, AdvisoryDatabase};
 use crate::core::{Dependency, PackageId, Summary};
 use crate::util::interning::InternedString;
 
@@ -22,6 +25,10 @@ pub struct VersionPreferences {
     prefer_patch_deps: HashMap<InternedString, HashSet<Dependency>>,
     version_ordering: VersionOrdering,
     rust_versions: Vec<PartialVersion>,
+    publish_cutoff: Option<OffsetDateTime>,
+    deny_recent: bool,
+    unify_with: HashSet<PackageId>,
+    advisories: Option<Rc<AdvisoryDatabase>>,
 }
 
 #[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
@@ -53,6 +60,33 @@ impl VersionPreferences {
         self.rust_versions = vers;
     }
 
+    /// Versions published after `cutoff` are considered too recent, and are
+    /// sorted after all other versions, or removed if `deny` is set.
+    ///
+    /// Versions without a known publish time are never too recent.
+    pub fn publish_cutoff(&mut self, cutoff: OffsetDateTime, deny: bool) {
+        self.publish_cutoff = Some(cutoff);
+        self.deny_recent = deny;
+    }
+
+    /// Indicate that the given package is used elsewhere in the dependency
+    /// graph, so selecting it avoids a duplicate of the package.
+    ///
+    /// Unlike [`Self::prefer_package_id`], this doesn't take precedence over
+    /// rust-version compatibility or the publish time.
+    pub fn prefer_unified(&mut self, pkg_id: PackageId) {
+        self.unify_with.insert(pkg_id);
+    }
+
+    /// Versions affected by an advisory of `advisories` are excluded, unless
+    /// they are preferred, like yanked versions are unless they are locked.
+    ///
+    /// They are sorted last by [`Self::sort_summaries`], the resolver then
+    /// rejects them, see [`Self::is_excluded_by_advisories`].
+    pub fn exclude_advisories(&mut self, advisories: Rc<AdvisoryDatabase>) {
+        self.advisories = Some(advisories);
+    }
+
     /// Sort (and filter) the given vector of summaries in-place
     ///
     /// Note: all summaries presumed to be for the same package.
@@ -60,26 +94,25 @@ impl VersionPreferences {
     /// Sort order:
     /// 1. Preferred packages
     /// 2. Most compatible [`VersionPreferences::rust_versions`]
-    /// 3. `first_version`, falling back to [`VersionPreferences::version_ordering`] when `None`
+    /// 3. Not excluded by [`VersionPreferences::exclude_advisories`]
+    /// 4. Published before [`VersionPreferences::publish_cutoff`]
+    /// 5. Unified packages, see [`VersionPreferences::prefer_unified`]
+    /// 6. `first_version`, falling back to [`VersionPreferences::version_ordering`] when `None`
     ///
     /// Filtering:
+    /// - Published after [`VersionPreferences::publish_cutoff`], if denied and not preferred
     /// - `first_version`
     pub fn sort_summaries(
         &self,
         summaries: &mut Vec<Summary>,
         first_version: Option<VersionOrdering>,
     ) {
-        let should_prefer = |pkg_id: &PackageId| {
-            self.try_to_use.contains(pkg_id)
-                || self
-                    .prefer_patch_deps
-                    .get(&pkg_id.name())
-                    .map(|deps| deps.iter().any(|d| d.matches_id(*pkg_id)))
-                    .unwrap_or(false)
-        };
+        if self.deny_recent {
+            summaries.retain(|s| !self.is_too_recent(s) || self.should_prefer(&s.package_id()));
+        }
         summaries.sort_unstable_by(|a, b| {
-            let prefer_a = should_prefer(&a.package_id());
-            let prefer_b = should_prefer(&b.package_id());
+            let prefer_a = self.should_prefer(&a.package_id());
+            let prefer_b = self.should_prefer(&b.package_id());
             let previous_cmp = prefer_a.cmp(&prefer_b).reverse();
             if previous_cmp != Ordering::Equal {
                 return previous_cmp;
@@ -93,6 +126,25 @@ impl VersionPreferences {
                 }
             }
 
+            let excluded_cmp = self
+                .is_excluded_by_advisories(a)
+                .cmp(&self.is_excluded_by_advisories(b));
+            if excluded_cmp != Ordering::Equal {
+                return excluded_cmp;
+            }
+
+            let recent_cmp = self.is_too_recent(a).cmp(&self.is_too_recent(b));
+            if recent_cmp != Ordering::Equal {
+                return recent_cmp;
+            }
+
+            let unify_a = self.unify_with.contains(&a.package_id());
+            let unify_b = self.unify_with.contains(&b.package_id());
+            let unify_cmp = unify_a.cmp(&unify_b).reverse();
+            if unify_cmp != Ordering::Equal {
+                return unify_cmp;
+            }
+
             let cmp = a.version().cmp(b.version());
             match first_version.unwrap_or(self.version_ordering) {
                 VersionOrdering::MaximumVersionsFirst => cmp.reverse(),
@@ -104,6 +156,15 @@ impl VersionPreferences {
         }
     }
 
+    fn should_prefer(&self, pkg_id: &PackageId) -> bool {
+        self.try_to_use.contains(pkg_id)
+            || self
+                .prefer_patch_deps
+                .get(&pkg_id.name())
+                .map(|deps| deps.iter().any(|d| d.matches_id(*pkg_id)))
+                .unwrap_or(false)
+    }
+
     fn msrv_compat_count(&self, summary: &Summary) -> usize {
         let Some(rust_version) = summary.rust_version() else {
             return self.rust_versions.len();
@@ -114,6 +175,40 @@ impl VersionPreferences {
             .filter(|max| rust_version.is_compatible_with(max))
             .count()
     }
+
+    /// Whether `summary` is removed by [`VersionPreferences::sort_summaries`]
+    /// for being published after [`VersionPreferences::publish_cutoff`].
+    pub fn is_denied_as_too_recent(&self, summary: &Summary) -> bool {
+        self.deny_recent && self.is_too_recent(summary)
+    }
+
+    /// Whether the resolver rejects `summary` for being affected by an
+    /// advisory of [`VersionPreferences::exclude_advisories`].
+    pub fn is_excluded_by_advisories(&self, summary: &Summary) -> bool {
+        match &self.advisories {
+            Some(advisories) => {
+                advisories.is_affected(summary.package_id())
+                    && !self.should_prefer(&summary.package_id())
+            }
+            None => false,
+        }
+    }
+
+    /// The advisories affecting `summary`, see
+    /// [`VersionPreferences::exclude_advisories`].
+    pub fn advisories_affecting(&self, summary: &Summary) -> Vec<&Advisory> {
+        match &self.advisories {
+            Some(advisories) => advisories.affecting(summary.package_id()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn is_too_recent(&self, summary: &Summary) -> bool {
+        match (self.publish_cutoff, summary.pubtime()) {
+            (Some(cutoff), Some(pubtime)) => pubtime > cutoff,
+            _ => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -301,6 +396,91 @@ mod test {
         );
     }
 
+    fn summ_published(name: &str, version: &str, pubtime: &str) -> Summary {
+        let mut summary = summ(name, version, None);
+        let pubtime =
+            OffsetDateTime::parse(pubtime, &time::format_description::well_known::Rfc3339).unwrap();
+        summary.set_pubtime(pubtime);
+        summary
+    }
+
+    fn cutoff() -> OffsetDateTime {
+        OffsetDateTime::parse(
+            "2024-06-01T00:00:00Z",
+            &time::format_description::well_known::Rfc3339,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_publish_cutoff_fallback() {
+        let mut vp = VersionPreferences::default();
+        vp.publish_cutoff(cutoff(), false);
+
+        let mut summaries = vec![
+            summ_published("foo", "1.2.4", "2024-06-02T00:00:00Z"),
+            summ_published("foo", "1.2.3", "2024-06-01T12:00:00Z"),
+            summ_published("foo", "1.2.2", "2024-05-01T00:00:00Z"),
+            summ("foo", "1.2.1", None),
+            summ_published("foo", "1.2.0", "2024-01-01T00:00:00Z"),
+        ];
+
+        vp.version_ordering(VersionOrdering::MaximumVersionsFirst);
+        vp.sort_summaries(&mut summaries, None);
+        assert_eq!(
+            describe(&summaries),
+            "foo/1.2.2, foo/1.2.1, foo/1.2.0, foo/1.2.4, foo/1.2.3".to_string()
+        );
+
+        vp.version_ordering(VersionOrdering::MinimumVersionsFirst);
+        vp.sort_summaries(&mut summaries, None);
+        assert_eq!(
+            describe(&summaries),
+            "foo/1.2.0, foo/1.2.1, foo/1.2.2, foo/1.2.3, foo/1.2.4".to_string()
+        );
+    }
+
+    #[test]
+    fn test_publish_cutoff_deny() {
+        let mut vp = VersionPreferences::default();
+        vp.publish_cutoff(cutoff(), true);
+        vp.prefer_package_id(pkgid("foo", "1.2.3"));
+
+        let mut summaries = vec![
+            summ_published("foo", "1.2.4", "2024-06-02T00:00:00Z"),
+            summ_published("foo", "1.2.3", "2024-06-01T12:00:00Z"),
+            summ_published("foo", "1.2.2", "2024-05-01T00:00:00Z"),
+            summ("foo", "1.2.1", None),
+        ];
+
+        vp.sort_summaries(&mut summaries, None);
+        assert_eq!(
+            describe(&summaries),
+            "foo/1.2.3, foo/1.2.2, foo/1.2.1".to_string()
+        );
+    }
+
+    #[test]
+    fn test_prefer_unified() {
+        let mut vp = VersionPreferences::default();
+        vp.prefer_unified(pkgid("foo", "1.1.0"));
+        vp.prefer_unified(pkgid("foo", "1.3.0"));
+        vp.rust_versions(vec!["1.50".parse().unwrap()]);
+
+        let mut summaries = vec![
+            summ("foo", "2.0.0", None),
+            summ("foo", "1.3.0", Some("1.60")),
+            summ("foo", "1.2.0", None),
+            summ("foo", "1.1.0", None),
+        ];
+
+        vp.sort_summaries(&mut summaries, None);
+        assert_eq!(
+            describe(&summaries),
+            "foo/1.1.0, foo/2.0.0, foo/1.2.0, foo/1.3.0".to_string()
+        );
+    }
+
     #[test]
     fn test_empty_summaries() {
         let vp = VersionPreferences::default();
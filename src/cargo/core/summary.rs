@@ -10,6 +10,7 @@ use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::mem;
 use std::sync::Arc;
+use time::OffsetDateTime;
 
 /// Subset of a `Manifest`. Contains only the most important information about
 /// a package.
@@ -28,6 +29,7 @@ struct Inner {
     checksum: Option<String>,
     links: Option<InternedString>,
     rust_version: Option<RustVersion>,
+    pubtime: Option<OffsetDateTime>,
 }
 
 /// Indicates the dependency inferred from the `dep` syntax that should exist,
@@ -89,6 +91,7 @@ impl Summary {
                 checksum: None,
                 links: links.map(|l| l.into()),
                 rust_version,
+                pubtime: None,
             }),
         })
     }
@@ -132,6 +135,15 @@ impl Summary {
         Arc::make_mut(&mut self.inner).checksum = Some(cksum);
     }
 
+    /// When this version was published, if known from the registry.
+    pub fn pubtime(&self) -> Option<OffsetDateTime> {
+        self.inner.pubtime
+    }
+
+    pub fn set_pubtime(&mut self, pubtime: OffsetDateTime) {
+        Arc::make_mut(&mut self.inner).pubtime = Some(pubtime);
+    }
+
     pub fn map_dependencies<F>(self, mut f: F) -> Summary
     where
         F: FnMut(Dependency) -> Dependency,
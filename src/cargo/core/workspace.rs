@@ -3,6 +3,7 @@ use std::collections::hash_map::{Entry, HashMap};
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::Duration;
 
 use anyhow::{anyhow, bail, Context as _};
 use glob::glob;
@@ -12,9 +13,10 @@ use url::Url;
 
 use crate::core::compiler::Unit;
 use crate::core::features::Features;
+use crate::core::gc;
 use crate::core::registry::PackageRegistry;
 use crate::core::resolver::features::CliFeatures;
-use crate::core::resolver::ResolveBehavior;
+use crate::core::resolver::{AdvisoryDatabase, ResolveBehavior};
 use crate::core::{
     Dependency, Edition, FeatureValue, PackageId, PackageIdSpec, PackageIdSpecQuery,
 };
@@ -30,7 +32,7 @@ use crate::util::lints::{
 use crate::util::toml::{read_manifest, InheritableFields};
 use crate::util::{
     context::CargoResolverConfig, context::ConfigRelativePath, context::IncompatibleRustVersions,
-    Filesystem, GlobalContext, IntoUrl,
+    context::RecentVersions, Filesystem, GlobalContext, IntoUrl,
 };
 use cargo_util::paths;
 use cargo_util::paths::normalize_path;
@@ -114,6 +116,15 @@ pub struct Workspace<'gctx> {
     /// and other places that use rust version.
     /// This is set based on the resolver version, config settings, and CLI flags.
     resolve_honors_rust_version: bool,
+    /// Versions of dependencies published less than this long ago are
+    /// avoided, from `resolver.min-publish-age`.
+    resolve_min_publish_age: Option<Duration>,
+    /// If `true`, versions published less than `resolve_min_publish_age` ago
+    /// are never selected, instead of only being selected as a fallback.
+    resolve_denies_recent_versions: bool,
+    /// Versions of dependencies affected by these advisories are avoided,
+    /// from `resolver.advisory-db`.
+    resolve_advisories: Option<Rc<AdvisoryDatabase>>,
 
     /// Workspace-level custom metadata
     custom_metadata: Option<toml::Value>,
@@ -248,6 +259,9 @@ impl<'gctx> Workspace<'gctx> {
             requested_lockfile_path: None,
             resolve_behavior: ResolveBehavior::V1,
             resolve_honors_rust_version: false,
+            resolve_min_publish_age: None,
+            resolve_denies_recent_versions: false,
+            resolve_advisories: None,
             custom_metadata: None,
             local_overlays: HashMap::new(),
         }
@@ -310,22 +324,59 @@ impl<'gctx> Workspace<'gctx> {
         }
         match self.gctx().get::<CargoResolverConfig>("resolver") {
             Ok(CargoResolverConfig {
-                incompatible_rust_versions: Some(incompatible_rust_versions),
+                incompatible_rust_versions,
+                min_publish_age,
+                recent_versions,
+                advisory_db,
             }) => {
-                if self.gctx().cli_unstable().msrv_policy {
-                    self.resolve_honors_rust_version =
-                        incompatible_rust_versions == IncompatibleRustVersions::Fallback;
-                } else {
-                    self.gctx()
-                        .shell()
-                        .warn("ignoring `resolver` config table without `-Zmsrv-policy`")?;
+                if let Some(incompatible_rust_versions) = incompatible_rust_versions {
+                    if self.gctx().cli_unstable().msrv_policy {
+                        self.resolve_honors_rust_version =
+                            incompatible_rust_versions == IncompatibleRustVersions::Fallback;
+                    } else {
+                        self.gctx()
+                            .shell()
+                            .warn("ignoring `resolver` config table without `-Zmsrv-policy`")?;
+                    }
+                }
+                if min_publish_age.is_some() || recent_versions.is_some() {
+                    if self.gctx().cli_unstable().min_publish_age {
+                        if let Some(min_publish_age) = min_publish_age {
+                            let age = gc::parse_time_span(&min_publish_age)
+                                .context("failed to parse `resolver.min-publish-age`")?;
+                            self.resolve_min_publish_age = Some(age);
+                        }
+                        self.resolve_denies_recent_versions =
+                            recent_versions == Some(RecentVersions::Deny);
+                        if self.resolve_min_publish_age.is_none() {
+                            self.gctx().shell().warn(
+                                "`resolver.recent-versions` has no effect \
+                                 without `resolver.min-publish-age`",
+                            )?;
+                        }
+                    } else {
+                        self.gctx().shell().warn(
+                            "ignoring `resolver.min-publish-age` and `resolver.recent-versions` \
+                             config without `-Zmin-publish-age`",
+                        )?;
+                    }
+                }
+                if let Some(advisory_db) = advisory_db {
+                    if self.gctx().cli_unstable().advisory_db {
+                        let path = advisory_db.resolve_path(self.gctx());
+                        let advisories = AdvisoryDatabase::load(&path)
+                            .context("failed to load `resolver.advisory-db`")?;
+                        self.resolve_advisories = Some(Rc::new(advisories));
+                    } else {
+                        self.gctx().shell().warn(
+                            "ignoring `resolver.advisory-db` config without `-Zadvisory-db`",
+                        )?;
+                    }
                 }
             }
-            Ok(CargoResolverConfig {
-                incompatible_rust_versions: None,
-            }) => {}
             Err(err) => {
-                if self.gctx().cli_unstable().msrv_policy {
+                let unstable = self.gctx().cli_unstable();
+                if unstable.msrv_policy || unstable.min_publish_age || unstable.advisory_db {
                     return Err(err);
                 } else {
                     self.gctx()
@@ -682,6 +733,18 @@ impl<'gctx> Workspace<'gctx> {
         self.resolve_honors_rust_version
     }
 
+    /// How long ago dependency versions must have been published to be
+    /// preferred, and whether more recent versions are denied altogether.
+    pub fn resolve_min_publish_age(&self) -> Option<(Duration, bool)> {
+        self.resolve_min_publish_age
+            .map(|age| (age, self.resolve_denies_recent_versions))
+    }
+
+    /// The advisories whose affected dependency versions are avoided.
+    pub fn resolve_advisories(&self) -> Option<&Rc<AdvisoryDatabase>> {
+        self.resolve_advisories.as_ref()
+    }
+
     pub fn custom_metadata(&self) -> Option<&toml::Value> {
         self.custom_metadata.as_ref()
     }
@@ -0,0 +1,70 @@
+//! Implementation of `cargo lockfile diff`.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use itertools::Itertools as _;
+
+use crate::core::{PackageId, Resolve, Workspace};
+use crate::drop_println;
+use crate::ops::cargo_update::{PackageChange, PackageChangeKind};
+use crate::ops::lockfile::LOCKFILE_NAME;
+use crate::util::CargoResult;
+
+use super::read_lockfile;
+
+/// Prints the packages added, removed, or changed between the lockfile at
+/// `old` and the one at `new`, or the lockfile of `ws` if `new` is `None`.
+pub fn diff(ws: &Workspace<'_>, old: &Path, new: Option<&Path>) -> CargoResult<()> {
+    let old = read_lockfile(ws, old)?;
+    let new = match new {
+        Some(new) => read_lockfile(ws, new)?,
+        None => read_lockfile(ws, &ws.lock_root().as_path_unlocked().join(LOCKFILE_NAME))?,
+    };
+
+    let gctx = ws.gctx();
+    for change in PackageChange::diff(ws, &old, &new).values() {
+        if change.kind != PackageChangeKind::Unchanged {
+            drop_println!(gctx, "{:>11} {change}", change.kind.status());
+            continue;
+        }
+        let details = describe_changes(&old, &new, change.package_id);
+        if !details.is_empty() {
+            drop_println!(gctx, "{:>11} {change}: {details}", "Changing");
+        }
+    }
+    Ok(())
+}
+
+/// Describes how the entry of a package in both lockfiles changed.
+///
+/// Dependencies are compared by name and source only, as the changes of
+/// their versions are listed for the dependencies themselves.
+fn describe_changes(old: &Resolve, new: &Resolve, pkg_id: PackageId) -> String {
+    let dep_names = |resolve: &Resolve| -> BTreeSet<_> {
+        resolve
+            .deps(pkg_id)
+            .map(|(dep, _)| (dep.name(), dep.source_id()))
+            .collect()
+    };
+    let old_deps = dep_names(old);
+    let new_deps = dep_names(new);
+
+    let mut details = Vec::new();
+    let dep_changes = new_deps
+        .difference(&old_deps)
+        .map(|(name, _)| format!("+{name}"))
+        .chain(
+            old_deps
+                .difference(&new_deps)
+                .map(|(name, _)| format!("-{name}")),
+        )
+        .join(", ");
+    if !dep_changes.is_empty() {
+        details.push(format!("dependencies {dep_changes}"));
+    }
+    if old.checksums().get(&pkg_id) != new.checksums().get(&pkg_id) {
+        details.push("checksum changed".to_string());
+    }
+    details.join("; ")
+}
@@ -0,0 +1,166 @@
+//! Implementation of `cargo lockfile merge`.
+//!
+//! The packages of the two lockfiles are merged as sets, relative to their
+//! common ancestor: a package is kept if both sides have it, or if one side
+//! added it. This can leave several SemVer-compatible versions of a package,
+//! or two different checksums for one, if both sides changed it. Only the
+//! higher version is kept then, or ours if both have the same one, so the
+//! result only depends on the two lockfiles. The merged set is then resolved
+//! offline, with every package locked.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use cargo_util::paths;
+use itertools::Itertools as _;
+
+use crate::core::resolver::features::CliFeatures;
+use crate::core::resolver::HasDevUnits;
+use crate::core::{Dependency, PackageId, Resolve, Workspace};
+use crate::ops;
+use crate::util::interning::InternedString;
+use crate::util::{CargoResult, Graph};
+
+use super::read_lockfile;
+
+pub struct MergeOptions<'a> {
+    /// The lockfile both sides started from.
+    pub base: &'a Path,
+    pub ours: &'a Path,
+    pub theirs: &'a Path,
+    /// Where to write the merged lockfile.
+    pub output: &'a Path,
+}
+
+/// Merges two lockfiles of `ws` and writes the result.
+pub fn merge(ws: &Workspace<'_>, opts: &MergeOptions<'_>) -> CargoResult<()> {
+    let base = read_lockfile(ws, opts.base)?;
+    let ours = read_lockfile(ws, opts.ours)?;
+    let theirs = read_lockfile(ws, opts.theirs)?;
+
+    let base_keys: HashSet<_> = base.iter().map(key).collect();
+    let ours_keys: HashSet<_> = ours.iter().map(key).collect();
+    let theirs_keys: HashSet<_> = theirs.iter().map(key).collect();
+    let keep = |k| {
+        let in_ours = ours_keys.contains(&k);
+        let in_theirs = theirs_keys.contains(&k);
+        (in_ours && in_theirs) || ((in_ours || in_theirs) && !base_keys.contains(&k))
+    };
+    let merged = ours.iter().filter(|id| keep(key(*id))).chain(
+        theirs
+            .iter()
+            .filter(|id| keep(key(*id)) && !ours_keys.contains(&key(*id))),
+    );
+
+    // Both sides changed these packages in different ways.
+    let mut conflicts: HashSet<InternedString> = HashSet::new();
+    let mut kept = HashMap::new();
+    for id in merged {
+        match kept.entry(id.as_activations_key()) {
+            Entry::Vacant(entry) => {
+                entry.insert(id);
+            }
+            Entry::Occupied(mut entry) => {
+                conflicts.insert(id.name());
+                if id.version() > entry.get().version() {
+                    entry.insert(id);
+                }
+            }
+        }
+    }
+    for id in ours.iter() {
+        if theirs.contains(&id) && ours.checksums().get(&id) != theirs.checksums().get(&id) {
+            conflicts.insert(id.name());
+        }
+    }
+
+    let merged: Vec<PackageId> = kept.into_values().collect();
+    let previous = merged_resolve(&merged, &ours, &theirs);
+    if !conflicts.is_empty() {
+        let names = conflicts
+            .iter()
+            .sorted()
+            .map(|name| format!("`{name}`"))
+            .join(", ");
+        ws.gctx().shell().note(format!(
+            "keeping the higher version of conflicting packages, or ours: {names}"
+        ))?;
+    }
+    let mut registry = ws.package_registry()?;
+    let resolve = ops::resolve_with_previous(
+        &mut registry,
+        ws,
+        &CliFeatures::new_all(true),
+        HasDevUnits::Yes,
+        Some(&previous),
+        None,
+        &[],
+        true,
+    )?;
+    ops::print_lockfile_changes(ws, Some(&ours), &resolve, &mut registry)?;
+
+    let out = ops::resolve_to_string(ws, &resolve)?;
+    paths::write(opts.output, out)?;
+    Ok(())
+}
+
+/// Identifies a package entry of a lockfile, including the revision of git
+/// packages which [`PackageId`] equality ignores.
+fn key(id: PackageId) -> (PackageId, Option<&'static str>) {
+    (id, id.source_id().precise_git_fragment())
+}
+
+/// Builds a resolve of the `merged` packages to lock the resolver to, with
+/// their dependencies from either side.
+fn merged_resolve(merged: &[PackageId], ours: &Resolve, theirs: &Resolve) -> Resolve {
+    let nodes: HashMap<_, _> = merged
+        .iter()
+        .map(|id| (id.as_activations_key(), *id))
+        .collect();
+    let mut graph: Graph<PackageId, HashSet<Dependency>> = Graph::new();
+    let mut checksums = HashMap::new();
+    let mut replacements = HashMap::new();
+    for &id in merged {
+        graph.add(id);
+        for side in [ours, theirs] {
+            if !side.contains(&id) {
+                continue;
+            }
+            for (dep_id, deps) in side.deps(id) {
+                if let Some(&dep_id) = nodes.get(&dep_id.as_activations_key()) {
+                    graph.link(id, dep_id).extend(deps.iter().cloned());
+                }
+            }
+            if let Some(replacement) = side.replacement(id) {
+                replacements.entry(id).or_insert(replacement);
+            }
+        }
+        let checksum = ours
+            .checksums()
+            .get(&id)
+            .or_else(|| theirs.checksums().get(&id));
+        if let Some(checksum) = checksum {
+            checksums.insert(id, checksum.clone());
+        }
+    }
+    let mut metadata = theirs.metadata().clone();
+    metadata.extend(ours.metadata().clone());
+    let unused_patches = ours
+        .unused_patches()
+        .iter()
+        .chain(theirs.unused_patches())
+        .copied()
+        .unique()
+        .collect();
+    Resolve::new(
+        graph,
+        replacements,
+        HashMap::new(),
+        checksums,
+        metadata,
+        unused_patches,
+        ours.version().max(theirs.version()),
+        HashMap::new(),
+    )
+}
@@ -0,0 +1,32 @@
+//! Implementation of the `cargo lockfile` subcommands, which compare and
+//! merge `Cargo.lock` files other than the one of the workspace, verify the
+//! one of the workspace, and make the ones of several workspaces agree.
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use cargo_util::paths;
+
+use crate::core::resolver::EncodableResolve;
+use crate::core::{Resolve, Workspace};
+use crate::util::CargoResult;
+
+mod diff;
+mod merge;
+mod unify;
+mod verify;
+
+pub use self::diff::diff;
+pub use self::merge::{merge, MergeOptions};
+pub use self::unify::unify;
+pub use self::verify::verify;
+
+/// Reads the lockfile at `path`, as if it was the lockfile of `ws`.
+fn read_lockfile(ws: &Workspace<'_>, path: &Path) -> CargoResult<Resolve> {
+    let s = paths::read(path)?;
+    (|| -> CargoResult<Resolve> {
+        let v: EncodableResolve = toml::from_str(&s)?;
+        v.into_resolve(&s, ws)
+    })()
+    .with_context(|| format!("failed to parse lock file at: {}", path.display()))
+}
@@ -0,0 +1,167 @@
+//! Implementation of `cargo lockfile unify`.
+//!
+//! The workspaces listed in the `super-workspace.members` config are
+//! resolved together, as if all their members were members of one
+//! workspace, so that they agree on the versions of their dependencies. The
+//! lockfile of each workspace is then the part of the joint resolve its
+//! members use, found by resolving the workspace again with the joint
+//! resolve locked.
+
+use std::path::PathBuf;
+
+use cargo_util::paths;
+use itertools::Itertools as _;
+
+use crate::core::resolver::features::{CliFeatures, RequestedFeatures};
+use crate::core::resolver::{self, HasDevUnits, ResolveOpts, ResolveVersion, VersionPreferences};
+use crate::core::{Resolve, Workspace};
+use crate::ops;
+use crate::ops::cargo_update::{PackageChange, PackageChangeKind};
+use crate::ops::lockfile::LOCKFILE_NAME;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::{CargoResult, GlobalContext};
+
+/// Resolves the workspaces of the `super-workspace` config together, and
+/// writes their lockfiles, or only checks that they agree with the joint
+/// resolve if `check` is set.
+pub fn unify(gctx: &GlobalContext, check: bool) -> CargoResult<()> {
+    let workspaces = super_workspace(gctx)?;
+    let joint = resolve_jointly(gctx, &workspaces)?;
+
+    let mut disagreeing = Vec::new();
+    for ws in &workspaces {
+        let lockfile = ws.lock_root().as_path_unlocked().join(LOCKFILE_NAME);
+        let previous = ops::load_pkg_lockfile(ws)?;
+        let mut registry = ws.package_registry()?;
+        let mut resolve = ops::resolve_with_previous(
+            &mut registry,
+            ws,
+            &CliFeatures::new_all(true),
+            HasDevUnits::Yes,
+            Some(&joint),
+            None,
+            &[],
+            true,
+        )?;
+        // Keep the encoding and metadata of the workspace lockfile, not the
+        // ones of the joint resolve.
+        match &previous {
+            Some(previous) => resolve.merge_from(previous)?,
+            None => {
+                resolve.set_version(ResolveVersion::with_rust_version(ws.lowest_rust_version()))
+            }
+        }
+
+        let changes: Vec<PackageChange> = match &previous {
+            Some(previous) => PackageChange::diff(ws, previous, &resolve)
+                .into_values()
+                .filter(|change| change.kind != PackageChangeKind::Unchanged)
+                .collect(),
+            None => Vec::new(),
+        };
+        if check {
+            if previous.is_none() {
+                disagreeing.push(format!("{}: no lockfile", lockfile.display()));
+            } else if !changes.is_empty() {
+                let changes = changes
+                    .iter()
+                    .map(|change| format!("  {} {change}", change.kind.status()))
+                    .join("\n");
+                disagreeing.push(format!("{}:\n{changes}", lockfile.display()));
+            }
+            continue;
+        }
+        if previous.is_none() || !changes.is_empty() {
+            gctx.shell()
+                .status("Unifying", lockfile.display().to_string())?;
+        }
+        ops::write_pkg_lockfile(ws, &mut resolve)?;
+        ops::print_lockfile_changes(ws, previous.as_ref(), &resolve, &mut registry)?;
+    }
+
+    if !disagreeing.is_empty() {
+        anyhow::bail!(
+            "{} of {} lockfiles don't agree with the joint resolve of \
+             `super-workspace.members`:\n{}",
+            disagreeing.len(),
+            workspaces.len(),
+            disagreeing.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Loads the workspaces listed in `super-workspace.members`.
+fn super_workspace(gctx: &GlobalContext) -> CargoResult<Vec<Workspace<'_>>> {
+    let Some(members) = gctx.get_list("super-workspace.members")? else {
+        anyhow::bail!("no workspaces listed in the `super-workspace.members` config");
+    };
+    let roots: Vec<PathBuf> = members
+        .val
+        .iter()
+        .map(|(path, def)| paths::normalize_path(&def.root(gctx).join(path)))
+        .unique()
+        .collect();
+    if roots.is_empty() {
+        anyhow::bail!("no workspaces listed in the `super-workspace.members` config");
+    }
+    let mut workspaces = Vec::new();
+    for root in roots {
+        let ws = Workspace::new(&root.join("Cargo.toml"), gctx)?;
+        // Patches only apply to the workspace they are declared in, so they
+        // can't be part of the joint resolve.
+        if !ws.root_patch()?.is_empty() || !ws.root_replace().is_empty() {
+            anyhow::bail!(
+                "`[patch]` and `[replace]` are not supported in the workspaces of \
+                 `super-workspace.members`, found in {}",
+                ws.root_manifest().display()
+            );
+        }
+        workspaces.push(ws);
+    }
+    Ok(workspaces)
+}
+
+/// Resolves the members of all `workspaces` together, preferring the
+/// versions locked in their lockfiles.
+fn resolve_jointly(gctx: &GlobalContext, workspaces: &[Workspace<'_>]) -> CargoResult<Resolve> {
+    let mut registry = workspaces[0].package_registry()?;
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+
+    let mut version_prefs = VersionPreferences::default();
+    let mut summaries = Vec::new();
+    for ws in workspaces {
+        ws.preload(&mut registry);
+        for member in ws.members() {
+            registry.add_sources(Some(member.package_id().source_id()))?;
+            summaries.push((
+                member.summary().clone(),
+                ResolveOpts {
+                    dev_deps: true,
+                    features: RequestedFeatures::CliFeatures(CliFeatures::new_all(true)),
+                },
+            ));
+        }
+        if let Some(previous) = ops::load_pkg_lockfile(ws)? {
+            registry.add_to_yanked_whitelist(previous.iter());
+            for id in previous.iter() {
+                version_prefs.prefer_package_id(id);
+            }
+        }
+    }
+    registry.lock_patches();
+
+    let version = workspaces
+        .iter()
+        .map(|ws| ResolveVersion::with_rust_version(ws.lowest_rust_version()))
+        .min()
+        .unwrap_or_else(|| ResolveVersion::with_rust_version(None));
+    resolver::resolve(
+        &summaries,
+        &[],
+        &mut registry,
+        &version_prefs,
+        version,
+        Some(gctx),
+    )
+}
@@ -0,0 +1,191 @@
+//! Implementation of `cargo lockfile verify`.
+//!
+//! Checks every entry of the workspace lockfile against the source it was
+//! locked from, after source replacement: that the version still exists and
+//! isn't yanked, that its checksum is the one of the registry, that git
+//! revisions can be fetched and that vendored files are unchanged. Entries
+//! which no workspace member depends on are reported as orphaned.
+
+use std::collections::{BTreeMap, HashSet};
+use std::task::Poll;
+
+use itertools::Itertools;
+
+use crate::core::{Dependency, PackageId, Resolve, SourceId, Workspace};
+use crate::ops;
+use crate::sources::source::{QueryKind, Source};
+use crate::sources::SourceConfigMap;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::CargoResult;
+
+/// Verifies the entries of the lockfile of `ws`.
+pub fn verify(ws: &Workspace<'_>) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let Some(resolve) = ops::load_pkg_lockfile(ws)? else {
+        anyhow::bail!(
+            "no `Cargo.lock` to verify in {}",
+            ws.lock_root().as_path_unlocked().display()
+        );
+    };
+
+    let reachable = reachable_from_members(ws, &resolve);
+    let mut problems: BTreeMap<PackageId, Vec<String>> = BTreeMap::new();
+    for id in resolve.iter() {
+        if !reachable.contains(&id) {
+            problems
+                .entry(id)
+                .or_default()
+                .push("orphaned, not used by any workspace member".to_string());
+        }
+    }
+
+    // Git packages of the same repository may be locked to different
+    // revisions, which need their own source.
+    let mut by_source: BTreeMap<(SourceId, Option<&str>), Vec<PackageId>> = BTreeMap::new();
+    for id in resolve.iter().sorted() {
+        let source_id = id.source_id();
+        if source_id.is_path() {
+            continue;
+        }
+        by_source
+            .entry((source_id, source_id.precise_git_fragment()))
+            .or_default()
+            .push(id);
+    }
+
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+    let source_config = SourceConfigMap::new(gctx)?;
+    for ((source_id, _), ids) in by_source {
+        let whitelist: HashSet<PackageId> = ids.iter().copied().collect();
+        let mut source = match source_config.load(source_id, &whitelist) {
+            Ok(source) => source,
+            Err(e) => {
+                for id in ids {
+                    problems.entry(id).or_default().push(format!("{e:#}"));
+                }
+                continue;
+            }
+        };
+        // Check against the current state of the source, not the one the
+        // lockfile was created from.
+        if !source_id.is_git() {
+            source.invalidate_cache();
+        }
+        for id in ids {
+            let checksum = resolve.checksums().get(&id).cloned().flatten();
+            let found = match verify_package(&mut *source, id, checksum.as_deref()) {
+                Ok(found) => found,
+                Err(e) => vec![format!("{e:#}")],
+            };
+            if !found.is_empty() {
+                problems.entry(id).or_default().extend(found);
+            }
+        }
+    }
+
+    if !problems.is_empty() {
+        let count = problems.len();
+        let entries = problems
+            .iter()
+            .map(|(id, reasons)| {
+                let reasons = reasons
+                    .iter()
+                    .map(|reason| {
+                        let mut lines = reason.lines();
+                        let first = lines.next().unwrap_or_default();
+                        let rest = lines.map(|line| {
+                            if line.is_empty() {
+                                "\n".to_string()
+                            } else {
+                                format!("\n    {line}")
+                            }
+                        });
+                        format!("  - {first}{}", rest.collect::<String>())
+                    })
+                    .join("\n");
+                format!("{id}:\n{reasons}")
+            })
+            .join("\n");
+        anyhow::bail!(
+            "{count} {} of `Cargo.lock` failed verification:\n{entries}",
+            if count == 1 { "entry" } else { "entries" }
+        );
+    }
+    gctx.shell().status(
+        "Verified",
+        format!("{} entries of `Cargo.lock`", resolve.iter().count()),
+    )?;
+    Ok(())
+}
+
+/// The packages of `resolve` the workspace members depend on, including the
+/// members themselves.
+fn reachable_from_members(ws: &Workspace<'_>, resolve: &Resolve) -> HashSet<PackageId> {
+    let mut reachable = HashSet::new();
+    let mut queue: Vec<PackageId> = ws.members().map(|pkg| pkg.package_id()).collect();
+    while let Some(id) = queue.pop() {
+        if !reachable.insert(id) {
+            continue;
+        }
+        queue.extend(resolve.deps_not_replaced(id).map(|(dep_id, _)| dep_id));
+        queue.extend(resolve.replacement(id));
+    }
+    reachable
+}
+
+/// Checks `id` against `source`, returning what is wrong with it.
+fn verify_package(
+    source: &mut dyn Source,
+    id: PackageId,
+    locked_checksum: Option<&str>,
+) -> CargoResult<Vec<String>> {
+    let mut dep = Dependency::parse(id.name(), None, id.source_id())?;
+    dep.lock_to(id);
+    let mut summaries = Vec::new();
+    loop {
+        summaries.clear();
+        let poll = source.query(&dep, QueryKind::Exact, &mut |s| {
+            summaries.push(s.into_summary())
+        });
+        match poll {
+            Poll::Ready(res) => break res?,
+            Poll::Pending => source.block_until_ready()?,
+        }
+    }
+    let Some(summary) = summaries.iter().find(|s| s.package_id() == id) else {
+        return Ok(vec![format!(
+            "version {} not found in {}",
+            id.version(),
+            source.describe()
+        )]);
+    };
+
+    let mut problems = Vec::new();
+    match (locked_checksum, summary.checksum()) {
+        (Some(locked), Some(actual)) if locked != actual => problems.push(format!(
+            "checksum `{locked}` does not match checksum `{actual}` of {}",
+            source.describe()
+        )),
+        (None, Some(_)) => {
+            problems.push(format!("missing checksum, {} has one", source.describe()))
+        }
+        (Some(_), None) => problems.push(format!(
+            "has a checksum, but {} has none",
+            source.describe()
+        )),
+        _ => {}
+    }
+    let yanked = loop {
+        match source.is_yanked(id) {
+            Poll::Ready(res) => break res?,
+            Poll::Pending => source.block_until_ready()?,
+        }
+    };
+    if yanked {
+        problems.push(format!("yanked in {}", source.describe()));
+    }
+    if let Err(e) = source.verify(id) {
+        problems.push(format!("{e:#}"));
+    }
+    Ok(problems)
+}
@@ -0,0 +1,284 @@
+//! Implementation of `cargo mirror`.
+//!
+//! The packages from crates.io listed in a set of lockfiles are downloaded,
+//! and written with their index entries in the layout of a local registry:
+//!
+//! ```text
+//! <dir>/<name>-<version>.crate
+//! <dir>/index/config.json
+//! <dir>/index/<prefix>/<name>
+//! ```
+//!
+//! The `index` directory is also a sparse index, whose `config.json` points
+//! to the `.crate` files, so the mirror can be served as is by a static file
+//! server. Running `cargo mirror` again adds packages to an existing mirror.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::task::Poll;
+
+use anyhow::Context as _;
+use cargo_util::{paths, Sha256};
+use serde::{Deserialize, Serialize};
+
+use crate::core::registry::PackageRegistry;
+use crate::core::{Dependency, PackageId, PackageSet, Registry, SourceId, Summary};
+use crate::sources::registry::{self, IndexSummary};
+use crate::sources::source::QueryKind;
+use crate::sources::SourceConfigMap;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::interning::InternedString;
+use crate::util::{CargoResult, GlobalContext};
+
+pub struct MirrorOptions<'a> {
+    /// The lockfiles listing the packages to mirror.
+    pub lockfiles: &'a [PathBuf],
+    /// The directory to write the mirror to.
+    pub destination: &'a Path,
+    /// The URL the mirror will be served from, if not only used locally.
+    pub url: Option<&'a str>,
+}
+
+/// The parts of a lockfile `cargo mirror` needs, which can be read without
+/// loading the workspace it belongs to.
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    checksum: Option<String>,
+}
+
+/// The `config.json` of a sparse index.
+#[derive(Serialize)]
+struct IndexConfig {
+    dl: String,
+}
+
+/// The version of an index entry, to merge the entries of an existing
+/// mirror.
+#[derive(Deserialize)]
+struct IndexEntryVersion {
+    vers: semver::Version,
+}
+
+pub fn mirror(gctx: &GlobalContext, opts: &MirrorOptions<'_>) -> CargoResult<()> {
+    let crates_io = SourceId::crates_io(gctx)?;
+    let locked = locked_packages(gctx, opts.lockfiles, crates_io)?;
+
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+    let mut registry = PackageRegistry::new_with_source_config(gctx, SourceConfigMap::new(gctx)?)?;
+    registry.add_sources([crates_io])?;
+    registry.add_to_yanked_whitelist(locked.keys().copied());
+    registry.lock_patches();
+
+    let mut summaries = BTreeMap::new();
+    for (&id, checksum) in &locked {
+        let summary = query_exact(&mut registry, id)?;
+        let index_checksum = summary.checksum();
+        if let (Some(checksum), Some(index_checksum)) = (checksum, index_checksum) {
+            if checksum != index_checksum {
+                anyhow::bail!(
+                    "the checksum of `{id}` in the lockfile doesn't match the one of the registry, \
+                     expected {index_checksum}, found {checksum}"
+                );
+            }
+        }
+        summaries.insert(id, summary);
+    }
+
+    let ids: Vec<PackageId> = locked.keys().copied().collect();
+    let set = registry.get(&ids)?;
+    let mut index_lines = index_lines(&set, &ids)?;
+    let packages = set.get_many(ids.iter().copied())?;
+
+    let dst = gctx.cwd().join(opts.destination);
+    let index_dir = dst.join("index");
+    paths::create_dir_all(&index_dir)?;
+    let mut entries: BTreeMap<String, Vec<(semver::Version, String)>> = BTreeMap::new();
+    for pkg in &packages {
+        let id = pkg.package_id();
+        let summary = &summaries[&id];
+        let Some(crate_file) = registry::cached_crate_path(gctx, pkg) else {
+            anyhow::bail!("could not find the downloaded `.crate` file of `{id}`");
+        };
+        let checksum = Sha256::new().update_path(&crate_file)?.finish_hex();
+        if summary.checksum() != Some(checksum.as_str()) {
+            anyhow::bail!("failed to verify the checksum of `{id}`");
+        }
+        paths::copy(&crate_file, dst.join(id.tarball_name()))?;
+
+        let Some(index_line) = index_lines.remove(&id) else {
+            anyhow::bail!("could not find `{id}` in the registry");
+        };
+        entries
+            .entry(index_path(id.name()))
+            .or_default()
+            .push((id.version().clone(), index_line));
+    }
+
+    for (path, mut lines) in entries {
+        let path = index_dir.join(path);
+        if path.exists() {
+            for line in paths::read(&path)?.lines().filter(|line| !line.is_empty()) {
+                let entry: IndexEntryVersion = serde_json::from_str(line)
+                    .with_context(|| format!("failed to parse index file `{}`", path.display()))?;
+                if !lines.iter().any(|(version, _)| *version == entry.vers) {
+                    lines.push((entry.vers, line.to_string()));
+                }
+            }
+        }
+        lines.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut contents = String::new();
+        for (_, line) in lines {
+            contents.push_str(&line);
+            contents.push('\n');
+        }
+        paths::create_dir_all(path.parent().unwrap())?;
+        paths::write_if_changed(&path, contents)?;
+    }
+
+    let url = match opts.url {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None => url::Url::from_directory_path(&dst)
+            .map_err(|()| anyhow::format_err!("invalid mirror path `{}`", dst.display()))?
+            .as_str()
+            .trim_end_matches('/')
+            .to_string(),
+    };
+    let config = IndexConfig {
+        dl: format!("{url}/{{crate}}-{{version}}.crate"),
+    };
+    let mut config = serde_json::to_string_pretty(&config)?;
+    config.push('\n');
+    paths::write_if_changed(index_dir.join("config.json"), config)?;
+
+    gctx.shell().status(
+        "Mirrored",
+        format!(
+            "{} package{} to {}",
+            packages.len(),
+            if packages.len() == 1 { "" } else { "s" },
+            opts.destination.display()
+        ),
+    )?;
+
+    let source = match opts.url {
+        Some(_) => format!("registry = \"sparse+{url}/index/\""),
+        None => format!(
+            "local-registry = {}",
+            toml::Value::String(dst.display().to_string())
+        ),
+    };
+    crate::drop_eprint!(
+        gctx,
+        "To use the mirror, add this to your .cargo/config.toml:\n\n"
+    );
+    crate::drop_print!(
+        gctx,
+        "[source.crates-io]\nreplace-with = \"mirror\"\n\n[source.mirror]\n{source}\n"
+    );
+    Ok(())
+}
+
+/// The packages from crates.io listed in `lockfiles`, with their checksums.
+fn locked_packages(
+    gctx: &GlobalContext,
+    lockfiles: &[PathBuf],
+    crates_io: SourceId,
+) -> CargoResult<BTreeMap<PackageId, Option<String>>> {
+    let mut locked = BTreeMap::new();
+    for lockfile in lockfiles {
+        let path = gctx.cwd().join(lockfile);
+        let contents = paths::read(&path)?;
+        let lockfile: Lockfile = toml::from_str(&contents)
+            .with_context(|| format!("failed to parse lock file at: {}", path.display()))?;
+        for pkg in lockfile.package {
+            let Some(source) = &pkg.source else {
+                continue;
+            };
+            let source_id = SourceId::from_url(source).with_context(|| {
+                format!("invalid source of `{}` in {}", pkg.name, path.display())
+            })?;
+            if source_id.is_git() {
+                continue;
+            }
+            let id = PackageId::try_new(pkg.name.as_str(), pkg.version.as_str(), source_id)?;
+            if source_id != crates_io {
+                gctx.shell().warn(format!(
+                    "skipping `{id}`, only packages from crates.io are mirrored"
+                ))?;
+                continue;
+            }
+            let checksum = locked.entry(id).or_insert(None);
+            if let (Some(previous), Some(checksum)) = (checksum.as_ref(), pkg.checksum.as_ref()) {
+                if previous != checksum {
+                    anyhow::bail!("lockfiles disagree on the checksum of `{id}`");
+                }
+            }
+            if checksum.is_none() {
+                *checksum = pkg.checksum;
+            }
+        }
+    }
+    Ok(locked)
+}
+
+/// The summary of the package `id` in the registry.
+fn query_exact(registry: &mut PackageRegistry<'_>, id: PackageId) -> CargoResult<Summary> {
+    let mut dep = Dependency::parse(id.name(), None, id.source_id())?;
+    dep.lock_to(id);
+    let summaries = loop {
+        match registry.query_vec(&dep, QueryKind::Exact) {
+            Poll::Ready(res) => break res?,
+            Poll::Pending => registry.block_until_ready()?,
+        }
+    };
+    summaries
+        .into_iter()
+        .map(IndexSummary::into_summary)
+        .find(|s| s.package_id() == id)
+        .ok_or_else(|| anyhow::format_err!("could not find `{id}` in the registry"))
+}
+
+/// The lines of the index files of the packages `ids` of `set`, as
+/// published by the registry.
+fn index_lines(set: &PackageSet<'_>, ids: &[PackageId]) -> CargoResult<HashMap<PackageId, String>> {
+    let mut sources = set.sources_mut();
+    let mut pending = ids.to_vec();
+    let mut lines = HashMap::new();
+    while !pending.is_empty() {
+        let mut result = Ok(());
+        pending.retain(|&id| {
+            let Some(source) = sources.get_mut(id.source_id()) else {
+                return false;
+            };
+            match source.index_line(id) {
+                Poll::Ready(Ok(Some(line))) => {
+                    lines.insert(id, line);
+                }
+                Poll::Ready(Ok(None)) => {}
+                Poll::Ready(Err(e)) => result = Err(e),
+                Poll::Pending => return true,
+            }
+            false
+        });
+        result?;
+        for (_id, source) in sources.sources_mut() {
+            source.block_until_ready()?;
+        }
+    }
+    Ok(lines)
+}
+
+/// The path of the index file of the package `name`, relative to the index
+/// directory.
+fn index_path(name: InternedString) -> String {
+    cargo_util::registry::make_dep_path(&name.to_lowercase(), false)
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io::SeekFrom;
@@ -41,6 +41,9 @@ pub struct PackageOpts<'gctx> {
     pub check_metadata: bool,
     pub allow_dirty: bool,
     pub verify: bool,
+    /// Package again from a scratch copy of the sources, and check that the
+    /// result is the same.
+    pub verify_reproducible: bool,
     pub jobs: Option<JobsConfig>,
     pub keep_going: bool,
     pub to_package: ops::Packages,
@@ -52,6 +55,7 @@ pub struct PackageOpts<'gctx> {
 const ORIGINAL_MANIFEST_FILE: &str = "Cargo.toml.orig";
 const VCS_INFO_FILE: &str = ".cargo_vcs_info.json";
 
+#[derive(Clone)]
 struct ArchiveFile {
     /// The relative path in the archive (not including the top-level package
     /// name directory).
@@ -62,6 +66,7 @@ struct ArchiveFile {
     contents: FileContents,
 }
 
+#[derive(Clone)]
 enum FileContents {
     /// Absolute path to the file on disk to add to the archive.
     OnDisk(PathBuf),
@@ -69,6 +74,7 @@ enum FileContents {
     Generated(GeneratedFile),
 }
 
+#[derive(Clone)]
 enum GeneratedFile {
     /// Generates `Cargo.toml` by rewriting the original.
     Manifest,
@@ -78,14 +84,14 @@ enum GeneratedFile {
     VcsInfo(VcsInfo),
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 struct VcsInfo {
     git: GitVcsInfo,
     /// Path to the package within repo (empty string if root). / not \
     path_in_vcs: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 struct GitVcsInfo {
     sha1: String,
     /// Indicate whether or not the Git worktree is dirty.
@@ -249,7 +255,18 @@ fn do_package<'a>(
                 drop_println!(ws.gctx(), "{}", ar_file.rel_str);
             }
         } else {
+            let vcs_info = opts.verify_reproducible.then(|| {
+                ar_files.iter().find_map(|ar_file| match &ar_file.contents {
+                    FileContents::Generated(GeneratedFile::VcsInfo(vcs_info)) => {
+                        Some(vcs_info.clone())
+                    }
+                    _ => None,
+                })
+            });
             let tarball = create_package(ws, &pkg, ar_files, local_reg.as_ref())?;
+            if let Some(vcs_info) = vcs_info {
+                verify_reproducible(ws, &pkg, vcs_info, local_reg.as_ref(), &tarball)?;
+            }
             if let Some(local_reg) = local_reg.as_mut() {
                 if pkg.publish() != &Some(Vec::new()) {
                     local_reg.add_package(ws, &pkg, &tarball)?;
@@ -904,6 +921,7 @@ fn tar(
     // Put all package files into a compressed archive.
     let mut ar = Builder::new(encoder);
     let gctx = ws.gctx();
+    let source_date_epoch = source_date_epoch(gctx)?;
 
     let base_name = format!("{}-{}", pkg.name(), pkg.version());
     let base_path = Path::new(&base_name);
@@ -933,6 +951,9 @@ fn tar(
                     format!("could not learn metadata for: `{}`", disk_path.display())
                 })?;
                 header.set_metadata_in_mode(&metadata, HeaderMode::Deterministic);
+                if let Some(mtime) = source_date_epoch {
+                    header.set_mtime(mtime);
+                }
                 header.set_cksum();
                 ar.append_data(&mut header, &ar_path, &mut file)
                     .with_context(|| {
@@ -950,7 +971,7 @@ fn tar(
                 header.set_mode(0o644);
                 header.set_size(contents.len() as u64);
                 // use something nonzero to avoid rust-lang/cargo#9512
-                header.set_mtime(1);
+                header.set_mtime(source_date_epoch.unwrap_or(1));
                 header.set_cksum();
                 ar.append_data(&mut header, &ar_path, contents.as_bytes())
                     .with_context(|| format!("could not archive source file `{}`", rel_str))?;
@@ -964,6 +985,179 @@ fn tar(
     Ok(uncompressed_size)
 }
 
+/// The modification time of the files of the archive, from the
+/// [`SOURCE_DATE_EPOCH`] environment variable, if set with
+/// `-Zunstable-options`.
+///
+/// [`SOURCE_DATE_EPOCH`]: https://reproducible-builds.org/specs/source-date-epoch/
+fn source_date_epoch(gctx: &GlobalContext) -> CargoResult<Option<u64>> {
+    if !gctx.cli_unstable().unstable_options {
+        return Ok(None);
+    }
+    let Some(value) = gctx.get_env_os("SOURCE_DATE_EPOCH") else {
+        return Ok(None);
+    };
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.to_str().and_then(|value| value.parse::<u64>().ok()) {
+        // use something nonzero to avoid rust-lang/cargo#9512
+        Some(0) => Ok(Some(1)),
+        Some(mtime) => Ok(Some(mtime)),
+        None => bail!(
+            "invalid `SOURCE_DATE_EPOCH` environment variable `{}`, \
+             expected a number of seconds since the Unix epoch",
+            value.to_string_lossy()
+        ),
+    }
+}
+
+/// Packages `pkg` again from a clean copy of `tarball`, extracted in a
+/// scratch directory, and checks that the result is byte for byte the same as
+/// `tarball`.
+///
+/// The copy is packaged like any other package: its files are listed again and
+/// its `Cargo.lock` is resolved again, in a new workspace, with a new Cargo
+/// home and target directory. The scratch directory is not a git checkout, so
+/// its `.cargo_vcs_info.json` is generated from the `vcs_info` of `pkg`.
+fn verify_reproducible(
+    ws: &Workspace<'_>,
+    pkg: &Package,
+    vcs_info: Option<VcsInfo>,
+    local_reg: Option<&TmpRegistry<'_>>,
+    tarball: &FileLock,
+) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    gctx.shell()
+        .status("Repackaging", pkg.package_id().to_string())?;
+
+    let scratch = tempfile::Builder::new()
+        .prefix(".repackage-")
+        .tempdir_in(tarball.parent())?;
+    tarball.file().seek(SeekFrom::Start(0))?;
+    let mut archive = Archive::new(GzDecoder::new(tarball.file()));
+    archive.set_preserve_mtime(false);
+    archive.unpack(scratch.path())?;
+
+    // The files generated by packaging use reserved names, so they can't be
+    // part of the sources of the copy.
+    let root = scratch
+        .path()
+        .join(format!("{}-{}", pkg.name(), pkg.version()));
+    let original_manifest = scratch.path().join(ORIGINAL_MANIFEST_FILE);
+    fs::rename(root.join(ORIGINAL_MANIFEST_FILE), &original_manifest)?;
+    let vcs_info_path = root.join(VCS_INFO_FILE);
+    if vcs_info_path.exists() {
+        paths::remove_file(&vcs_info_path)?;
+    }
+
+    // Only keep the configuration of the Cargo home, so that the sources and
+    // registries are the same.
+    let home = scratch.path().join("home");
+    paths::create_dir_all(&home)?;
+    for name in ["config", "config.toml", "credentials", "credentials.toml"] {
+        let path = gctx.home().as_path_unlocked().join(name);
+        if path.is_file() {
+            paths::copy(&path, home.join(name))?;
+        }
+    }
+    let repackage_gctx = gctx.with_home(home, scratch.path().join("target"))?;
+
+    let id = SourceId::for_path(&root)?;
+    let mut src = PathSource::new(&root, id, &repackage_gctx);
+    let new_pkg = src.root_package()?;
+    let mut new_ws = Workspace::ephemeral(new_pkg.clone(), &repackage_gctx, None, true)?;
+    if let Some(local_reg) = local_reg {
+        new_ws.add_local_overlay(
+            local_reg.upstream,
+            local_reg.root.as_path_unlocked().to_owned(),
+        );
+    }
+    let src_files = src.list_files(&new_pkg)?;
+    let mut ar_files = build_ar_list(&new_ws, &new_pkg, src_files, vcs_info)?;
+    // The manifest of the copy is the normalized one, not the original one.
+    for ar_file in &mut ar_files {
+        if ar_file.rel_str == ORIGINAL_MANIFEST_FILE {
+            ar_file.contents = FileContents::OnDisk(original_manifest.clone());
+        }
+    }
+
+    let filename = pkg.package_id().tarball_name();
+    let repackaged_path = scratch.path().join(&filename);
+    let repackaged = File::create(&repackaged_path)?;
+    tar(
+        &new_ws,
+        &new_pkg,
+        local_reg,
+        ar_files,
+        &repackaged,
+        &filename,
+    )
+    .context("failed to package again")?;
+
+    let mut original = Vec::new();
+    tarball.file().seek(SeekFrom::Start(0))?;
+    tarball.file().read_to_end(&mut original)?;
+    let repackaged = paths::read_bytes(&repackaged_path)?;
+    if original != repackaged {
+        let mut differences = archive_differences(&original, &repackaged)?;
+        if differences.is_empty() {
+            differences.push("the compressed archives differ".to_string());
+        }
+        bail!(
+            "`{}` is not reproducible, packaging it again produced a different `{filename}`:\n  {}",
+            pkg.package_id(),
+            differences.join("\n  ")
+        );
+    }
+    Ok(())
+}
+
+/// The differences between the entries of the `.crate` files `a` and `b`.
+fn archive_differences(a: &[u8], b: &[u8]) -> CargoResult<Vec<String>> {
+    /// The path, metadata and contents of each entry of a `.crate` file.
+    fn entries(archive: &[u8]) -> CargoResult<BTreeMap<String, (u32, u64, Vec<u8>)>> {
+        let mut archive = Archive::new(GzDecoder::new(archive));
+        let mut entries = BTreeMap::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.to_string_lossy().into_owned();
+            let mode = entry.header().mode()?;
+            let mtime = entry.header().mtime()?;
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            entries.insert(path, (mode, mtime, contents));
+        }
+        Ok(entries)
+    }
+
+    let a = entries(a)?;
+    let b = entries(b)?;
+    let mut differences = Vec::new();
+    for path in a
+        .keys()
+        .chain(b.keys().filter(|path| !a.contains_key(*path)))
+    {
+        match (a.get(path), b.get(path)) {
+            (Some(_), None) => differences.push(format!("{path}: missing the second time")),
+            (None, Some(_)) => differences.push(format!("{path}: missing the first time")),
+            (Some((a_mode, a_mtime, a_contents)), Some((b_mode, b_mtime, b_contents))) => {
+                if a_mode != b_mode {
+                    differences.push(format!("{path}: mode {a_mode:o} != {b_mode:o}"));
+                }
+                if a_mtime != b_mtime {
+                    differences.push(format!("{path}: mtime {a_mtime} != {b_mtime}"));
+                }
+                if a_contents != b_contents {
+                    differences.push(format!("{path}: contents differ"));
+                }
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+    Ok(differences)
+}
+
 /// Generate warnings when packaging Cargo.lock, and the resolve have changed.
 fn compare_resolve(
     gctx: &GlobalContext,
@@ -1383,6 +1577,7 @@ impl<'a> TmpRegistry<'a> {
             yanked: None,
             links: new_crate.links.map(|x| x.into()),
             rust_version: None,
+            pubtime: None,
             v: Some(2),
         })?;
 
@@ -0,0 +1,4 @@
+//! Implementation of `cargo report` subcommands that need more than a lookup
+//! of an on-disk report.
+
+pub mod timings;
@@ -0,0 +1,356 @@
+//! Implementation of `cargo report timings`.
+//!
+//! This reads the timing data saved by `--timings=json-file` in
+//! `target/cargo-timings` and lists past runs, shows the slowest units of a
+//! run, or compares two runs unit by unit.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context as _};
+
+use crate::core::compiler::timings::{TimingData, TimingUnit};
+use crate::core::Workspace;
+use crate::drop_println;
+use crate::util::CargoResult;
+use crate::GlobalContext;
+
+pub struct ReportTimingsOptions<'a> {
+    /// Only list the available runs.
+    pub list: bool,
+    /// The run to report on, either an id or a path to a timing data file.
+    /// Defaults to the most recent run.
+    pub id: Option<&'a str>,
+    /// A run to compare `id` against.
+    pub compare: Option<&'a str>,
+    /// Maximum number of units to display.
+    pub top: usize,
+}
+
+/// A timing data file saved by a previous build.
+struct TimingRun {
+    /// The timestamp identifying the run.
+    id: String,
+    data: TimingData,
+}
+
+pub fn report_timings(ws: &Workspace<'_>, opts: &ReportTimingsOptions<'_>) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let timings_dir = ws.target_dir().as_path_unlocked().join("cargo-timings");
+    if opts.list {
+        let runs = load_runs(gctx, &timings_dir)?;
+        if runs.is_empty() {
+            bail!("{}", no_runs_message(&timings_dir));
+        }
+        let mut out = String::new();
+        writeln!(
+            out,
+            "{:<18} {:<10} {:>6} {:>10}  {}",
+            "ID", "PROFILE", "UNITS", "DURATION", "STATUS"
+        )?;
+        for run in &runs {
+            writeln!(
+                out,
+                "{:<18} {:<10} {:>6} {:>10}  {}",
+                run.id,
+                run.data.profile,
+                run.data.units.len(),
+                format!("{:.1}s", run.data.duration),
+                if run.data.error.is_some() {
+                    "failed"
+                } else {
+                    "ok"
+                },
+            )?;
+        }
+        drop_println!(gctx, "{}", out.trim_end());
+        return Ok(());
+    }
+
+    let run = find_run(gctx, &timings_dir, opts.id)?;
+    let out = match opts.compare {
+        None => render_run(&run, opts.top)?,
+        Some(base) => {
+            let base = find_run(gctx, &timings_dir, Some(base))?;
+            render_comparison(&base, &run, opts.top)?
+        }
+    };
+    drop_println!(gctx, "{}", out.trim_end());
+    Ok(())
+}
+
+fn no_runs_message(timings_dir: &Path) -> String {
+    format!(
+        "no timing data found in `{}`\n\n\
+         help: build with `-Zunstable-options --timings=json-file` to save timing data",
+        timings_dir.display()
+    )
+}
+
+/// Loads all timestamped timing data files, oldest first.
+///
+/// Files that can't be loaded, like ones saved by another version of Cargo,
+/// are skipped with a warning.
+fn load_runs(gctx: &GlobalContext, timings_dir: &Path) -> CargoResult<Vec<TimingRun>> {
+    let mut runs = Vec::new();
+    let entries = match std::fs::read_dir(timings_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(runs),
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to read `{}`", timings_dir.display()))
+        }
+    };
+    for entry in entries {
+        let path = entry?.path();
+        let Some(id) = run_id(&path) else {
+            continue;
+        };
+        match TimingData::load(&path) {
+            Ok(data) => runs.push(TimingRun { id, data }),
+            Err(e) => gctx.shell().warn(format!("skipping timing data: {e:#}"))?,
+        }
+    }
+    runs.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(runs)
+}
+
+/// Returns the id of a run from the name of its timestamped timing data file.
+fn run_id(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let id = name.strip_prefix("cargo-timing-")?.strip_suffix(".json")?;
+    // Skip `--timings=trace` output.
+    if id.ends_with(".trace") {
+        return None;
+    }
+    Some(id.to_string())
+}
+
+/// Finds a run by id or path, or the most recent run if `id` is `None`.
+fn find_run(gctx: &GlobalContext, timings_dir: &Path, id: Option<&str>) -> CargoResult<TimingRun> {
+    if let Some(id) = id {
+        let path = PathBuf::from(id);
+        if path.is_file() {
+            let data = TimingData::load(&path)?;
+            return Ok(TimingRun {
+                id: id.to_string(),
+                data,
+            });
+        }
+    }
+    let runs = load_runs(gctx, timings_dir)?;
+    if runs.is_empty() {
+        bail!("{}", no_runs_message(timings_dir));
+    }
+    match id {
+        None => Ok(runs.into_iter().last().unwrap()),
+        Some(id) => match runs.into_iter().find(|run| run.id == id) {
+            Some(run) => Ok(run),
+            None => bail!(
+                "timing data with id `{id}` not found\n\n\
+                 help: use `cargo report timings --list` to list available runs"
+            ),
+        },
+    }
+}
+
+fn unit_name(unit: &TimingUnit) -> String {
+    format!("{} v{}{}", unit.name, unit.version, unit.target)
+}
+
+fn codegen_time(unit: &TimingUnit) -> Option<f64> {
+    unit.rmeta_time.map(|rmeta_time| unit.duration - rmeta_time)
+}
+
+/// The average number of units running at once over the whole build.
+fn average_parallelism(data: &TimingData) -> f64 {
+    if data.duration <= 0.0 {
+        return 0.0;
+    }
+    data.units.iter().map(|u| u.duration).sum::<f64>() / data.duration
+}
+
+fn render_run(run: &TimingRun, top: usize) -> CargoResult<String> {
+    let data = &run.data;
+    let mut out = String::new();
+    let roots: Vec<_> = data.roots.iter().map(|r| r.package.as_str()).collect();
+    writeln!(out, "Timing report {} ({})", run.id, roots.join(", "))?;
+    writeln!(out, "  Profile: {}", data.profile)?;
+    writeln!(
+        out,
+        "  Units: {} ({} fresh, {} dirty)",
+        data.fresh_units + data.dirty_units,
+        data.fresh_units,
+        data.dirty_units
+    )?;
+    writeln!(out, "  Total time: {:.1}s", data.duration)?;
+    let max_concurrency = data.concurrency.iter().map(|c| c.active).max().unwrap_or(0);
+    writeln!(
+        out,
+        "  Parallelism: {:.1} average, {} max (jobs={})",
+        average_parallelism(data),
+        max_concurrency,
+        data.jobs
+    )?;
+    if !data.critical_path.is_empty() {
+        writeln!(
+            out,
+            "  Critical path: {:.1}s ({} units)",
+            data.critical_path_duration,
+            data.critical_path.len()
+        )?;
+    }
+    if let Some(error) = &data.error {
+        writeln!(out, "  Error: {error}")?;
+    }
+
+    let mut units: Vec<&TimingUnit> = data.units.iter().collect();
+    units.sort_by(|a, b| b.duration.total_cmp(&a.duration));
+    writeln!(out)?;
+    writeln!(out, "Slowest units:")?;
+    for (i, unit) in units.iter().take(top).enumerate() {
+        let codegen = match codegen_time(unit) {
+            Some(ctime) if unit.duration > 0.0 => format!(
+                " (codegen {:.1}s, {:.0}%)",
+                ctime,
+                ctime / unit.duration * 100.0
+            ),
+            _ => String::new(),
+        };
+        writeln!(
+            out,
+            "{:>4}. {:>7.1}s  {}{}",
+            i + 1,
+            unit.duration,
+            unit_name(unit),
+            codegen
+        )?;
+    }
+
+    let critical: Vec<&TimingUnit> = data
+        .critical_path
+        .iter()
+        .filter_map(|&i| data.units.get(i))
+        .collect();
+    if !critical.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "Critical path:")?;
+        for unit in critical {
+            let speedup = match unit.max_speedup {
+                Some(speedup) => format!(" (build up to {:.1}s faster)", speedup),
+                None => String::new(),
+            };
+            writeln!(
+                out,
+                "  {:>7.1}s  {}{}",
+                unit.duration,
+                unit_name(unit),
+                speedup
+            )?;
+        }
+    }
+    Ok(out)
+}
+
+fn render_comparison(base: &TimingRun, new: &TimingRun, top: usize) -> CargoResult<String> {
+    let mut out = String::new();
+    writeln!(out, "Comparing timing report {} with {}", base.id, new.id)?;
+    let delta = new.data.duration - base.data.duration;
+    writeln!(
+        out,
+        "  Total time: {:.1}s -> {:.1}s ({:+.1}s{})",
+        base.data.duration,
+        new.data.duration,
+        delta,
+        percent_change(base.data.duration, new.data.duration)
+    )?;
+    writeln!(
+        out,
+        "  Average parallelism: {:.1} -> {:.1}",
+        average_parallelism(&base.data),
+        average_parallelism(&new.data)
+    )?;
+    writeln!(
+        out,
+        "  Units: {} -> {}",
+        base.data.units.len(),
+        new.data.units.len()
+    )?;
+
+    let base_units: HashMap<_, &TimingUnit> =
+        base.data.units.iter().map(|u| (u.key(), u)).collect();
+    let new_keys: std::collections::HashSet<_> =
+        new.data.units.iter().map(TimingUnit::key).collect();
+
+    let mut changed: Vec<(&TimingUnit, &TimingUnit)> = new
+        .data
+        .units
+        .iter()
+        .filter_map(|u| base_units.get(&u.key()).map(|b| (*b, u)))
+        // Skip units whose change rounds to zero in the report.
+        .filter(|(b, n)| (n.duration - b.duration).abs() >= 0.05)
+        .collect();
+    changed.sort_by(|(a_base, a_new), (b_base, b_new)| {
+        let a = (a_new.duration - a_base.duration).abs();
+        let b = (b_new.duration - b_base.duration).abs();
+        b.total_cmp(&a)
+    });
+    if !changed.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "Largest changes:")?;
+        for (b, n) in changed.iter().take(top) {
+            let codegen = match (codegen_time(b), codegen_time(n)) {
+                (Some(b), Some(n)) => format!(", codegen {:.1}s -> {:.1}s ({:+.1}s)", b, n, n - b),
+                _ => String::new(),
+            };
+            writeln!(
+                out,
+                "  {:>+7.1}s  {}: {:.1}s -> {:.1}s{}",
+                n.duration - b.duration,
+                unit_name(n),
+                b.duration,
+                n.duration,
+                codegen
+            )?;
+        }
+    }
+
+    let mut added: Vec<&TimingUnit> = new
+        .data
+        .units
+        .iter()
+        .filter(|u| !base_units.contains_key(&u.key()))
+        .collect();
+    added.sort_by(|a, b| b.duration.total_cmp(&a.duration));
+    if !added.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "Only in {}:", new.id)?;
+        for unit in added.iter().take(top) {
+            writeln!(out, "  {:>7.1}s  {}", unit.duration, unit_name(unit))?;
+        }
+    }
+
+    let mut removed: Vec<&TimingUnit> = base
+        .data
+        .units
+        .iter()
+        .filter(|u| !new_keys.contains(&u.key()))
+        .collect();
+    removed.sort_by(|a, b| b.duration.total_cmp(&a.duration));
+    if !removed.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "Only in {}:", base.id)?;
+        for unit in removed.iter().take(top) {
+            writeln!(out, "  {:>7.1}s  {}", unit.duration, unit_name(unit))?;
+        }
+    }
+    Ok(out)
+}
+
+fn percent_change(base: f64, new: f64) -> String {
+    if base > 0.0 {
+        format!(", {:+.1}%", (new - base) / base * 100.0)
+    } else {
+        String::new()
+    }
+}
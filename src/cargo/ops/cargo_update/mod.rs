@@ -5,11 +5,13 @@ use crate::core::shell::Verbosity;
 use crate::core::Registry as _;
 use crate::core::{PackageId, PackageIdSpec, PackageIdSpecQuery};
 use crate::core::{Resolve, SourceId, Workspace};
+use crate::drop_println;
 use crate::ops;
 use crate::sources::source::QueryKind;
 use crate::sources::IndexSummary;
 use crate::util::cache_lock::CacheLockMode;
 use crate::util::context::GlobalContext;
+use crate::util::machine_message::{self, Message as _};
 use crate::util::toml_mut::dependency::{MaybeWorkspace, Source};
 use crate::util::toml_mut::manifest::LocalManifest;
 use crate::util::toml_mut::upgrade::upgrade_requirement;
@@ -24,6 +26,10 @@ use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use tracing::{debug, trace};
 
+use self::policy::{UpdatePolicy, VersionFilter};
+
+mod policy;
+
 pub type UpgradeMap = HashMap<(String, SourceId), Version>;
 
 pub struct UpdateOptions<'a> {
@@ -33,9 +39,25 @@ pub struct UpdateOptions<'a> {
     pub recursive: bool,
     pub dry_run: bool,
     pub workspace: bool,
+    pub message_format: LockfileMessageFormat,
+}
+
+/// How `cargo update` and `cargo generate-lockfile` report the changes to the
+/// lockfile.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LockfileMessageFormat {
+    /// Status messages on stderr.
+    #[default]
+    Human,
+    /// Status messages on stderr, and a [`machine_message::LockfileChange`]
+    /// for each package on stdout.
+    Json,
 }
 
-pub fn generate_lockfile(ws: &Workspace<'_>) -> CargoResult<()> {
+pub fn generate_lockfile(
+    ws: &Workspace<'_>,
+    message_format: LockfileMessageFormat,
+) -> CargoResult<()> {
     let mut registry = ws.package_registry()?;
     let previous_resolve = None;
     let mut resolve = ops::resolve_with_previous(
@@ -50,6 +72,9 @@ pub fn generate_lockfile(ws: &Workspace<'_>) -> CargoResult<()> {
     )?;
     ops::write_pkg_lockfile(ws, &mut resolve)?;
     print_lockfile_changes(ws, previous_resolve, &resolve, &mut registry)?;
+    if message_format == LockfileMessageFormat::Json {
+        emit_lockfile_changes(ws, previous_resolve, &resolve, None, &mut registry)?;
+    }
     Ok(())
 }
 
@@ -72,7 +97,7 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
         Some(resolve) => resolve,
         None => {
             match opts.precise {
-                None => return generate_lockfile(ws),
+                None => return generate_lockfile(ws, opts.message_format),
 
                 // Precise option specified, so calculate a previous_resolve required
                 // by precise package update later.
@@ -95,14 +120,20 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
     let mut registry = ws.package_registry()?;
     let mut to_avoid = HashSet::new();
 
-    if opts.to_update.is_empty() {
+    let policy = UpdatePolicy::load(opts.gctx)?;
+    let to_update = match &policy {
+        Some(policy) => policy.expand_groups(&previous_resolve, &opts.to_update)?,
+        None => opts.to_update.clone(),
+    };
+
+    if to_update.is_empty() {
         if !opts.workspace {
             to_avoid.extend(previous_resolve.iter());
             to_avoid.extend(previous_resolve.unused_patches());
         }
     } else {
         let mut sources = Vec::new();
-        for name in opts.to_update.iter() {
+        for name in to_update.iter() {
             let pid = previous_resolve.query(name)?;
             if opts.recursive {
                 fill_with_deps(&previous_resolve, pid, &mut to_avoid, &mut HashSet::new());
@@ -171,6 +202,14 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
 
     let keep = |p: &PackageId| !to_avoid_sources.contains(&p.source_id()) && !to_avoid.contains(p);
 
+    let version_filter = policy
+        .as_ref()
+        .map(|policy| policy.version_filter(&previous_resolve));
+    if let Some(version_filter) = version_filter.clone() {
+        registry.set_version_filter(Some(Box::new(move |summary| {
+            version_filter.allows(summary)
+        })));
+    }
     let mut resolve = ops::resolve_with_previous(
         &mut registry,
         ws,
@@ -181,6 +220,8 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
         &[],
         true,
     )?;
+    // Report the versions which are available regardless of the policy.
+    registry.set_version_filter(None);
 
     print_lockfile_updates(
         ws,
@@ -189,6 +230,28 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
         opts.precise.is_some(),
         &mut registry,
     )?;
+    if opts.message_format == LockfileMessageFormat::Json {
+        emit_lockfile_changes(
+            ws,
+            Some(&previous_resolve),
+            &resolve,
+            policy.as_ref().zip(version_filter.as_ref()),
+            &mut registry,
+        )?;
+    }
+    // The JSON messages already carry the report.
+    if let (Some(policy), Some(version_filter), true, LockfileMessageFormat::Human) =
+        (&policy, &version_filter, opts.dry_run, opts.message_format)
+    {
+        print_update_report(
+            ws,
+            policy,
+            version_filter,
+            &previous_resolve,
+            &resolve,
+            &mut registry,
+        )?;
+    }
     if opts.dry_run {
         opts.gctx
             .shell()
@@ -538,9 +601,10 @@ fn print_lockfile_generation(
                     vec![]
                 };
 
+                let advisories = report_advisories(ws, &possibilities, change);
                 let required_rust_version = report_required_rust_version(resolve, change);
                 let latest = report_latest(&possibilities, change);
-                let note = required_rust_version.or(latest);
+                let note = advisories.or(required_rust_version).or(latest);
 
                 if let Some(note) = note {
                     ws.gctx().shell().status_with_color(
@@ -654,9 +718,13 @@ fn print_lockfile_updates(
             PackageChangeKind::Added
             | PackageChangeKind::Upgraded
             | PackageChangeKind::Downgraded => {
+                let advisories = report_advisories(ws, &possibilities, change);
                 let required_rust_version = report_required_rust_version(resolve, change);
                 let latest = report_latest(&possibilities, change);
-                let note = required_rust_version.or(latest).unwrap_or_default();
+                let note = advisories
+                    .or(required_rust_version)
+                    .or(latest)
+                    .unwrap_or_default();
 
                 ws.gctx().shell().status_with_color(
                     change.kind.status(),
@@ -672,15 +740,20 @@ fn print_lockfile_updates(
                 )?;
             }
             PackageChangeKind::Unchanged => {
+                let advisories = report_advisories(ws, &possibilities, change);
                 let required_rust_version = report_required_rust_version(resolve, change);
                 let latest = report_latest(&possibilities, change);
-                let note = required_rust_version.as_deref().or(latest.as_deref());
+                let note = advisories
+                    .as_deref()
+                    .or(required_rust_version.as_deref())
+                    .or(latest.as_deref());
 
                 if let Some(note) = note {
-                    if latest.is_some() {
+                    if advisories.is_none() && latest.is_some() {
                         unchanged_behind += 1;
                     }
-                    if ws.gctx().shell().verbosity() == Verbosity::Verbose {
+                    // Upgrades blocked by advisories are always reported.
+                    if advisories.is_some() || ws.gctx().shell().verbosity() == Verbosity::Verbose {
                         ws.gctx().shell().status_with_color(
                             change.kind.status(),
                             format!("{change}{note}"),
@@ -707,6 +780,174 @@ fn print_lockfile_updates(
     Ok(())
 }
 
+/// Emits a [`machine_message::LockfileChange`] on stdout for each package
+/// that isn't a workspace member, with the groups and held back versions of
+/// the update `policy`, if any.
+fn emit_lockfile_changes(
+    ws: &Workspace<'_>,
+    previous_resolve: Option<&Resolve>,
+    resolve: &Resolve,
+    policy: Option<(&UpdatePolicy, &VersionFilter)>,
+    registry: &mut PackageRegistry<'_>,
+) -> CargoResult<()> {
+    let mut changes = match previous_resolve {
+        Some(previous_resolve) => PackageChange::diff(ws, previous_resolve, resolve),
+        None => PackageChange::new(ws, resolve),
+    };
+    annotate_required_rust_version(ws, resolve, &mut changes);
+
+    for change in changes.values() {
+        if change.is_member.unwrap_or(false) {
+            continue;
+        }
+        let package_id = change.package_id;
+        let possibilities = if let Some(query) = change.alternatives_query() {
+            loop {
+                match registry.query_vec(&query, QueryKind::Exact) {
+                    std::task::Poll::Ready(res) => {
+                        break res?;
+                    }
+                    std::task::Poll::Pending => registry.block_until_ready()?,
+                }
+            }
+        } else {
+            vec![]
+        };
+        let version_req = package_id.version().to_caret_req();
+        let latest_compatible = possibilities
+            .iter()
+            .map(|s| s.as_summary().version())
+            .filter(|v| version_req.matches(v))
+            .max();
+        let latest = possibilities
+            .iter()
+            .map(|s| s.as_summary().version())
+            .filter(|v| !v.is_prerelease() || *v == package_id.version())
+            .max();
+        // Removed packages aren't in `resolve`.
+        let rust_version = (change.kind != PackageChangeKind::Removed)
+            .then(|| resolve.summary(package_id).rust_version().cloned())
+            .flatten();
+        let rust_version_compatible = rust_version
+            .as_ref()
+            .zip(change.required_rust_version.as_ref())
+            .map(|(rust_version, required)| rust_version.is_compatible_with(required));
+        let group = policy.and_then(|(policy, _)| policy.group_of(&package_id.name()));
+        let held_back = policy.and_then(|(_, version_filter)| {
+            held_back_version(change, &possibilities, version_filter)
+        });
+        let msg = machine_message::LockfileChange {
+            kind: change.kind.name(),
+            package_id: package_id.to_spec(),
+            name: package_id.name().to_string(),
+            version: package_id.version().to_string(),
+            source: package_id.source_id().as_encoded_url().to_string(),
+            previous_package_id: change.previous_id.map(|id| id.to_spec()),
+            previous_version: change.previous_id.map(|id| id.version().to_string()),
+            direct: change.is_transitive.map(|transitive| !transitive),
+            rust_version: rust_version.map(|rv| rv.to_string()),
+            rust_version_compatible,
+            latest_compatible: latest_compatible.map(|v| v.to_string()),
+            latest: latest.map(|v| v.to_string()),
+            group: group.map(|g| g.to_string()),
+            held_back: held_back.map(|v| v.to_string()),
+        }
+        .to_json_string();
+        drop_println!(ws.gctx(), "{}", msg);
+    }
+    Ok(())
+}
+
+/// Prints the changes of `cargo update --dry-run` for each group of the
+/// update `policy`, noting versions held back by the policy.
+fn print_update_report(
+    ws: &Workspace<'_>,
+    policy: &UpdatePolicy,
+    version_filter: &VersionFilter,
+    previous_resolve: &Resolve,
+    resolve: &Resolve,
+    registry: &mut PackageRegistry<'_>,
+) -> CargoResult<()> {
+    let changes = PackageChange::diff(ws, previous_resolve, resolve);
+    let mut groups: IndexMap<Option<&str>, Vec<String>> = policy
+        .group_names()
+        .map(|name| (Some(name), Vec::new()))
+        .collect();
+    groups.insert(None, Vec::new());
+    for change in changes.values() {
+        if change.is_member.unwrap_or(false) {
+            continue;
+        }
+        let possibilities = if let Some(query) = change.alternatives_query() {
+            loop {
+                match registry.query_vec(&query, QueryKind::Exact) {
+                    std::task::Poll::Ready(res) => {
+                        break res?;
+                    }
+                    std::task::Poll::Pending => registry.block_until_ready()?,
+                }
+            }
+        } else {
+            vec![]
+        };
+        let held_back = held_back_version(change, &possibilities, version_filter);
+        if change.kind == PackageChangeKind::Unchanged && held_back.is_none() {
+            continue;
+        }
+        let mut line = format!("{:>11} {change}", change.kind.status());
+        if let Some(held_back) = held_back {
+            line.push_str(&format!(" (v{held_back} held back by update policy)"));
+        }
+        groups
+            .entry(policy.group_of(&change.package_id.name()))
+            .or_default()
+            .push(line);
+    }
+
+    let gctx = ws.gctx();
+    for (group, lines) in groups {
+        if lines.is_empty() {
+            continue;
+        }
+        match group {
+            Some(group) => drop_println!(gctx, "group `{group}`:"),
+            None => drop_println!(gctx, "ungrouped:"),
+        }
+        for line in lines {
+            drop_println!(gctx, "{line}");
+        }
+    }
+    Ok(())
+}
+
+/// The newest SemVer-compatible version of `possibilities` newer than the
+/// version of `change` which the update policy doesn't allow.
+fn held_back_version(
+    change: &PackageChange,
+    possibilities: &[IndexSummary],
+    version_filter: &VersionFilter,
+) -> Option<semver::Version> {
+    match change.kind {
+        PackageChangeKind::Upgraded
+        | PackageChangeKind::Downgraded
+        | PackageChangeKind::Unchanged => {
+            let version = change.package_id.version();
+            let version_req = version.to_caret_req();
+            possibilities
+                .iter()
+                .map(|s| s.as_summary())
+                .filter(|s| {
+                    s.version() > version
+                        && version_req.matches(s.version())
+                        && !version_filter.allows(s)
+                })
+                .map(|s| s.version().clone())
+                .max()
+        }
+        PackageChangeKind::Added | PackageChangeKind::Removed => None,
+    }
+}
+
 fn status_locking(ws: &Workspace<'_>, num_pkgs: usize) -> CargoResult<()> {
     use std::fmt::Write as _;
 
@@ -765,6 +1006,36 @@ fn report_required_rust_version(resolve: &Resolve, change: &PackageChange) -> Op
     ))
 }
 
+/// Describes the advisories which moved `change` away from its previous
+/// version, or which hold it back from the newest compatible version.
+fn report_advisories(
+    ws: &Workspace<'_>,
+    possibilities: &[IndexSummary],
+    change: &PackageChange,
+) -> Option<String> {
+    let advisories = ws.resolve_advisories()?;
+    let report = |id: PackageId| {
+        let warn = style::WARN;
+        let ids = advisories.affecting(id).map(|a| a.id()).join(", ");
+        format!(" {warn}(v{} affected by {ids}){warn:#}", id.version())
+    };
+
+    let package_id = change.package_id;
+    if let Some(previous_id) = change.previous_id {
+        if previous_id != package_id && advisories.is_affected(previous_id) {
+            return Some(report(previous_id));
+        }
+    }
+
+    let version_req = package_id.version().to_caret_req();
+    let newest = possibilities
+        .iter()
+        .map(|s| s.package_id())
+        .filter(|id| id.version() > package_id.version() && version_req.matches(id.version()))
+        .max_by(|a, b| a.version().cmp(b.version()))?;
+    advisories.is_affected(newest).then(|| report(newest))
+}
+
 fn report_latest(possibilities: &[IndexSummary], change: &PackageChange) -> Option<String> {
     let package_id = change.package_id;
     if !package_id.source_id().is_registry() {
@@ -880,10 +1151,10 @@ fn fill_with_deps<'a>(
 }
 
 #[derive(Clone, Debug)]
-struct PackageChange {
-    package_id: PackageId,
+pub(crate) struct PackageChange {
+    pub(crate) package_id: PackageId,
     previous_id: Option<PackageId>,
-    kind: PackageChangeKind,
+    pub(crate) kind: PackageChangeKind,
     is_member: Option<bool>,
     is_transitive: Option<bool>,
     required_rust_version: Option<PartialVersion>,
@@ -1034,7 +1305,7 @@ impl std::fmt::Display for PackageChange {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum PackageChangeKind {
+pub(crate) enum PackageChangeKind {
     Added,
     Removed,
     Upgraded,
@@ -1050,6 +1321,17 @@ impl PackageChangeKind {
         }
     }
 
+    /// The name of the kind in machine-readable messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Upgraded => "upgraded",
+            Self::Downgraded => "downgraded",
+            Self::Unchanged => "unchanged",
+        }
+    }
+
     pub fn status(&self) -> &'static str {
         match self {
             Self::Added => "Adding",
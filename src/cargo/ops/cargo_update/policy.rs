@@ -0,0 +1,240 @@
+//! Update policies from the `[update-policy]` config table, see
+//! `-Zupdate-policy`.
+//!
+//! Rules in `update-policy.packages` limit how far `cargo update` may move
+//! the packages matching a name pattern, and `update-policy.groups` names sets
+//! of packages which are always updated together and reported together.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Context as _;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+
+use crate::core::{Resolve, Summary};
+use crate::util::interning::InternedString;
+use crate::util::{CargoResult, GlobalContext};
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct UpdatePolicyConfig {
+    #[serde(default)]
+    packages: BTreeMap<String, PackageRuleConfig>,
+    #[serde(default)]
+    groups: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PackageRuleConfig {
+    allow: Option<AllowedUpdates>,
+    max_version: Option<String>,
+}
+
+/// How far a rule lets a package move from its locked version.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AllowedUpdates {
+    /// The package stays at its locked versions.
+    None,
+    /// The package may move to versions with the same major and minor
+    /// version.
+    Patch,
+    /// The package may move to any SemVer-compatible version.
+    #[default]
+    Compatible,
+}
+
+#[derive(Clone, Debug)]
+struct Rule {
+    pattern: glob::Pattern,
+    allow: AllowedUpdates,
+    max_version: Option<VersionReq>,
+}
+
+#[derive(Debug)]
+struct Group {
+    name: String,
+    patterns: Vec<glob::Pattern>,
+}
+
+/// The update policy of the `[update-policy]` config table.
+#[derive(Debug)]
+pub struct UpdatePolicy {
+    rules: Vec<Rule>,
+    groups: Vec<Group>,
+}
+
+impl UpdatePolicy {
+    /// Loads the update policy, if `-Zupdate-policy` is enabled.
+    pub fn load(gctx: &GlobalContext) -> CargoResult<Option<UpdatePolicy>> {
+        if !gctx.cli_unstable().update_policy {
+            // Invalid policies are ignored too, so only check for the table.
+            if !matches!(
+                gctx.get::<Option<UpdatePolicyConfig>>("update-policy"),
+                Ok(None)
+            ) {
+                gctx.shell()
+                    .warn("ignoring `update-policy` config without `-Zupdate-policy`")?;
+            }
+            return Ok(None);
+        }
+        let config = gctx
+            .get::<Option<UpdatePolicyConfig>>("update-policy")?
+            .unwrap_or_default();
+        let mut rules = Vec::new();
+        for (pattern, rule) in config.packages {
+            let max_version = rule
+                .max_version
+                .map(|max| {
+                    VersionReq::parse(&format!("<={max}")).with_context(|| {
+                        format!("failed to parse `update-policy.packages.{pattern}.max-version`")
+                    })
+                })
+                .transpose()?;
+            rules.push(Rule {
+                pattern: parse_pattern(&pattern)?,
+                allow: rule.allow.unwrap_or_default(),
+                max_version,
+            });
+        }
+        let mut groups = Vec::new();
+        for (name, patterns) in config.groups {
+            groups.push(Group {
+                patterns: patterns
+                    .iter()
+                    .map(|p| parse_pattern(p))
+                    .collect::<CargoResult<_>>()?,
+                name,
+            });
+        }
+        Ok(Some(UpdatePolicy { rules, groups }))
+    }
+
+    /// The name of the first group containing the package `name`.
+    pub fn group_of(&self, name: &str) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|group| group.patterns.iter().any(|p| p.matches(name)))
+            .map(|group| group.name.as_str())
+    }
+
+    /// The names of all groups, in the order they are reported.
+    pub fn group_names(&self) -> impl Iterator<Item = &str> {
+        self.groups.iter().map(|group| group.name.as_str())
+    }
+
+    /// Adds the packages of `previous` sharing a group with a package of
+    /// `to_update` to it, so that groups are always updated together.
+    pub fn expand_groups(
+        &self,
+        previous: &Resolve,
+        to_update: &[String],
+    ) -> CargoResult<Vec<String>> {
+        let mut expanded = to_update.to_vec();
+        let mut groups = HashSet::new();
+        for spec in to_update {
+            let pkg_id = previous.query(spec)?;
+            if let Some(group) = self.group_of(&pkg_id.name()) {
+                groups.insert(group);
+            }
+        }
+        for pkg_id in previous.iter() {
+            let Some(group) = self.group_of(&pkg_id.name()) else {
+                continue;
+            };
+            if groups.contains(group) {
+                expanded.push(pkg_id.to_spec().to_string());
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Creates the filter of versions allowed by the rules, relative to the
+    /// versions locked in `previous`.
+    pub fn version_filter(&self, previous: &Resolve) -> VersionFilter {
+        let mut locked: HashMap<InternedString, Vec<Version>> = HashMap::new();
+        for pkg_id in previous.iter() {
+            locked
+                .entry(pkg_id.name())
+                .or_default()
+                .push(pkg_id.version().clone());
+        }
+        VersionFilter {
+            rules: self.rules.clone(),
+            locked,
+        }
+    }
+}
+
+/// Decides whether the rules of an [`UpdatePolicy`] allow a version.
+#[derive(Clone)]
+pub struct VersionFilter {
+    rules: Vec<Rule>,
+    /// The previously locked versions of each package name.
+    locked: HashMap<InternedString, Vec<Version>>,
+}
+
+impl VersionFilter {
+    /// Whether `summary` may be selected.
+    ///
+    /// Previously locked versions are always allowed, even if a rule was
+    /// added since they were locked.
+    pub fn allows(&self, summary: &Summary) -> bool {
+        let version = summary.version();
+        let locked = self
+            .locked
+            .get(&summary.name())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if locked.contains(version) {
+            return true;
+        }
+        // Only versions compatible with a locked version are updates of it,
+        // others are new dependencies, which rules don't apply to.
+        let updated: Vec<_> = locked
+            .iter()
+            .filter(|l| is_compatible(l, version))
+            .collect();
+        self.rules
+            .iter()
+            .filter(|rule| rule.pattern.matches(&summary.name()))
+            .all(|rule| {
+                if let Some(max_version) = &rule.max_version {
+                    if !max_version.matches(version) {
+                        return false;
+                    }
+                }
+                if updated.is_empty() {
+                    return true;
+                }
+                match rule.allow {
+                    AllowedUpdates::None => false,
+                    AllowedUpdates::Patch => updated
+                        .iter()
+                        .any(|l| l.major == version.major && l.minor == version.minor),
+                    AllowedUpdates::Compatible => true,
+                }
+            })
+    }
+}
+
+fn parse_pattern(pattern: &str) -> CargoResult<glob::Pattern> {
+    glob::Pattern::new(pattern)
+        .with_context(|| format!("invalid package name pattern `{pattern}` in `update-policy`"))
+}
+
+/// Whether `a` and `b` are SemVer-compatible.
+fn is_compatible(a: &Version, b: &Version) -> bool {
+    if a.major != b.major {
+        false
+    } else if a.major != 0 {
+        true
+    } else if a.minor != b.minor {
+        false
+    } else if a.minor != 0 {
+        true
+    } else {
+        a.patch == b.patch
+    }
+}
@@ -8,6 +8,7 @@ pub use self::cargo_compile::{CompileFilter, FilterRule, LibRule, Packages};
 pub use self::cargo_doc::{doc, DocOptions, OutputFormat};
 pub use self::cargo_fetch::{fetch, FetchOptions};
 pub use self::cargo_install::{install, install_list};
+pub use self::cargo_mirror::{mirror, MirrorOptions};
 pub use self::cargo_new::{init, new, NewOptions, NewProjectKind, VersionControl};
 pub use self::cargo_output_metadata::{output_metadata, ExportInfo, OutputMetadataOptions};
 pub use self::cargo_package::{check_yanked, package, PackageOpts};
@@ -21,6 +22,7 @@ pub use self::cargo_update::print_lockfile_changes;
 pub use self::cargo_update::update_lockfile;
 pub use self::cargo_update::upgrade_manifests;
 pub use self::cargo_update::write_manifest_upgrades;
+pub use self::cargo_update::LockfileMessageFormat;
 pub use self::cargo_update::UpdateOptions;
 pub use self::common_for_install_and_uninstall::{resolve_root, InstallTracker};
 pub use self::fix::{fix, fix_exec_rustc, fix_get_proxy_lock_addr, FixOptions};
@@ -49,12 +51,15 @@ pub mod cargo_config;
 mod cargo_doc;
 mod cargo_fetch;
 mod cargo_install;
+pub mod cargo_lockfile;
+mod cargo_mirror;
 mod cargo_new;
 mod cargo_output_metadata;
 mod cargo_package;
 mod cargo_pkgid;
 mod cargo_read_manifest;
 pub mod cargo_remove;
+pub mod cargo_report;
 mod cargo_run;
 mod cargo_test;
 mod cargo_uninstall;
@@ -8,6 +8,7 @@ mod logout;
 mod owner;
 mod publish;
 mod search;
+mod semver_check;
 mod yank;
 
 use std::collections::HashSet;
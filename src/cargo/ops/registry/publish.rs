@@ -65,6 +65,7 @@ pub struct PublishOpts<'gctx> {
     pub targets: Vec<String>,
     pub dry_run: bool,
     pub cli_features: CliFeatures,
+    pub semver_check: bool,
 }
 
 pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
@@ -143,6 +144,7 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
         &PackageOpts {
             gctx: opts.gctx,
             verify: opts.verify,
+            verify_reproducible: false,
             list: false,
             check_metadata: true,
             allow_dirty: opts.allow_dirty,
@@ -158,6 +160,12 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
         pkgs,
     )?;
 
+    if opts.semver_check {
+        for (pkg, (cli_features, _)) in pkg_dep_graph.packages.values() {
+            super::semver_check::check(ws, pkg, cli_features, &opts.targets, source_ids.original)?;
+        }
+    }
+
     let mut plan = PublishPlan::new(&pkg_dep_graph.graph);
     // May contains packages from previous rounds as `wait_for_any_publish_confirmation` returns
     // after it confirms any packages, not all packages, requiring us to handle the rest in the next
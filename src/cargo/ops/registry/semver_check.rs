@@ -0,0 +1,553 @@
+//! Checks that a package about to be published doesn't break the public API
+//! of its previous version under a compatible version bump, see
+//! `cargo publish --semver-check`.
+//!
+//! The public API of both versions is read from their rustdoc JSON: the items
+//! reachable from the crate root through public modules and re-exports, along
+//! with the public fields, variants, inherent methods and trait items of the
+//! types and traits among them. The check is conservative: any change to the
+//! signature of a function is considered breaking, even if callers could be
+//! unaffected by it.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::task::Poll;
+
+use anyhow::Context as _;
+use cargo_util::paths;
+use serde_json::Value;
+
+use crate::core::compiler::{BuildConfig, CompileMode};
+use crate::core::registry::PackageRegistry;
+use crate::core::resolver::CliFeatures;
+use crate::core::summary::FeatureValue;
+use crate::core::Dependency;
+use crate::core::Package;
+use crate::core::Registry as _;
+use crate::core::SourceId;
+use crate::core::Workspace;
+use crate::ops;
+use crate::sources::source::QueryKind;
+use crate::sources::IndexSummary;
+use crate::sources::SourceConfigMap;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::interning::InternedString;
+use crate::CargoResult;
+use crate::GlobalContext;
+
+/// Checks the public API of `pkg` of `ws` against the one of the greatest
+/// version published before it on `registry_src`, if they are compatible
+/// versions.
+///
+/// Both APIs are documented with `cli_features`, for each of `targets`. The
+/// features the previous version doesn't have are left out for it.
+pub(super) fn check(
+    ws: &Workspace<'_>,
+    pkg: &Package,
+    cli_features: &CliFeatures,
+    targets: &[String],
+    registry_src: SourceId,
+) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    if !pkg.targets().iter().any(|t| t.is_lib()) {
+        return Ok(());
+    }
+    let Some(previous) = previous_version(gctx, pkg, registry_src)? else {
+        return Ok(());
+    };
+
+    gctx.shell().status(
+        "Checking",
+        format!(
+            "{} for semver compatibility with v{}",
+            pkg.package_id(),
+            previous.version()
+        ),
+    )?;
+    let new_docs = rustdoc_json(ws, pkg, cli_features, targets)?;
+    let target_dir = ws.target_dir().join("semver-check");
+    let mut previous_ws = Workspace::ephemeral(previous.clone(), gctx, Some(target_dir), false)?;
+    previous_ws.set_ignore_lock(gctx.lock_update_allowed());
+    let previous_features = previous_features(&previous, cli_features);
+    let previous_docs = rustdoc_json(&previous_ws, &previous, &previous_features, targets)?;
+
+    let mut breaking = Vec::new();
+    for (previous_doc, new_doc) in previous_docs.iter().zip(&new_docs) {
+        for change in breaking_changes(&public_api(previous_doc)?, &public_api(new_doc)?) {
+            if !breaking.contains(&change) {
+                breaking.push(change);
+            }
+        }
+    }
+    if !breaking.is_empty() {
+        anyhow::bail!(
+            "`{}` is not semver compatible with v{}:\n  {}\n\
+             bump the version to {} to publish these changes",
+            pkg.package_id(),
+            previous.version(),
+            breaking.join("\n  "),
+            next_breaking_version(pkg.version()),
+        );
+    }
+    Ok(())
+}
+
+/// Finds the greatest non-yanked version of `pkg` published before its
+/// current version, and downloads it if `pkg` is a compatible version of it.
+fn previous_version(
+    gctx: &GlobalContext,
+    pkg: &Package,
+    registry_src: SourceId,
+) -> CargoResult<Option<Package>> {
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+    let mut registry = PackageRegistry::new_with_source_config(gctx, SourceConfigMap::new(gctx)?)?;
+    registry.add_sources([registry_src])?;
+    registry.lock_patches();
+
+    let req = format!("<{}", pkg.version());
+    let dep = Dependency::parse(pkg.name(), Some(&req), registry_src)?;
+    let summaries = loop {
+        match registry.query_vec(&dep, QueryKind::Exact) {
+            Poll::Ready(res) => break res?,
+            Poll::Pending => registry.block_until_ready()?,
+        }
+    };
+    let Some(id) = summaries
+        .into_iter()
+        .map(|s| IndexSummary::into_summary(s).package_id())
+        .max_by(|a, b| a.version().cmp(b.version()))
+    else {
+        return Ok(None);
+    };
+    if !is_compatible(id.version(), pkg.version()) {
+        return Ok(None);
+    }
+    let set = registry.get(&[id])?;
+    Ok(Some(set.get_one(id)?.clone()))
+}
+
+/// Whether `new` is a compatible version of `old`, according to the rules of
+/// caret requirements.
+fn is_compatible(old: &semver::Version, new: &semver::Version) -> bool {
+    if old.major != new.major {
+        false
+    } else if old.major != 0 {
+        true
+    } else {
+        old.minor == new.minor && old.minor != 0
+    }
+}
+
+/// The smallest version after `version` allowed to break its API.
+fn next_breaking_version(version: &semver::Version) -> semver::Version {
+    if version.major != 0 {
+        semver::Version::new(version.major + 1, 0, 0)
+    } else if version.minor != 0 {
+        semver::Version::new(0, version.minor + 1, 0)
+    } else {
+        semver::Version::new(0, 0, version.patch + 1)
+    }
+}
+
+/// The features of `cli_features` which `previous` has.
+fn previous_features(previous: &Package, cli_features: &CliFeatures) -> CliFeatures {
+    let has_dep = |dep_name: &InternedString| {
+        previous
+            .dependencies()
+            .iter()
+            .any(|dep| dep.name_in_toml() == *dep_name)
+    };
+    let features = cli_features
+        .features
+        .iter()
+        .filter(|feature| match feature {
+            FeatureValue::Feature(name) => previous.summary().features().contains_key(name),
+            FeatureValue::Dep { dep_name } | FeatureValue::DepFeature { dep_name, .. } => {
+                has_dep(dep_name)
+            }
+        })
+        .cloned()
+        .collect();
+    CliFeatures {
+        features: Rc::new(features),
+        ..cli_features.clone()
+    }
+}
+
+/// Documents the library of `pkg` of `ws` as JSON, with `cli_features`, once
+/// for each of `targets`.
+fn rustdoc_json(
+    ws: &Workspace<'_>,
+    pkg: &Package,
+    cli_features: &CliFeatures,
+    targets: &[String],
+) -> CargoResult<Vec<Value>> {
+    let lib = pkg.targets().iter().find(|t| t.is_lib()).unwrap();
+    let mode = CompileMode::Doc {
+        deps: false,
+        json: true,
+    };
+    let mut compile_opts = ops::CompileOptions::new(ws.gctx(), mode)?;
+    compile_opts.build_config = BuildConfig::new(ws.gctx(), None, false, targets, mode)?;
+    compile_opts.cli_features = cli_features.clone();
+    compile_opts.spec = ops::Packages::Packages(vec![pkg.package_id().to_spec().to_string()]);
+    compile_opts.filter = ops::CompileFilter::lib_only();
+    let compilation = ops::compile(ws, &compile_opts).with_context(|| {
+        format!(
+            "failed to generate the rustdoc JSON of `{}`",
+            pkg.package_id()
+        )
+    })?;
+
+    let mut docs = Vec::new();
+    for kind in &compile_opts.build_config.requested_kinds {
+        let path = compilation.root_output[kind]
+            .with_file_name("doc")
+            .join(format!("{}.json", lib.crate_name()));
+        let json = paths::read(&path)?;
+        let doc = serde_json::from_str(&json)
+            .with_context(|| format!("failed to parse rustdoc JSON `{}`", path.display()))?;
+        docs.push(doc);
+    }
+    Ok(docs)
+}
+
+/// An item of a public API.
+#[derive(Debug, PartialEq)]
+struct ApiItem {
+    /// The kind of item, as named by rustdoc JSON (`function`, `struct`, ...).
+    kind: String,
+    /// The signature of a function, with the ids and parameter names removed.
+    signature: Option<Value>,
+    /// Whether this is a trait item without a default.
+    required: bool,
+    /// Whether this is marked `#[non_exhaustive]`.
+    non_exhaustive: bool,
+    /// Whether this is a struct with private fields.
+    private_fields: bool,
+}
+
+/// Collects the public API of the crate documented by `doc`, by path.
+fn public_api(doc: &Value) -> CargoResult<BTreeMap<String, ApiItem>> {
+    let root = id_key(&doc["root"])
+        .ok_or_else(|| anyhow::format_err!("rustdoc JSON is missing its root module"))?;
+    let crate_name = doc["index"][&root]["name"]
+        .as_str()
+        .ok_or_else(|| anyhow::format_err!("rustdoc JSON is missing its root module"))?;
+    let mut api = PublicApi {
+        doc,
+        items: BTreeMap::new(),
+        walking: HashSet::new(),
+    };
+    api.module(&root, crate_name);
+    Ok(api.items)
+}
+
+struct PublicApi<'a> {
+    doc: &'a Value,
+    items: BTreeMap<String, ApiItem>,
+    /// Modules and types being walked, which may be reachable from
+    /// themselves, like a module glob re-exporting its parent.
+    walking: HashSet<String>,
+}
+
+impl<'a> PublicApi<'a> {
+    fn item(&self, id: &str) -> Option<&'a Value> {
+        self.doc["index"].get(id)
+    }
+
+    fn module(&mut self, id: &str, path: &str) {
+        let Some(module) = self.item(id) else {
+            return;
+        };
+        if !self.walking.insert(id.to_owned()) {
+            return;
+        }
+        for child in array(&module["inner"]["module"]["items"]) {
+            let Some(child) = id_key(child) else {
+                continue;
+            };
+            let Some(item) = self.item(&child) else {
+                continue;
+            };
+            if item["visibility"] != "public" {
+                continue;
+            }
+            let inner = &item["inner"];
+            if let Some(import) = inner.get("use").or_else(|| inner.get("import")) {
+                self.import(import, path);
+            } else if let Some(name) = item["name"].as_str() {
+                self.add(&child, &format!("{path}::{name}"));
+            }
+        }
+        self.walking.remove(id);
+    }
+
+    /// Adds the target of a `pub use` in the module at `path`.
+    fn import(&mut self, import: &Value, path: &str) {
+        let target = id_key(&import["id"]);
+        let name = import["name"].as_str().unwrap_or_default();
+        if import["is_glob"] == true {
+            let Some(target) = target else {
+                return;
+            };
+            let Some(item) = self.item(&target) else {
+                return;
+            };
+            if item["inner"].get("module").is_some() {
+                self.module(&target, path);
+            } else if let Some(variants) = item["inner"]["enum"].get("variants") {
+                for variant in array(variants) {
+                    if let Some(variant) = id_key(variant) {
+                        let name = self.item(&variant).and_then(|v| v["name"].as_str());
+                        if let Some(name) = name {
+                            self.add(&variant, &format!("{path}::{name}"));
+                        }
+                    }
+                }
+            }
+            return;
+        }
+        let path = format!("{path}::{name}");
+        match target {
+            Some(target) if self.item(&target).is_some() => self.add(&target, &path),
+            // Re-exports of items of other crates are only known by their kind.
+            target => {
+                let kind = target
+                    .and_then(|target| self.doc["paths"][&target]["kind"].as_str())
+                    .unwrap_or("use");
+                self.items.insert(
+                    path,
+                    ApiItem {
+                        kind: kind.to_owned(),
+                        signature: None,
+                        required: false,
+                        non_exhaustive: false,
+                        private_fields: false,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Adds the item `id` at `path`, along with its own public items.
+    fn add(&mut self, id: &str, path: &str) {
+        let Some(item) = self.item(id) else {
+            return;
+        };
+        let Some((kind, inner)) = item["inner"].as_object().and_then(|o| o.iter().next()) else {
+            return;
+        };
+        let signature = (kind == "function").then(|| self.signature(inner));
+        let required = match kind.as_str() {
+            "function" => inner["has_body"] == false,
+            "assoc_const" => {
+                inner.get("value").or_else(|| inner.get("default")) == Some(&Value::Null)
+            }
+            "assoc_type" => {
+                inner.get("type").or_else(|| inner.get("default")) == Some(&Value::Null)
+            }
+            _ => false,
+        };
+        // Older rustdoc JSON formats have the source of each attribute,
+        // newer ones name the attributes they know about.
+        let non_exhaustive = array(&item["attrs"])
+            .iter()
+            .any(|attr| attr == "#[non_exhaustive]" || attr == "non_exhaustive");
+        // Older rustdoc JSON formats name the flag `fields_stripped`, and
+        // tuple structs list their private fields as `null`.
+        let struct_kind = &inner["kind"];
+        let private_fields = struct_kind["plain"]["has_stripped_fields"] == true
+            || struct_kind["plain"]["fields_stripped"] == true
+            || array(&struct_kind["tuple"]).iter().any(Value::is_null);
+        self.items.insert(
+            path.to_owned(),
+            ApiItem {
+                kind: kind.clone(),
+                signature,
+                required,
+                non_exhaustive,
+                private_fields,
+            },
+        );
+
+        match kind.as_str() {
+            "module" => self.module(id, path),
+            "struct" | "union" | "enum" | "trait" => {
+                if !self.walking.insert(id.to_owned()) {
+                    return;
+                }
+                let struct_kind = &inner["kind"];
+                let fields = [
+                    &inner["fields"],
+                    &struct_kind["plain"]["fields"],
+                    &struct_kind["tuple"],
+                    &inner["variants"],
+                    &inner["items"],
+                ];
+                for child in fields.into_iter().flat_map(array) {
+                    self.member(child, path, false);
+                }
+                for imp in array(&inner["impls"]) {
+                    let Some(imp) = id_key(imp).and_then(|imp| self.item(&imp)) else {
+                        continue;
+                    };
+                    let imp = &imp["inner"]["impl"];
+                    if imp["trait"].is_null() {
+                        for child in array(&imp["items"]) {
+                            self.member(child, path, true);
+                        }
+                    }
+                }
+                self.walking.remove(id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds a field, variant, trait item or, if `inherent`, public item of an
+    /// inherent impl of the type or trait at `path`.
+    fn member(&mut self, id: &Value, path: &str, inherent: bool) {
+        let Some(id) = id_key(id) else {
+            return;
+        };
+        let Some(item) = self.item(&id) else {
+            return;
+        };
+        let visibility = &item["visibility"];
+        // Variants and trait items have the visibility of their parent.
+        if visibility != "public" && (inherent || visibility != "default") {
+            return;
+        }
+        if let Some(name) = item["name"].as_str() {
+            let path = format!("{path}::{name}");
+            self.add(&id, &path);
+        }
+    }
+
+    /// The signature of a function, without anything specific to one build
+    /// of the docs, nor the names of the parameters.
+    fn signature(&self, function: &Value) -> Value {
+        let mut signature = function.clone();
+        if let Some(function) = signature.as_object_mut() {
+            function.remove("has_body");
+        }
+        if let Some(inputs) = signature["sig"]["inputs"].as_array_mut() {
+            for input in inputs {
+                if let Some(input) = input.as_array_mut() {
+                    input.remove(0);
+                }
+            }
+        }
+        self.normalize(&mut signature);
+        signature
+    }
+
+    /// Replaces the paths of references to items by their canonical path, and
+    /// removes their ids.
+    fn normalize(&self, value: &mut Value) {
+        match value {
+            Value::Object(object) => {
+                if let Some(id) = object.remove("id") {
+                    let canonical = id_key(&id).and_then(|id| self.doc["paths"][&id].get("path"));
+                    if let (Some(path), Some(canonical)) = (object.get_mut("path"), canonical) {
+                        *path = canonical.clone();
+                    }
+                }
+                object.values_mut().for_each(|v| self.normalize(v));
+            }
+            Value::Array(values) => values.iter_mut().for_each(|v| self.normalize(v)),
+            _ => {}
+        }
+    }
+}
+
+/// The changes from the API `old` to `new` breaking its users.
+fn breaking_changes(
+    old: &BTreeMap<String, ApiItem>,
+    new: &BTreeMap<String, ApiItem>,
+) -> Vec<String> {
+    let mut breaking = Vec::new();
+    for (path, old_item) in old {
+        let old_kind = describe(&old_item.kind);
+        match new.get(path) {
+            None => breaking.push(format!("removed {old_kind} `{path}`")),
+            Some(new_item) if new_item.kind != old_item.kind => breaking.push(format!(
+                "changed {old_kind} `{path}` to {} `{path}`",
+                describe(&new_item.kind)
+            )),
+            Some(new_item) => {
+                if new_item.signature != old_item.signature {
+                    breaking.push(format!("changed the signature of {old_kind} `{path}`"));
+                }
+                if new_item.required && !old_item.required {
+                    breaking.push(format!("removed the default of {old_kind} `{path}`"));
+                }
+                if new_item.non_exhaustive && !old_item.non_exhaustive {
+                    breaking.push(format!("made {old_kind} `{path}` non-exhaustive"));
+                }
+            }
+        }
+    }
+    for (path, new_item) in new {
+        if old.contains_key(path) {
+            continue;
+        }
+        let Some(parent) = path
+            .rsplit_once("::")
+            .and_then(|(parent, _)| old.get(parent))
+        else {
+            continue;
+        };
+        let parent_path = &path[..path.rfind("::").unwrap()];
+        let kind = describe(&new_item.kind);
+        if new_item.kind == "variant" && parent.kind == "enum" && !parent.non_exhaustive {
+            breaking.push(format!(
+                "added {kind} `{path}` to exhaustive enum `{parent_path}`"
+            ));
+        } else if new_item.kind == "struct_field"
+            && parent.kind == "struct"
+            && !parent.non_exhaustive
+            && !parent.private_fields
+        {
+            // Struct literals outside of the crate must list every field.
+            breaking.push(format!(
+                "added {kind} `{path}` to exhaustive struct `{parent_path}`"
+            ));
+        } else if new_item.required && parent.kind == "trait" {
+            breaking.push(format!(
+                "added required {kind} `{path}` to trait `{parent_path}`"
+            ));
+        }
+    }
+    breaking
+}
+
+/// The kind of an item, as written in messages.
+fn describe(kind: &str) -> &str {
+    match kind {
+        "struct_field" => "field",
+        "type_alias" | "typedef" => "type alias",
+        "assoc_const" => "associated constant",
+        "assoc_type" => "associated type",
+        "trait_alias" => "trait alias",
+        "proc_macro" => "procedural macro",
+        "use" => "re-export",
+        kind => kind,
+    }
+}
+
+/// The key in the index of an id, which older formats of rustdoc JSON write
+/// as strings, and newer ones as numbers.
+fn id_key(id: &Value) -> Option<String> {
+    match id {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+fn array(value: &Value) -> &[Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or_default()
+}
@@ -61,7 +61,8 @@ use crate::core::resolver::features::{
     CliFeatures, FeatureOpts, FeatureResolver, ForceAllTargets, RequestedFeatures, ResolvedFeatures,
 };
 use crate::core::resolver::{
-    self, HasDevUnits, Resolve, ResolveOpts, ResolveVersion, VersionOrdering, VersionPreferences,
+    self, HasDevUnits, Resolve, ResolveError, ResolveOpts, ResolveVersion, VersionOrdering,
+    VersionPreferences,
 };
 use crate::core::summary::Summary;
 use crate::core::Dependency;
@@ -76,11 +77,14 @@ use crate::ops;
 use crate::sources::RecursivePathSource;
 use crate::util::cache_lock::CacheLockMode;
 use crate::util::errors::CargoResult;
+use crate::util::interning::InternedString;
 use crate::util::CanonicalUrl;
 use anyhow::Context as _;
 use cargo_util::paths;
 use cargo_util_schemas::core::PartialVersion;
 use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use time::OffsetDateTime;
 use tracing::{debug, trace};
 
 /// Filter for keep using Package ID from previous lockfile.
@@ -369,6 +373,13 @@ pub fn resolve_with_previous<'gctx>(
         }
         version_prefs.rust_versions(rust_versions);
     }
+    if let Some((min_publish_age, deny)) = ws.resolve_min_publish_age() {
+        let cutoff = OffsetDateTime::now_utc() - min_publish_age;
+        version_prefs.publish_cutoff(cutoff, deny);
+    }
+    if let Some(advisories) = ws.resolve_advisories() {
+        version_prefs.exclude_advisories(Rc::clone(advisories));
+    }
 
     let avoid_patch_ids = if register_patches {
         register_patch_entries(registry, ws, previous, &mut version_prefs, keep_previous)?
@@ -381,13 +392,19 @@ pub fn resolve_with_previous<'gctx>(
 
     let dev_deps = ws.require_optional_deps() || has_dev_units == HasDevUnits::Yes;
 
+    // Patches are locked once below, so they can't be locked again if the
+    // incremental resolve fails.
+    let incremental = ws.gctx().cli_unstable().incremental_resolve
+        && previous.is_some()
+        && registry.patches().is_empty();
+
     if let Some(r) = previous {
         trace!("previous: {:?}", r);
 
         // In the case where a previous instance of resolve is available, we
         // want to lock as many packages as possible to the previous version
         // without disturbing the graph structure.
-        register_previous_locks(ws, registry, r, &keep, dev_deps);
+        register_previous_locks(ws, registry, r, &keep, dev_deps, incremental);
 
         // Prefer to use anything in the previous lock file, aka we want to have conservative updates.
         let _span = tracing::span!(tracing::Level::TRACE, "prefer_package_id").entered();
@@ -401,33 +418,84 @@ pub fn resolve_with_previous<'gctx>(
         registry.lock_patches();
     }
 
-    let summaries: Vec<(Summary, ResolveOpts)> = {
+    let members = ws.members_with_features(specs, cli_features)?;
+    let lock_members = |registry: &mut PackageRegistry<'gctx>| -> Vec<(Summary, ResolveOpts)> {
         let _span = tracing::span!(tracing::Level::TRACE, "registry.lock").entered();
-        ws.members_with_features(specs, cli_features)?
-            .into_iter()
+        members
+            .iter()
             .map(|(member, features)| {
                 let summary = registry.lock(member.summary().clone());
                 (
                     summary,
                     ResolveOpts {
                         dev_deps,
-                        features: RequestedFeatures::CliFeatures(features),
+                        features: RequestedFeatures::CliFeatures(features.clone()),
                     },
                 )
             })
             .collect()
     };
+    let mut summaries = lock_members(registry);
 
     let replace = lock_replacements(ws, previous, &keep);
 
-    let mut resolved = resolver::resolve(
+    let mut resolved = match resolver::resolve(
         &summaries,
         &replace,
         registry,
         &version_prefs,
         ResolveVersion::with_rust_version(ws.lowest_rust_version()),
         Some(ws.gctx()),
-    )?;
+    ) {
+        Ok(resolved) => resolved,
+        Err(e) if incremental && e.downcast_ref::<ResolveError>().is_some() => {
+            // The changed dependencies may need versions of packages which
+            // were kept locked, so verify by unlocking their whole sources,
+            // as without `-Zincremental-resolve`. Other errors, like failing
+            // to query a source, would only happen again.
+            debug!("incremental resolve failed, unlocking changed sources: {e:?}");
+            let previous = previous.unwrap();
+            register_previous_locks(ws, registry, previous, &keep, dev_deps, false);
+            summaries = lock_members(registry);
+            resolver::resolve(
+                &summaries,
+                &replace,
+                registry,
+                &version_prefs,
+                ResolveVersion::with_rust_version(ws.lowest_rust_version()),
+                Some(ws.gctx()),
+            )?
+        }
+        Err(e) => return Err(e),
+    };
+
+    if ws.gctx().cli_unstable().minimize_duplicates {
+        // Preferences only reorder candidates, so resolving again can't fail
+        // where the first resolve succeeded. Each pass only adds preferences,
+        // so this stops once no duplicate can be unified further.
+        let mut unified = HashSet::new();
+        loop {
+            let to_unify: Vec<_> = duplicates_to_unify(&resolved)
+                .into_iter()
+                .filter(|id| unified.insert(*id))
+                .collect();
+            if to_unify.is_empty() {
+                break;
+            }
+            for id in to_unify {
+                debug!("preferring {} to unify duplicates", id);
+                version_prefs.prefer_unified(id);
+            }
+            resolved = resolver::resolve(
+                &summaries,
+                &replace,
+                registry,
+                &version_prefs,
+                ResolveVersion::with_rust_version(ws.lowest_rust_version()),
+                Some(ws.gctx()),
+            )?;
+        }
+    }
 
     let patches = registry.patches().values().flat_map(|v| v.iter());
     resolved.register_used_patches(patches);
@@ -445,6 +513,54 @@ pub fn resolve_with_previous<'gctx>(
     Ok(resolved)
 }
 
+/// Finds the versions of packages with several versions in `resolve` that
+/// would let more of the requirements on the package use a single version.
+///
+/// For each such package, this is the version allowed by the most
+/// requirements, preferring newer versions, if it is allowed by more than the
+/// requirements already using it.
+fn duplicates_to_unify(resolve: &Resolve) -> Vec<PackageId> {
+    let mut versions: HashMap<(InternedString, SourceId), Vec<PackageId>> = HashMap::new();
+    for id in resolve.iter() {
+        versions
+            .entry((id.name(), id.source_id()))
+            .or_default()
+            .push(id);
+    }
+    let mut requirements: HashMap<PackageId, Vec<&Dependency>> = HashMap::new();
+    for parent in resolve.iter() {
+        for (id, deps) in resolve.deps(parent) {
+            requirements.entry(id).or_default().extend(deps);
+        }
+    }
+
+    let mut to_unify = Vec::new();
+    for ids in versions.values().filter(|ids| ids.len() > 1) {
+        let reqs: Vec<&Dependency> = ids
+            .iter()
+            .filter_map(|id| requirements.get(id))
+            .flatten()
+            .copied()
+            .collect();
+        let allowed_by = |id: &PackageId| {
+            reqs.iter()
+                .filter(|dep| dep.version_req().matches_unlocked(id.version()))
+                .count()
+        };
+        let Some(best) = ids
+            .iter()
+            .max_by(|a, b| (allowed_by(a), a.version()).cmp(&(allowed_by(b), b.version())))
+        else {
+            continue;
+        };
+        let using = requirements.get(best).map_or(0, Vec::len);
+        if allowed_by(best) > using {
+            to_unify.push(*best);
+        }
+    }
+    to_unify
+}
+
 /// Read the `paths` configuration variable to discover all path overrides that
 /// have been configured.
 #[tracing::instrument(skip_all)]
@@ -511,6 +627,7 @@ fn register_previous_locks(
     resolve: &Resolve,
     keep: Keep<'_>,
     dev_deps: bool,
+    incremental: bool,
 ) {
     let path_pkg = |id: SourceId| {
         if !id.is_path() {
@@ -585,8 +702,19 @@ fn register_previous_locks(
     // crates from crates.io* are not locked (aka added to `avoid_locking`).
     // For dependencies like `log` their previous version in the lock file will
     // come up first before newer version, if newer version are available.
+    //
+    // With `-Zincremental-resolve` only the packages with the name of the
+    // changed dependency, and their dependencies, are poisoned instead, so
+    // that large workspaces don't resolve all of crates.io again after
+    // editing a single dependency. If that isn't enough to resolve the
+    // changed dependency, `resolve_with_previous` falls back to poisoning
+    // the whole source.
     {
         let _span = tracing::span!(tracing::Level::TRACE, "poison").entered();
+        let mut previous_by_name: HashMap<InternedString, Vec<PackageId>> = HashMap::new();
+        for id in resolve.iter() {
+            previous_by_name.entry(id.name()).or_default().push(id);
+        }
         let mut path_deps = ws.members().cloned().collect::<Vec<_>>();
         let mut visited = HashSet::new();
         while let Some(member) = path_deps.pop() {
@@ -624,7 +752,11 @@ fn register_previous_locks(
 
                 // If we match *anything* in the dependency graph then we consider
                 // ourselves all ok, and assume that we'll resolve to that.
-                if resolve.iter().any(|id| dep.matches_ignoring_source(id)) {
+                let same_name = previous_by_name
+                    .get(&dep.package_name())
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                if same_name.iter().any(|id| dep.matches_ignoring_source(*id)) {
                     continue;
                 }
 
@@ -636,6 +768,15 @@ fn register_previous_locks(
                     member.package_id(),
                     dep.package_name()
                 );
+                if incremental {
+                    for id in same_name
+                        .iter()
+                        .filter(|id| id.source_id() == dep.source_id())
+                    {
+                        add_deps(resolve, *id, &mut avoid_locking);
+                    }
+                    continue;
+                }
                 for id in resolve
                     .iter()
                     .filter(|id| id.source_id() == dep.source_id())
@@ -15,6 +15,7 @@ use std::str::FromStr;
 
 mod format;
 mod graph;
+mod why_version;
 
 pub use {graph::EdgeKind, graph::Node};
 
@@ -47,6 +48,9 @@ pub struct TreeOptions {
     pub max_display_depth: u32,
     /// Excludes proc-macro dependencies.
     pub no_proc_macro: bool,
+    /// If set, explains why the given package was resolved to its versions
+    /// instead of displaying the tree.
+    pub why_version: Option<String>,
 }
 
 #[derive(PartialEq)]
@@ -109,6 +113,9 @@ static ASCII_SYMBOLS: Symbols = Symbols {
 
 /// Entry point for the `cargo tree` command.
 pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()> {
+    if let Some(spec) = &opts.why_version {
+        return why_version::print(ws, spec);
+    }
     let requested_targets = match &opts.target {
         Target::All | Target::Host => Vec::new(),
         Target::Specific(t) => t.clone(),
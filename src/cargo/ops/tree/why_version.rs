@@ -0,0 +1,348 @@
+//! Implementation of `cargo tree --why-version`.
+//!
+//! Explains the versions the lockfile selected for a package: the
+//! requirements on it from the resolve graph, why each newer candidate
+//! version from its source was rejected, and the single change to a
+//! requirement that would let it unify on one version, or move to a newer one.
+
+use std::collections::{BTreeSet, HashSet};
+use std::task::Poll;
+
+use cargo_util_schemas::core::PartialVersion;
+use itertools::Itertools;
+use semver::Version;
+use time::OffsetDateTime;
+
+use crate::core::dependency::DepKind;
+use crate::core::{
+    Dependency, PackageId, PackageIdSpec, PackageIdSpecQuery, Resolve, Summary, Workspace,
+};
+use crate::drop_println;
+use crate::ops;
+use crate::sources::source::QueryKind;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::CargoResult;
+
+/// A requirement of a package in the resolve graph on the explained package.
+struct Requirement<'a> {
+    parent: PackageId,
+    dep: &'a Dependency,
+    selected: PackageId,
+}
+
+/// Prints why the packages matching `spec` were resolved to their versions.
+pub fn print(ws: &Workspace<'_>, spec: &str) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let spec = PackageIdSpec::parse(spec)?;
+    let (pkg_set, resolve) = ops::resolve_ws(ws, false)?;
+
+    let Some(first) = resolve.iter().find(|id| spec.matches(*id)) else {
+        anyhow::bail!("package ID specification `{spec}` did not match any packages");
+    };
+    let name = first.name();
+    let source_id = first.source_id();
+    let selected: Vec<PackageId> = resolve
+        .iter()
+        .filter(|id| id.name() == name && id.source_id() == source_id)
+        .sorted()
+        .collect();
+
+    let mut requirements = Vec::new();
+    for parent in resolve.iter().sorted() {
+        for (dep_id, deps) in resolve.deps(parent) {
+            if !selected.contains(&dep_id) {
+                continue;
+            }
+            for dep in deps.iter().sorted_by_key(|dep| dep.kind()) {
+                requirements.push(Requirement {
+                    parent,
+                    dep,
+                    selected: dep_id,
+                });
+            }
+        }
+    }
+
+    // Yanked versions are hidden from queries, unless they are locked.
+    let (candidates, yanked) = {
+        let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+        let mut sources = pkg_set.sources_mut();
+        let source = sources
+            .get_mut(source_id)
+            .expect("source of a resolved package is loaded");
+        source.invalidate_cache();
+        source.add_to_yanked_whitelist(&selected);
+        let query = Dependency::parse(name, None, source_id)?;
+        let mut candidates = Vec::new();
+        let mut yanked = HashSet::new();
+        loop {
+            candidates.clear();
+            let poll = source.query(&query, QueryKind::Exact, &mut |s| {
+                if s.is_yanked() {
+                    yanked.insert(s.package_id());
+                }
+                candidates.push(s.into_summary());
+            });
+            match poll {
+                Poll::Ready(res) => break res?,
+                Poll::Pending => source.block_until_ready()?,
+            }
+        }
+        candidates.sort_by(|a, b| b.version().cmp(a.version()));
+        candidates.dedup_by_key(|s| s.package_id());
+        (candidates, yanked)
+    };
+
+    let required_rust_version = if ws.resolve_honors_rust_version() {
+        match ws.lowest_rust_version() {
+            Some(rust_version) => Some(rust_version.clone().into_partial()),
+            None => {
+                let rustc = gctx.load_global_rustc(Some(ws))?;
+                Some(rustc.version.clone().into())
+            }
+        }
+    } else {
+        None
+    };
+    let publish_cutoff = ws
+        .resolve_min_publish_age()
+        .map(|(age, _)| OffsetDateTime::now_utc() - age);
+
+    drop_println!(gctx, "selected:");
+    for id in &selected {
+        drop_println!(gctx, "  {id}");
+    }
+
+    drop_println!(gctx);
+    drop_println!(gctx, "requirements:");
+    for req in &requirements {
+        drop_println!(
+            gctx,
+            "  {} requires `{}`{}, selected v{}",
+            req.parent,
+            req.dep.version_req(),
+            describe_dep(req.dep),
+            req.selected.version(),
+        );
+    }
+
+    // Older versions than all selected ones are never interesting.
+    let oldest = selected[0].version();
+    drop_println!(gctx);
+    drop_println!(gctx, "candidates:");
+    for summary in &candidates {
+        let version = summary.version();
+        if version < oldest {
+            continue;
+        }
+        let mut reasons = Vec::new();
+        if yanked.contains(&summary.package_id()) {
+            reasons.push("yanked".to_string());
+        }
+        if selected.contains(&summary.package_id()) {
+            reasons.insert(0, "selected".to_string());
+        } else {
+            reasons.extend(rejections(
+                summary,
+                &requirements,
+                &resolve,
+                required_rust_version.as_ref(),
+                publish_cutoff,
+            ));
+            if reasons.is_empty() {
+                let newer = requirements
+                    .iter()
+                    .any(|req| req.selected.version() < version);
+                reasons.push(if newer {
+                    "matches all requirements, but the lockfile keeps the selected version"
+                        .to_string()
+                } else {
+                    "older than the selected version".to_string()
+                });
+            }
+        }
+        drop_println!(gctx, "  v{version}: {}", reasons.join("; "));
+    }
+
+    let suggestion = suggest(
+        &selected,
+        &candidates,
+        &yanked,
+        &requirements,
+        &resolve,
+        required_rust_version.as_ref(),
+        publish_cutoff,
+    );
+    if let Some(suggestion) = suggestion {
+        drop_println!(gctx);
+        drop_println!(gctx, "suggestion:");
+        drop_println!(gctx, "  {suggestion}");
+    }
+    Ok(())
+}
+
+/// Describes the kind and features of a requirement.
+fn describe_dep(dep: &Dependency) -> String {
+    let mut desc = String::new();
+    if !dep.features().is_empty() {
+        let features = dep.features().iter().map(|f| format!("`{f}`")).join(", ");
+        desc.push_str(&format!(" with features {features}"));
+    }
+    match dep.kind() {
+        DepKind::Normal => {}
+        DepKind::Build => desc.push_str(" (build)"),
+        DepKind::Development => desc.push_str(" (dev)"),
+    }
+    desc
+}
+
+/// Why the resolver can't select `candidate`, other than its version not
+/// matching the requirements.
+fn other_rejections(
+    candidate: &Summary,
+    requirements: &[Requirement<'_>],
+    resolve: &Resolve,
+    required_rust_version: Option<&PartialVersion>,
+    publish_cutoff: Option<OffsetDateTime>,
+) -> Vec<String> {
+    let mut reasons = Vec::new();
+    for req in requirements {
+        if !req.dep.version_req().matches_unlocked(candidate.version()) {
+            continue;
+        }
+        for feature in req.dep.features() {
+            if !candidate.features().contains_key(feature) {
+                reasons.push(format!(
+                    "missing feature `{feature}` required by {}",
+                    req.parent
+                ));
+            }
+        }
+    }
+    if let (Some(candidate_rust_version), Some(required)) =
+        (candidate.rust_version(), required_rust_version)
+    {
+        if !candidate_rust_version.is_compatible_with(required) {
+            reasons.push(format!(
+                "requires Rust {candidate_rust_version}, newer than Rust {required}"
+            ));
+        }
+    }
+    if let Some(links) = candidate.links() {
+        if let Some(other) = resolve
+            .iter()
+            .find(|id| id.name() != candidate.name() && resolve.summary(*id).links() == Some(links))
+        {
+            reasons.push(format!("`links = \"{links}\"` is already used by {other}"));
+        }
+    }
+    if let (Some(cutoff), Some(pubtime)) = (publish_cutoff, candidate.pubtime()) {
+        if pubtime > cutoff {
+            reasons.push("published less than `resolver.min-publish-age` ago".to_string());
+        }
+    }
+    reasons
+}
+
+/// Why the resolver can't select `candidate`.
+fn rejections(
+    candidate: &Summary,
+    requirements: &[Requirement<'_>],
+    resolve: &Resolve,
+    required_rust_version: Option<&PartialVersion>,
+    publish_cutoff: Option<OffsetDateTime>,
+) -> Vec<String> {
+    let mut reasons: Vec<_> = requirements
+        .iter()
+        .filter(|req| !req.dep.version_req().matches_unlocked(candidate.version()))
+        .map(|req| {
+            format!(
+                "does not match `{}` required by {}",
+                req.dep.version_req(),
+                req.parent
+            )
+        })
+        .unique()
+        .collect();
+    reasons.extend(other_rejections(
+        candidate,
+        requirements,
+        resolve,
+        required_rust_version,
+        publish_cutoff,
+    ));
+    reasons
+}
+
+/// Describes the smallest change that would select a single version, or a
+/// newer one if only one is selected: running `cargo update` if the
+/// requirements already allow a newer candidate, otherwise changing the
+/// requirements of a single package.
+fn suggest(
+    selected: &[PackageId],
+    candidates: &[Summary],
+    yanked: &HashSet<PackageId>,
+    requirements: &[Requirement<'_>],
+    resolve: &Resolve,
+    required_rust_version: Option<&PartialVersion>,
+    publish_cutoff: Option<OffsetDateTime>,
+) -> Option<String> {
+    let name = selected[0].name();
+    let newest_selected = selected.iter().map(|id| id.version()).max()?;
+    let unify = selected.len() > 1;
+    // The packages whose requirements block each viable candidate, newest
+    // candidates first.
+    let mut viable = Vec::new();
+    for summary in candidates {
+        let version: &Version = summary.version();
+        if !unify && version <= newest_selected {
+            break;
+        }
+        if yanked.contains(&summary.package_id())
+            || !other_rejections(
+                summary,
+                requirements,
+                resolve,
+                required_rust_version,
+                publish_cutoff,
+            )
+            .is_empty()
+        {
+            continue;
+        }
+        let blocking: BTreeSet<PackageId> = requirements
+            .iter()
+            .filter(|req| !req.dep.version_req().matches_unlocked(version))
+            .map(|req| req.parent)
+            .collect();
+        viable.push((version, blocking));
+    }
+    let outcome = |version: &Version| {
+        if unify {
+            format!("{name} to unify on v{version}")
+        } else {
+            format!("{name} v{version} to be selected")
+        }
+    };
+
+    if let Some((version, _)) = viable.iter().find(|(_, blocking)| blocking.is_empty()) {
+        return Some(format!(
+            "all requirements allow v{version}, `cargo update {name}` would allow {}",
+            outcome(version)
+        ));
+    }
+    if let Some((version, blocking)) = viable.iter().find(|(_, blocking)| blocking.len() == 1) {
+        let parent = blocking.first().unwrap();
+        let reqs = requirements
+            .iter()
+            .filter(|req| req.parent == *parent)
+            .map(|req| format!("`{}`", req.dep.version_req()))
+            .unique()
+            .join(", ");
+        return Some(format!(
+            "changing the requirement {reqs} of {parent} to allow v{version} would allow {}",
+            outcome(version)
+        ));
+    }
+    unify.then(|| format!("no change to the requirements of a single package would unify {name}"))
+}
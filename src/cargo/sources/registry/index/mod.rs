@@ -37,6 +37,8 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::str;
 use std::task::{ready, Poll};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 use tracing::{debug, info};
 
 mod cache;
@@ -233,6 +235,11 @@ pub struct IndexPackage<'a> {
     /// Added in 2023 (see <https://github.com/rust-lang/crates.io/pull/6267>),
     /// can be `None` if published before then or if not set in the manifest.
     pub rust_version: Option<RustVersion>,
+    /// When this version was published, in RFC 3339 format.
+    ///
+    /// Used by `resolver.min-publish-age`. Can be `None` if the registry
+    /// doesn't record it.
+    pub pubtime: Option<String>,
     /// The schema version for this entry.
     ///
     /// If this is None, it defaults to version `1`. Entries with unknown
@@ -322,6 +329,61 @@ impl<'gctx> RegistryIndex<'gctx> {
             .ok_or_else(|| internal(format!("no hash listed for {}", pkg)))?))
     }
 
+    /// Returns the line of the index file for a specified `PackageId`, as
+    /// published by the registry.
+    ///
+    /// Lines are only kept in memory for summaries loaded from the on-disk
+    /// cache, others are read again from the cache, or from the index file.
+    pub fn index_line(
+        &mut self,
+        pkg: PackageId,
+        load: &mut dyn RegistryData,
+    ) -> Poll<CargoResult<Option<String>>> {
+        let summaries = ready!(self.load_summaries(pkg.name(), load)?);
+        let line = match summaries.versions.get(pkg.version()) {
+            None => return Poll::Ready(Ok(None)),
+            Some(MaybeIndexSummary::Unparsed { start, end }) => {
+                Some(String::from_utf8_lossy(&summaries.raw_data[*start..*end]).into_owned())
+            }
+            Some(MaybeIndexSummary::Parsed(_)) => None,
+        };
+        if line.is_some() {
+            return Poll::Ready(Ok(line));
+        }
+
+        // The lines of index files loaded from the registry aren't kept, read
+        // them again from the cache written when loading them.
+        let name = pkg.name().to_lowercase();
+        if let Some(contents) = self.cache_manager.get(&name) {
+            if let Ok((cached, _)) = Summaries::parse_cache(contents) {
+                if let Some(MaybeIndexSummary::Unparsed { start, end }) =
+                    cached.versions.get(pkg.version())
+                {
+                    let line = String::from_utf8_lossy(&cached.raw_data[*start..*end]);
+                    return Poll::Ready(Ok(Some(line.into_owned())));
+                }
+            }
+        }
+
+        // Registries without an index version, like local registries, aren't
+        // cached, but can load their index files again.
+        let root = load.assert_index_locked(&self.path);
+        let relative = make_dep_path(&name, false);
+        let LoadResponse::Data { raw_data, .. } =
+            ready!(load.load(root, relative.as_ref(), None)?)
+        else {
+            return Poll::Ready(Ok(None));
+        };
+        let bindeps = self.gctx.cli_unstable().bindeps;
+        let line = split(&raw_data, b'\n').find(|line| {
+            IndexSummary::parse(line, self.source_id, bindeps)
+                .is_ok_and(|summary| summary.package_id().version() == pkg.version())
+        });
+        Poll::Ready(Ok(
+            line.map(|line| String::from_utf8_lossy(line).into_owned())
+        ))
+    }
+
     /// Load a list of summaries for `name` package in this registry which
     /// match `req`.
     ///
@@ -722,6 +784,7 @@ impl IndexSummary {
             yanked,
             links,
             rust_version,
+            pubtime,
             v,
         } = serde_json::from_slice(line)?;
         let v = v.unwrap_or(1);
@@ -738,6 +801,11 @@ impl IndexSummary {
         }
         let mut summary = Summary::new(pkgid, deps, &features, links, rust_version)?;
         summary.set_checksum(cksum);
+        // An invalid publish time is ignored rather than making the whole
+        // entry unusable.
+        if let Some(pubtime) = pubtime.and_then(|t| OffsetDateTime::parse(&t, &Rfc3339).ok()) {
+            summary.set_pubtime(pubtime);
+        }
 
         let v_max = if bindeps {
             INDEX_V_MAX + 1
@@ -467,6 +467,26 @@ fn short_name(id: SourceId, is_shallow: bool) -> String {
     name
 }
 
+/// The path of the `.crate` file of `pkg` in the cache of downloaded
+/// packages, if it was unpacked from there.
+///
+/// Remote registries unpack packages in a directory of
+/// [`GlobalContext::registry_source_path`] named like the directory of
+/// [`GlobalContext::registry_cache_path`] the `.crate` files are kept in.
+pub(crate) fn cached_crate_path(gctx: &GlobalContext, pkg: &Package) -> Option<PathBuf> {
+    let src_path = gctx.registry_source_path().into_path_unlocked();
+    let registry_dir = pkg.root().parent()?;
+    if registry_dir.parent()? != src_path {
+        return None;
+    }
+    let path = gctx
+        .registry_cache_path()
+        .into_path_unlocked()
+        .join(registry_dir.file_name()?)
+        .join(pkg.package_id().tarball_name());
+    path.is_file().then_some(path)
+}
+
 impl<'gctx> RegistrySource<'gctx> {
     /// Creates a [`Source`] of a "remote" registry.
     /// It could be either an HTTP-based [`http_remote::HttpRegistry`] or
@@ -937,6 +957,10 @@ impl<'gctx> Source for RegistrySource<'gctx> {
         self.index.is_yanked(pkg, &mut *self.ops)
     }
 
+    fn index_line(&mut self, pkg: PackageId) -> Poll<CargoResult<Option<String>>> {
+        self.index.index_line(pkg, &mut *self.ops)
+    }
+
     fn block_until_ready(&mut self) -> CargoResult<()> {
         // Before starting to work on the registry, make sure that
         // `<cargo_home>/registry` is marked as excluded from indexing and
@@ -167,6 +167,10 @@ impl<'gctx> Source for ReplacedSource<'gctx> {
         self.inner.is_yanked(pkg)
     }
 
+    fn index_line(&mut self, pkg: PackageId) -> Poll<CargoResult<Option<String>>> {
+        self.inner.index_line(pkg)
+    }
+
     fn block_until_ready(&mut self) -> CargoResult<()> {
         self.inner.block_until_ready().map_err(|e| {
             if self.is_builtin_replacement() {
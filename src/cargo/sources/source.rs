@@ -161,6 +161,12 @@ pub trait Source {
     /// as yanked. This ignores the yanked whitelist.
     fn is_yanked(&mut self, _pkg: PackageId) -> Poll<CargoResult<bool>>;
 
+    /// The line of the index file of a package, as published by the
+    /// registry. Only registry sources have index files.
+    fn index_line(&mut self, _pkg: PackageId) -> Poll<CargoResult<Option<String>>> {
+        Poll::Ready(Ok(None))
+    }
+
     /// Block until all outstanding [`Poll::Pending`] requests are [`Poll::Ready`].
     ///
     /// After calling this function, the source should return `Poll::Ready` for
@@ -274,6 +280,10 @@ impl<'a, T: Source + ?Sized + 'a> Source for Box<T> {
         (**self).is_yanked(pkg)
     }
 
+    fn index_line(&mut self, pkg: PackageId) -> Poll<CargoResult<Option<String>>> {
+        (**self).index_line(pkg)
+    }
+
     fn block_until_ready(&mut self) -> CargoResult<()> {
         (**self).block_until_ready()
     }
@@ -346,6 +356,10 @@ impl<'a, T: Source + ?Sized + 'a> Source for &'a mut T {
         (**self).is_yanked(pkg)
     }
 
+    fn index_line(&mut self, pkg: PackageId) -> Poll<CargoResult<Option<String>>> {
+        (**self).index_line(pkg)
+    }
+
     fn block_until_ready(&mut self) -> CargoResult<()> {
         (**self).block_until_ready()
     }
@@ -341,6 +341,17 @@ pub trait CommandExt: Sized {
         self._arg(multi_opt("message-format", "FMT", "Error format"))
     }
 
+    fn arg_lockfile_message_format(self) -> Self {
+        self._arg(
+            opt(
+                "message-format",
+                "Output representation of the lockfile changes (unstable)",
+            )
+            .value_name("FMT")
+            .value_parser(["human", "json"]),
+        )
+    }
+
     fn arg_build_plan(self) -> Self {
         self._arg(
             flag("build-plan", "Output the build plan in JSON (unstable)")
@@ -348,6 +359,17 @@ pub trait CommandExt: Sized {
         )
     }
 
+    fn arg_replay_build_plan(self) -> Self {
+        self._arg(
+            opt(
+                "replay-build-plan",
+                "Run the invocations of a build plan instead of building (unstable)",
+            )
+            .value_name("PATH")
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
+    }
+
     fn arg_unit_graph(self) -> Self {
         self._arg(
             flag("unit-graph", "Output build graph in JSON (unstable)")
@@ -355,6 +377,16 @@ pub trait CommandExt: Sized {
         )
     }
 
+    fn arg_explain_rebuild(self) -> Self {
+        self._arg(
+            flag(
+                "explain-rebuild",
+                "Explain why each unit is rebuilt (unstable)",
+            )
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
+    }
+
     fn arg_new_opts(self) -> Self {
         self._arg(
             opt(
@@ -436,7 +468,7 @@ pub trait CommandExt: Sized {
         self._arg(
             optional_opt(
                 "timings",
-                "Timing output formats (unstable) (comma separated): html, json",
+                "Timing output formats (unstable) (comma separated): html, json, json-file, trace",
             )
             .value_name("FMTS")
             .require_equals(true)
@@ -599,6 +631,22 @@ pub trait ArgMatchesExt {
         self.flag("dry-run")
     }
 
+    fn lockfile_message_format(
+        &self,
+        gctx: &GlobalContext,
+    ) -> CargoResult<ops::LockfileMessageFormat> {
+        let Some(fmt) = self._value_of("message-format") else {
+            return Ok(ops::LockfileMessageFormat::Human);
+        };
+        gctx.cli_unstable()
+            .fail_if_stable_opt_untracked("--message-format", "lockfile-message-format")?;
+        match fmt {
+            "human" => Ok(ops::LockfileMessageFormat::Human),
+            "json" => Ok(ops::LockfileMessageFormat::Json),
+            _ => unreachable!("checked by the value parser"),
+        }
+    }
+
     fn keep_going(&self) -> bool {
         self.maybe_flag("keep-going")
     }
@@ -759,6 +807,7 @@ Run `{cmd}` to see possible targets."
         build_config.build_plan = self.flag("build-plan");
         build_config.unit_graph = self.flag("unit-graph");
         build_config.future_incompat_report = self.flag("future-incompat-report");
+        build_config.explain_rebuild = self.flag("explain-rebuild");
 
         if self._contains("timings") {
             for timing_output in self._values_of("timings") {
@@ -775,6 +824,16 @@ Run `{cmd}` to see possible targets."
                                 .fail_if_stable_opt("--timings=json", 7405)?;
                             TimingOutput::Json
                         }
+                        "json-file" => {
+                            gctx.cli_unstable()
+                                .fail_if_stable_opt("--timings=json-file", 7405)?;
+                            TimingOutput::JsonFile
+                        }
+                        "trace" => {
+                            gctx.cli_unstable()
+                                .fail_if_stable_opt("--timings=trace", 7405)?;
+                            TimingOutput::Trace
+                        }
                         s => bail!("invalid timings output specifier: `{}`", s),
                     };
                     build_config.timing_outputs.push(timing_output);
@@ -793,6 +852,10 @@ Run `{cmd}` to see possible targets."
             gctx.cli_unstable()
                 .fail_if_stable_opt("--unit-graph", 8002)?;
         }
+        if build_config.explain_rebuild {
+            gctx.cli_unstable()
+                .fail_if_stable_opt("--explain-rebuild", 2904)?;
+        }
 
         let opts = CompileOptions {
             build_config,
@@ -1099,6 +1099,30 @@ impl GlobalContext {
         Ok(())
     }
 
+    /// Creates a new quiet instance with the same command-line settings as
+    /// this one, but with `homedir` as the Cargo home directory and
+    /// `target_dir` as the target directory.
+    ///
+    /// The config files of `homedir` are read instead of the ones of the
+    /// current home directory, so they need to be copied there first.
+    pub fn with_home(&self, homedir: PathBuf, target_dir: PathBuf) -> CargoResult<GlobalContext> {
+        let shell = Shell::from_write(Box::new(std::io::sink()));
+        let mut gctx = GlobalContext::new(shell, self.cwd.clone(), homedir);
+        gctx.nightly_features_allowed = self.nightly_features_allowed;
+        gctx.configure(
+            0,
+            true,
+            None,
+            self.frozen,
+            self.locked,
+            self.offline,
+            &Some(target_dir),
+            self.unstable_flags_cli.as_deref().unwrap_or_default(),
+            self.cli_config.as_deref().unwrap_or_default(),
+        )?;
+        Ok(gctx)
+    }
+
     fn load_unstable_flags_from_config(&mut self) -> CargoResult<()> {
         // If nightly features are enabled, allow setting Z-flags from config
         // using the `unstable` table. Ignore that block otherwise.
@@ -1146,6 +1170,10 @@ impl GlobalContext {
         self.locked = locked;
     }
 
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
     pub fn lock_update_allowed(&self) -> bool {
         !self.frozen && !self.locked
     }
@@ -2620,6 +2648,10 @@ pub struct CargoBuildConfig {
     // deprecated alias for artifact-dir
     pub out_dir: Option<ConfigRelativePath>,
     pub artifact_dir: Option<ConfigRelativePath>,
+    pub shared_deps_dir: Option<ConfigRelativePath>,
+    pub memory_limit: Option<String>,
+    pub link_jobs: Option<u32>,
+    pub unit_memory: Option<HashMap<String, String>>,
 }
 
 /// Configuration for `build.target`.
@@ -2686,6 +2718,9 @@ impl BuildTargetConfig {
 #[serde(rename_all = "kebab-case")]
 pub struct CargoResolverConfig {
     pub incompatible_rust_versions: Option<IncompatibleRustVersions>,
+    pub min_publish_age: Option<String>,
+    pub recent_versions: Option<RecentVersions>,
+    pub advisory_db: Option<ConfigRelativePath>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -2695,6 +2730,13 @@ pub enum IncompatibleRustVersions {
     Fallback,
 }
 
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecentVersions {
+    Fallback,
+    Deny,
+}
+
 #[derive(Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct TermConfig {
@@ -36,6 +36,22 @@ pub struct DependencyQueue<N: Hash + Eq, E: Hash + Eq, V> {
 
     /// An expected cost for building this package. Used to determine priority.
     cost: HashMap<N, usize>,
+
+    /// How `priority` is derived from `cost`.
+    priority_mode: PriorityMode,
+}
+
+/// How the priority of a node is derived from the costs of the nodes that
+/// depend on it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PriorityMode {
+    /// The cost of the node plus the costs of all nodes depending on it,
+    /// including transitively.
+    #[default]
+    TotalCost,
+    /// The cost of the most expensive chain of nodes starting with the node,
+    /// that is the longest path to the end of the graph.
+    LongestPath,
 }
 
 impl<N: Hash + Eq, E: Hash + Eq, V> Default for DependencyQueue<N, E, V> {
@@ -52,6 +68,7 @@ impl<N: Hash + Eq, E: Hash + Eq, V> DependencyQueue<N, E, V> {
             reverse_dep_map: HashMap::new(),
             priority: HashMap::new(),
             cost: HashMap::new(),
+            priority_mode: PriorityMode::default(),
         }
     }
 }
@@ -95,9 +112,26 @@ impl<N: Hash + Eq + Clone, E: Eq + Hash + Clone, V> DependencyQueue<N, E, V> {
         self.cost.insert(key, cost);
     }
 
+    /// Sets how priorities are computed by [`DependencyQueue::queue_finished`].
+    pub fn set_priority_mode(&mut self, mode: PriorityMode) {
+        self.priority_mode = mode;
+    }
+
     /// All nodes have been added, calculate some internal metadata and prepare
     /// for `dequeue`.
     pub fn queue_finished(&mut self) {
+        if self.priority_mode == PriorityMode::LongestPath {
+            let mut out = HashMap::new();
+            for key in self.dep_map.keys() {
+                longest_path(key, &self.reverse_dep_map, &self.cost, &mut out);
+            }
+            self.priority = out
+                .into_iter()
+                .map(|(n, cost)| (n, cost.unwrap()))
+                .collect();
+            return;
+        }
+
         let mut out = HashMap::new();
         for key in self.dep_map.keys() {
             depth(key, &self.reverse_dep_map, &mut out);
@@ -143,6 +177,33 @@ impl<N: Hash + Eq + Clone, E: Eq + Hash + Clone, V> DependencyQueue<N, E, V> {
             *slot = set;
             &*slot
         }
+
+        /// Finds the total cost of the most expensive chain of reverse
+        /// dependencies starting with `key`.
+        fn longest_path<N: Hash + Eq + Clone, E: Hash + Eq + Clone>(
+            key: &N,
+            map: &HashMap<N, HashMap<E, HashSet<N>>>,
+            cost: &HashMap<N, usize>,
+            results: &mut HashMap<N, Option<usize>>,
+        ) -> usize {
+            if let Some(result) = results.get(key) {
+                return result.expect("cycle in DependencyQueue");
+            }
+            results.insert(key.clone(), None);
+
+            let max_dep = map
+                .get(key)
+                .into_iter()
+                .flat_map(|it| it.values())
+                .flatten()
+                .map(|dep| longest_path(dep, map, cost, results))
+                .max()
+                .unwrap_or(0);
+
+            let total = cost[key] + max_dep;
+            results.insert(key.clone(), Some(total));
+            total
+        }
     }
 
     /// Dequeues a package that is ready to be built.
@@ -199,7 +260,7 @@ impl<N: Hash + Eq + Clone, E: Eq + Hash + Clone, V> DependencyQueue<N, E, V> {
 
 #[cfg(test)]
 mod test {
-    use super::DependencyQueue;
+    use super::{DependencyQueue, PriorityMode};
 
     #[test]
     fn deep_first_equal_cost() {
@@ -251,4 +312,34 @@ mod test {
         q.finish(&4, &());
         assert_eq!(q.dequeue(), None);
     }
+
+    #[test]
+    fn longest_path_first() {
+        let mut q = DependencyQueue::new();
+        q.set_priority_mode(PriorityMode::LongestPath);
+
+        // 1 -> 2 -> 4 is the critical path, even though the total cost of
+        // the nodes depending on 3 is higher.
+        q.queue(1, (), vec![], 1);
+        q.queue(2, (), vec![(1, ())], 10);
+        q.queue(3, (), vec![], 2);
+        q.queue(4, (), vec![(2, ()), (3, ())], 1);
+        q.queue(5, (), vec![(3, ())], 5);
+        q.queue(6, (), vec![(3, ())], 4);
+        q.queue_finished();
+
+        assert_eq!(q.dequeue(), Some((1, (), 12)));
+        assert_eq!(q.dequeue(), Some((3, (), 7)));
+        assert_eq!(q.dequeue(), None);
+        q.finish(&1, &());
+        assert_eq!(q.dequeue(), Some((2, (), 11)));
+        assert_eq!(q.dequeue(), None);
+        q.finish(&3, &());
+        assert_eq!(q.dequeue(), Some((5, (), 5)));
+        assert_eq!(q.dequeue(), Some((6, (), 4)));
+        assert_eq!(q.dequeue(), None);
+        q.finish(&2, &());
+        assert_eq!(q.dequeue(), Some((4, (), 1)));
+        assert_eq!(q.dequeue(), None);
+    }
 }
@@ -103,6 +103,68 @@ impl<'a> Message for TimingInfo<'a> {
     }
 }
 
+#[derive(Serialize)]
+pub struct TimingCriticalPath<'a> {
+    pub duration: f64,
+    pub units: Vec<TimingCriticalPathUnit<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct TimingCriticalPathUnit<'a> {
+    pub package_id: PackageIdSpec,
+    pub target: &'a Target,
+    pub mode: CompileMode,
+    pub critical: bool,
+    pub slack: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_speedup: Option<f64>,
+}
+
+impl<'a> Message for TimingCriticalPath<'a> {
+    fn reason(&self) -> &str {
+        "timing-critical-path"
+    }
+}
+
+#[derive(Serialize)]
+pub struct RebuildReason<'a> {
+    pub package_id: PackageIdSpec,
+    pub target: &'a Target,
+    pub mode: CompileMode,
+    #[serde(flatten)]
+    pub cause: RebuildCause<'a>,
+    /// The chain of dependencies which were rebuilt before this unit, ending
+    /// with the one whose change caused the rebuild.
+    pub caused_by: Vec<RebuildCauseOfDep<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct RebuildCauseOfDep<'a> {
+    pub package_id: PackageIdSpec,
+    pub target: &'a Target,
+    pub mode: CompileMode,
+    #[serde(flatten)]
+    pub cause: RebuildCause<'a>,
+}
+
+#[derive(Serialize)]
+pub struct RebuildCause<'a> {
+    pub kind: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependency: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<&'a Path>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_var: Option<&'a str>,
+}
+
+impl<'a> Message for RebuildReason<'a> {
+    fn reason(&self) -> &str {
+        "rebuild-reason"
+    }
+}
+
 #[derive(Serialize)]
 pub struct BuildFinished {
     pub success: bool,
@@ -113,3 +175,44 @@ impl Message for BuildFinished {
         "build-finished"
     }
 }
+
+#[derive(Serialize)]
+pub struct LockfileChange {
+    pub kind: &'static str,
+    pub package_id: PackageIdSpec,
+    pub name: String,
+    pub version: String,
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_package_id: Option<PackageIdSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
+    /// Whether a workspace member depends on the package directly.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direct: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_version: Option<String>,
+    /// Whether `rust_version` is compatible with the Rust version required
+    /// by the workspace, if both are known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_version_compatible: Option<bool>,
+    /// The newest SemVer-compatible version available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_compatible: Option<String>,
+    /// The newest version available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<String>,
+    /// The `update-policy` group of the package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// The newest SemVer-compatible version not allowed by the
+    /// `update-policy`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub held_back: Option<String>,
+}
+
+impl Message for LockfileChange {
+    fn reason(&self) -> &str {
+        "lockfile-change"
+    }
+}
@@ -4,7 +4,7 @@ use std::time::Duration;
 pub use self::canonical_url::CanonicalUrl;
 pub use self::context::{homedir, ConfigValue, GlobalContext};
 pub(crate) use self::counter::MetricsCounter;
-pub use self::dependency_queue::DependencyQueue;
+pub use self::dependency_queue::{DependencyQueue, PriorityMode};
 pub use self::diagnostic_server::RustfixDiagnosticServer;
 pub use self::edit_distance::{closest, closest_msg, edit_distance};
 pub use self::errors::CliError;
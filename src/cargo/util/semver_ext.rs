@@ -139,6 +139,17 @@ impl OptVersionReq {
         }
     }
 
+    /// Whether the original version requirement matches `version`, regardless
+    /// of the version it is locked or pinned to.
+    pub fn matches_unlocked(&self, version: &Version) -> bool {
+        match self {
+            OptVersionReq::Any => true,
+            OptVersionReq::Req(req)
+            | OptVersionReq::Locked(_, req)
+            | OptVersionReq::Precise(_, req) => req.matches(version),
+        }
+    }
+
     pub fn matches(&self, version: &Version) -> bool {
         match self {
             OptVersionReq::Any => true,
@@ -0,0 +1,452 @@
+//! Tests for the `-Zadvisory-db` feature.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, str};
+
+/// An advisory for `dep`, with the given `[versions]` table.
+fn advisory(id: &str, versions: &str) -> String {
+    format!(
+        r#"
+            [advisory]
+            id = "{id}"
+            package = "dep"
+            title = "Something is wrong"
+
+            [versions]
+            {versions}
+        "#
+    )
+}
+
+#[cargo_test]
+fn affected_versions_are_avoided() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.1.0").publish();
+    Package::new("dep", "1.2.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                advisory-db = "advisories"
+            "#,
+        )
+        .build();
+    p.change_file(
+        "advisories/dep/RUSTSEC-0000-0001.toml",
+        &advisory(
+            "RUSTSEC-0000-0001",
+            r#"patched = [">= 1.3.0"]
+            unaffected = ["< 1.1.0"]"#,
+        ),
+    );
+    // Informational and withdrawn advisories don't affect any version.
+    p.change_file(
+        "advisories/dep/RUSTSEC-0000-0002.toml",
+        r#"
+            [advisory]
+            id = "RUSTSEC-0000-0002"
+            package = "dep"
+            informational = "unmaintained"
+        "#,
+    );
+
+    p.cargo("generate-lockfile -Zadvisory-db")
+        .masquerade_as_nightly_cargo(&["advisory-db"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[ADDING] dep v1.0.0 (v1.2.0 affected by RUSTSEC-0000-0001)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn no_unaffected_version() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                advisory-db = "advisories"
+            "#,
+        )
+        .build();
+    p.change_file(
+        "advisories/RUSTSEC-0000-0001.toml",
+        &advisory("RUSTSEC-0000-0001", r#"patched = [">= 2.0.0"]"#),
+    );
+
+    p.cargo("generate-lockfile -Zadvisory-db")
+        .masquerade_as_nightly_cargo(&["advisory-db"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[ERROR] failed to select a version for `dep`.
+    ... required by package `foo v0.0.1 ([ROOT]/foo)`
+versions that meet the requirements `^1.0` are: 1.1.0, 1.0.0
+
+the package `foo` depends on `dep`, but versions affected by security advisories are excluded: 1.1.0 (RUSTSEC-0000-0001), 1.0.0 (RUSTSEC-0000-0001)
+
+failed to select a version for `dep` which could resolve this conflict
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn backtracks_to_unaffected_dependent() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "2.0.0").publish();
+    Package::new("bar", "1.0.0").dep("dep", "2.0").publish();
+    Package::new("bar", "1.1.0").dep("dep", "1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                advisory-db = "advisories"
+            "#,
+        )
+        .build();
+    p.change_file(
+        "advisories/RUSTSEC-0000-0001.toml",
+        &advisory("RUSTSEC-0000-0001", r#"patched = [">= 2.0.0"]"#),
+    );
+
+    // `bar v1.1.0` only allows affected versions of `dep`.
+    p.cargo("generate-lockfile -Zadvisory-db")
+        .masquerade_as_nightly_cargo(&["advisory-db"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[ADDING] bar v1.0.0 (available: v1.1.0)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn update_moves_away_from_affected_version() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                advisory-db = "advisories"
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+    assert!(p.read_lockfile().contains("1.1.0"));
+
+    p.change_file(
+        "advisories/RUSTSEC-0000-0001.toml",
+        &advisory("RUSTSEC-0000-0001", r#"unaffected = ["< 1.1.0"]"#),
+    );
+
+    // The locked version is kept until it is updated.
+    p.cargo("check -Zadvisory-db")
+        .masquerade_as_nightly_cargo(&["advisory-db"])
+        .with_stderr_data(str![[r#"
+[DOWNLOADING] crates ...
+[DOWNLOADED] dep v1.1.0 (registry `dummy-registry`)
+[CHECKING] dep v1.1.0
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    p.cargo("update -Zadvisory-db")
+        .masquerade_as_nightly_cargo(&["advisory-db"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[DOWNGRADING] dep v1.1.0 -> v1.0.0 (v1.1.0 affected by RUSTSEC-0000-0001)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn update_blocked_by_advisory() {
+    Package::new("dep", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                advisory-db = "advisories"
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    Package::new("dep", "1.1.0").publish();
+    p.change_file(
+        "advisories/RUSTSEC-0000-0001.toml",
+        &advisory("RUSTSEC-0000-0001", r#"unaffected = ["< 1.1.0"]"#),
+    );
+
+    p.cargo("update -Zadvisory-db")
+        .masquerade_as_nightly_cargo(&["advisory-db"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 0 packages to latest compatible versions
+[UNCHANGED] dep v1.0.0 (v1.1.0 affected by RUSTSEC-0000-0001)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn requires_unstable_flag() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                advisory-db = "advisories"
+            "#,
+        )
+        .build();
+    p.change_file(
+        "advisories/RUSTSEC-0000-0001.toml",
+        &advisory("RUSTSEC-0000-0001", r#"unaffected = ["< 1.1.0"]"#),
+    );
+
+    p.cargo("generate-lockfile")
+        .with_stderr_data(str![[r#"
+[WARNING] ignoring `resolver.advisory-db` config without `-Zadvisory-db`
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+
+"#]])
+        .run();
+    assert!(p.read_lockfile().contains("1.1.0"));
+}
+
+#[cargo_test]
+fn invalid_advisory() {
+    Package::new("dep", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                advisory-db = "advisories"
+            "#,
+        )
+        .build();
+    p.change_file(
+        "advisories/RUSTSEC-0000-0001.toml",
+        &advisory("RUSTSEC-0000-0001", r#"patched = ["not a version"]"#),
+    );
+
+    p.cargo("generate-lockfile -Zadvisory-db")
+        .masquerade_as_nightly_cargo(&["advisory-db"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to load `resolver.advisory-db`
+
+Caused by:
+  failed to parse advisory `[ROOT]/foo/advisories/RUSTSEC-0000-0001.toml`
+
+Caused by:
+  TOML parse error at line 8, column 24
+...
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn markdown_advisory() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                advisory-db = "advisory-db/crates"
+            "#,
+        )
+        .file("advisory-db/crates/README.md", "# Advisories")
+        .file(
+            "advisory-db/crates/dep/RUSTSEC-0000-0001.md",
+            r#"```toml
+[advisory]
+id = "RUSTSEC-0000-0001"
+package = "dep"
+date = 2024-01-01
+
+[versions]
+unaffected = ["< 1.1.0"]
+```
+
+# Something is wrong
+
+`dep` 1.1.0 does something wrong.
+"#,
+        )
+        .build();
+
+    p.cargo("generate-lockfile -Zadvisory-db")
+        .masquerade_as_nightly_cargo(&["advisory-db"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[ADDING] dep v1.0.0 (v1.1.0 affected by RUSTSEC-0000-0001)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn no_advisories() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                advisory-db = "advisory-db"
+            "#,
+        )
+        .file("advisory-db/README.md", "# Advisories")
+        .build();
+
+    p.cargo("generate-lockfile -Zadvisory-db")
+        .masquerade_as_nightly_cargo(&["advisory-db"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to load `resolver.advisory-db`
+
+Caused by:
+  no advisories found in `[ROOT]/foo/advisory-db`
+
+"#]])
+        .run();
+}
@@ -0,0 +1,358 @@
+//! Tests for the `-Zbuild-cache` feature.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::{Package, RegistryBuilder, Response};
+use cargo_test_support::{paths, project, str};
+
+#[cargo_test]
+fn local_cache_shared_between_projects() {
+    Package::new("baz", "1.0.0")
+        .file("src/lib.rs", "pub fn baz() { println!(\"baz\"); }")
+        .publish();
+    Package::new("bar", "1.0.0")
+        .dep("baz", "1.0")
+        .file(
+            "src/lib.rs",
+            "extern crate baz; pub fn bar() { baz::baz(); }",
+        )
+        .publish();
+    let config = format!(
+        "[build-cache]\npath = '{}'\n",
+        paths::root().join("cache").display()
+    );
+
+    let p1 = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file(".cargo/config.toml", &config)
+        .build();
+    p1.cargo("build -v -Zbuild-cache")
+        .masquerade_as_nightly_cargo(&["build-cache"])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] baz v1.0.0 (registry `dummy-registry`)
+[COMPILING] baz v1.0.0
+[RUNNING] `rustc --crate-name baz [..]`
+[COMPILING] bar v1.0.0
+[RUNNING] `rustc --crate-name bar [..]`
+[COMPILING] foo v0.1.0 ([ROOT]/a)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+    assert!(paths::root().join("cache").is_dir());
+
+    // The dependencies are restored from the cache instead of being compiled.
+    let p2 = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file(".cargo/config.toml", &config)
+        .build();
+    p2.cargo("run -v -Zbuild-cache")
+        .masquerade_as_nightly_cargo(&["build-cache"])
+        .with_stdout_data(str![[r#"
+baz
+
+"#]])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[COMPILING] baz v1.0.0
+[COMPILING] bar v1.0.0
+[COMPILING] foo v0.1.0 ([ROOT]/b)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] `target/debug/foo[EXE]`
+
+"#]]
+            .unordered(),
+        )
+        .run();
+
+    // Restored units are fresh afterwards.
+    p2.cargo("build -v -Zbuild-cache")
+        .masquerade_as_nightly_cargo(&["build-cache"])
+        .with_stderr_data(
+            str![[r#"
+[FRESH] baz v1.0.0
+[FRESH] bar v1.0.0
+[FRESH] foo v0.1.0 ([ROOT]/b)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+
+    // Different flags don't share entries.
+    p2.cargo("build -v -Zbuild-cache --release")
+        .masquerade_as_nightly_cargo(&["build-cache"])
+        .with_stderr_data(
+            str![[r#"
+[COMPILING] baz v1.0.0
+[RUNNING] `rustc --crate-name baz [..]`
+[COMPILING] bar v1.0.0
+[RUNNING] `rustc --crate-name bar [..]`
+[COMPILING] foo v0.1.0 ([ROOT]/b)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `release` profile [optimized] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn build_scripts_are_not_cached() {
+    Package::new("baz", "1.0.0")
+        .file("build.rs", "fn main() {}")
+        .file("src/lib.rs", "pub fn baz() {}")
+        .publish();
+    Package::new("bar", "1.0.0")
+        .dep("baz", "1.0")
+        .file(
+            "src/lib.rs",
+            "extern crate baz; pub fn bar() { baz::baz(); }",
+        )
+        .publish();
+    let config = format!(
+        "[build-cache]\npath = '{}'\n",
+        paths::root().join("cache").display()
+    );
+
+    let p1 = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file(".cargo/config.toml", &config)
+        .build();
+    p1.cargo("build -Zbuild-cache")
+        .masquerade_as_nightly_cargo(&["build-cache"])
+        .run();
+
+    // `baz` has a build script, and `bar` depends on it.
+    let p2 = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file(".cargo/config.toml", &config)
+        .build();
+    p2.cargo("build -v -Zbuild-cache")
+        .masquerade_as_nightly_cargo(&["build-cache"])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[COMPILING] baz v1.0.0
+[RUNNING] `rustc --crate-name build_script_build [..]`
+[RUNNING] `[ROOT]/b/target/debug/build/baz-[HASH]/build-script-build`
+[RUNNING] `rustc --crate-name baz [..]`
+[COMPILING] bar v1.0.0
+[RUNNING] `rustc --crate-name bar [..]`
+[COMPILING] foo v0.1.0 ([ROOT]/b)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn http_cache() {
+    let entries = Arc::new(Mutex::new(HashMap::new()));
+    let server_entries = Arc::clone(&entries);
+    let registry = RegistryBuilder::new()
+        .http_api()
+        .not_found_handler(move |req, _server| {
+            let Some(key) = req.url.path().strip_prefix("/build-cache/") else {
+                return Response {
+                    code: 404,
+                    headers: vec![],
+                    body: vec![],
+                };
+            };
+            let mut entries = server_entries.lock().unwrap();
+            match req.method.as_str() {
+                "put" => {
+                    entries.insert(key.to_string(), req.body.clone().unwrap());
+                    Response {
+                        code: 201,
+                        headers: vec![],
+                        body: vec![],
+                    }
+                }
+                _ => match entries.get(key) {
+                    Some(body) => Response {
+                        code: 200,
+                        headers: vec![],
+                        body: body.clone(),
+                    },
+                    None => Response {
+                        code: 404,
+                        headers: vec![],
+                        body: vec![],
+                    },
+                },
+            }
+        })
+        .build();
+    Package::new("baz", "1.0.0")
+        .file("src/lib.rs", "pub fn baz() { println!(\"baz\"); }")
+        .publish();
+    Package::new("bar", "1.0.0")
+        .dep("baz", "1.0")
+        .file(
+            "src/lib.rs",
+            "extern crate baz; pub fn bar() { baz::baz(); }",
+        )
+        .publish();
+    let config = format!("[build-cache]\nurl = '{}build-cache'\n", registry.api_url());
+
+    let p1 = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file(".cargo/config.toml", &config)
+        .build();
+    p1.cargo("build -Zbuild-cache")
+        .masquerade_as_nightly_cargo(&["build-cache"])
+        .run();
+    assert_eq!(entries.lock().unwrap().len(), 2);
+
+    let p2 = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file(".cargo/config.toml", &config)
+        .build();
+    p2.cargo("run -v -Zbuild-cache")
+        .masquerade_as_nightly_cargo(&["build-cache"])
+        .with_stdout_data(str![[r#"
+baz
+
+"#]])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[COMPILING] baz v1.0.0
+[COMPILING] bar v1.0.0
+[COMPILING] foo v0.1.0 ([ROOT]/b)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] `target/debug/foo[EXE]`
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn both_path_and_url() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build-cache]
+                path = "cache"
+                url = "http://localhost/cache"
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Zbuild-cache")
+        .masquerade_as_nightly_cargo(&["build-cache"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] only one of `build-cache.path` and `build-cache.url` may be specified
+
+"#]])
+        .run();
+
+    // The configuration is ignored without `-Zbuild-cache`.
+    p.cargo("build").run();
+}
@@ -1,9 +1,12 @@
 //! Tests for --build-plan feature.
 
+use cargo::core::compiler::build_plan::executor::{self, Plan};
 use cargo_test_support::prelude::*;
 use cargo_test_support::registry::Package;
 use cargo_test_support::{basic_bin_manifest, basic_manifest, main_file, project, str};
 
+use crate::config::GlobalContextBuilder;
+
 #[cargo_test]
 fn cargo_build_plan_simple() {
     let p = project()
@@ -22,10 +25,16 @@ fn cargo_build_plan_simple() {
   "invocations": [
     {
       "args": "{...}",
+      "build_script_uses": [],
       "compile_mode": "build",
       "cwd": "[ROOT]/foo",
+      "dep_info": "[ROOT]/foo/target/debug/deps/foo-[HASH].d",
       "deps": [],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [
+        "[ROOT]/foo/src/foo.rs"
+      ],
       "kind": null,
       "links": "{...}",
       "outputs": "{...}",
@@ -34,9 +43,11 @@ fn cargo_build_plan_simple() {
       "program": "rustc",
       "target_kind": [
         "bin"
-      ]
+      ],
+      "target_name": "foo"
     }
-  ]
+  ],
+  "version": 1
 }
 "#]]
             .is_json(),
@@ -85,10 +96,16 @@ fn cargo_build_plan_single_dep() {
   "invocations": [
     {
       "args": "{...}",
+      "build_script_uses": [],
       "compile_mode": "build",
       "cwd": "[ROOT]/foo",
+      "dep_info": "[ROOT]/foo/target/debug/deps/bar-[HASH].d",
       "deps": [],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [
+        "[ROOT]/foo/bar/src/lib.rs"
+      ],
       "kind": null,
       "links": {},
       "outputs": [
@@ -100,16 +117,23 @@ fn cargo_build_plan_single_dep() {
       "program": "rustc",
       "target_kind": [
         "lib"
-      ]
+      ],
+      "target_name": "bar"
     },
     {
       "args": "{...}",
+      "build_script_uses": [],
       "compile_mode": "build",
       "cwd": "[ROOT]/foo",
+      "dep_info": "[ROOT]/foo/target/debug/deps/foo-[HASH].d",
       "deps": [
         0
       ],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [
+        "[ROOT]/foo/src/lib.rs"
+      ],
       "kind": null,
       "links": "{...}",
       "outputs": [
@@ -121,9 +145,11 @@ fn cargo_build_plan_single_dep() {
       "program": "rustc",
       "target_kind": [
         "lib"
-      ]
+      ],
+      "target_name": "foo"
     }
-  ]
+  ],
+  "version": 1
 }
 "#]]
             .is_json(),
@@ -160,10 +186,16 @@ fn cargo_build_plan_build_script() {
   "invocations": [
     {
       "args": "{...}",
+      "build_script_uses": [],
       "compile_mode": "build",
       "cwd": "[ROOT]/foo",
+      "dep_info": "[ROOT]/foo/target/debug/build/foo-[HASH]/build_script_build-[HASH].d",
       "deps": [],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [
+        "[ROOT]/foo/build.rs"
+      ],
       "kind": null,
       "links": "{...}",
       "outputs": "{...}",
@@ -172,16 +204,35 @@ fn cargo_build_plan_build_script() {
       "program": "rustc",
       "target_kind": [
         "custom-build"
-      ]
+      ],
+      "target_name": "build-script-build"
     },
     {
       "args": "{...}",
+      "build_script": {
+        "links": null,
+        "out_dir": "[ROOT]/foo/target/debug/build/foo-[HASH]/out",
+        "output_file": "[ROOT]/foo/target/debug/build/foo-[HASH]/output",
+        "targets": [
+          {
+            "kind": "bin",
+            "name": "foo"
+          },
+          {
+            "kind": "build-script",
+            "name": "build-script-build"
+          }
+        ]
+      },
+      "build_script_uses": [],
       "compile_mode": "run-custom-build",
       "cwd": "[ROOT]/foo",
       "deps": [
         0
       ],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [],
       "kind": null,
       "links": {},
       "outputs": [],
@@ -190,16 +241,32 @@ fn cargo_build_plan_build_script() {
       "program": "[ROOT]/foo/target/debug/build/foo-[HASH]/build-script-build",
       "target_kind": [
         "custom-build"
-      ]
+      ],
+      "target_name": "build-script-build"
     },
     {
       "args": "{...}",
+      "build_script_uses": [
+        {
+          "flags": true,
+          "invocation": 1,
+          "link_args": true,
+          "link_lib": true,
+          "link_search": true,
+          "metadata": false
+        }
+      ],
       "compile_mode": "build",
       "cwd": "[ROOT]/foo",
+      "dep_info": "[ROOT]/foo/target/debug/deps/foo-[HASH].d",
       "deps": [
         1
       ],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [
+        "[ROOT]/foo/src/main.rs"
+      ],
       "kind": null,
       "links": "{...}",
       "outputs": "{...}",
@@ -208,9 +275,11 @@ fn cargo_build_plan_build_script() {
       "program": "rustc",
       "target_kind": [
         "bin"
-      ]
+      ],
+      "target_name": "foo"
     }
-  ]
+  ],
+  "version": 1
 }
 "#]]
             .is_json(),
@@ -242,3 +311,119 @@ fn build_plan_with_dev_dep() {
         .masquerade_as_nightly_cargo(&["build-plan"])
         .run();
 }
+
+#[cargo_test]
+fn execute_build_plan() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    let value = std::env::var("DEP_BAR_VALUE").unwrap();
+                    println!("cargo::rustc-env=BAR_VALUE={}", value);
+                    println!("cargo::rustc-cfg=from_build_script");
+                }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                #[cfg(from_build_script)]
+                fn main() {
+                    println!("{} {}", env!("BAR_VALUE"), bar::bar());
+                }
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.0.1"
+                edition = "2015"
+                links = "bar"
+            "#,
+        )
+        .file(
+            "bar/build.rs",
+            r#"fn main() { println!("cargo:value=from-bar"); }"#,
+        )
+        .file("bar/src/lib.rs", "pub fn bar() -> u32 { 42 }")
+        .build();
+
+    let output = p
+        .cargo("build --build-plan -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .exec_with_output()
+        .unwrap();
+    let plan = Plan::from_json(std::str::from_utf8(&output.stdout).unwrap()).unwrap();
+    assert!(!p.bin("foo").is_file());
+
+    let gctx = GlobalContextBuilder::new().cwd(p.root()).build();
+    executor::execute(&gctx, &plan).unwrap();
+
+    p.process(&p.bin("foo"))
+        .with_stdout_data(str![[r#"
+from-bar 42
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+#[cfg(target_os = "linux")]
+fn execute_build_plan_link_arg_bins() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.5.0"))
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo::rustc-link-arg-bins=-Wl,--as-needed"); }"#,
+        )
+        .file("src/main.rs", r#"fn main() { println!("hello"); }"#)
+        .build();
+
+    let output = p
+        .cargo("build --build-plan -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .exec_with_output()
+        .unwrap();
+    std::fs::write(p.root().join("plan.json"), &output.stdout).unwrap();
+
+    p.cargo("build --replay-build-plan plan.json")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--replay-build-plan` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://github.com/rust-lang/cargo/issues/5579 for more information about the `--replay-build-plan` flag.
+
+"#]])
+        .run();
+
+    p.cargo("build --replay-build-plan plan.json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .with_stderr_data(str![[r#"
+       Built foo v0.5.0 (build `build-script-build`)
+       Built foo v0.5.0 (build `foo`)
+
+"#]])
+        .run();
+
+    p.process(&p.bin("foo"))
+        .with_stdout_data(str![[r#"
+hello
+
+"#]])
+        .run();
+}
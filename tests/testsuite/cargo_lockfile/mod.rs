@@ -0,0 +1,634 @@
+//! Tests for the `cargo lockfile` command.
+
+use std::fs;
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_lib_manifest, git, project, str};
+
+mod help;
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("lockfile diff Cargo.lock")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `cargo lockfile` command is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#cargo-lockfile for more information about the `cargo lockfile` command.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn diff() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    fs::write(p.root().join("base.lock"), p.read_lockfile()).unwrap();
+
+    for version in ["1.0.1", "1.0.2"] {
+        Package::new("a", version).publish();
+        Package::new("b", version).publish();
+    }
+    p.cargo("update b --precise 1.0.1").run();
+    fs::write(p.root().join("theirs.lock"), p.read_lockfile()).unwrap();
+
+    p.cargo("lockfile diff base.lock theirs.lock -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+   Updating b v1.0.0 -> v1.0.1
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn diff_dependencies() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    fs::write(p.root().join("base.lock"), p.read_lockfile()).unwrap();
+
+    for version in ["1.0.1", "1.0.2"] {
+        Package::new("a", version).publish();
+        Package::new("b", version).publish();
+    }
+    Package::new("c", "1.0.0").publish();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            a = "1.0"
+            c = "1.0"
+        "#,
+    );
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("lockfile diff base.lock -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+   Updating a v1.0.0 -> v1.0.2
+   Removing b v1.0.0
+     Adding c v1.0.0
+   Changing foo v0.1.0 ([ROOT]/foo): dependencies +c, -b
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn diff_lockfile_path() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    fs::write(p.root().join("base.lock"), p.read_lockfile()).unwrap();
+
+    for version in ["1.0.1", "1.0.2"] {
+        Package::new("a", version).publish();
+        Package::new("b", version).publish();
+    }
+    p.cargo("update b --precise 1.0.1").run();
+    fs::create_dir(p.root().join("theirs")).unwrap();
+    fs::write(p.root().join("theirs/Cargo.lock"), p.read_lockfile()).unwrap();
+
+    p.cargo("lockfile diff base.lock --lockfile-path theirs/Cargo.lock -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+   Updating b v1.0.0 -> v1.0.1
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn merge_without_conflicts() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    let base = p.read_lockfile();
+    fs::write(p.root().join("base.lock"), &base).unwrap();
+
+    for version in ["1.0.1", "1.0.2"] {
+        Package::new("a", version).publish();
+        Package::new("b", version).publish();
+    }
+    p.cargo("update a --precise 1.0.1").run();
+    fs::write(p.root().join("ours.lock"), p.read_lockfile()).unwrap();
+
+    fs::write(p.root().join("Cargo.lock"), &base).unwrap();
+    p.cargo("update b --precise 1.0.1").run();
+    fs::write(p.root().join("theirs.lock"), p.read_lockfile()).unwrap();
+
+    p.cargo("lockfile merge base.lock ours.lock theirs.lock -o Cargo.lock -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[LOCKING] 1 package to latest compatible version
+[UPDATING] b v1.0.0 -> v1.0.1 (available: v1.0.2)
+
+"#]])
+        .run();
+    p.cargo("lockfile diff base.lock -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+   Updating a v1.0.0 -> v1.0.1
+   Updating b v1.0.0 -> v1.0.1
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn merge_with_conflicts() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    let base = p.read_lockfile();
+    fs::write(p.root().join("base.lock"), &base).unwrap();
+
+    for version in ["1.0.1", "1.0.2"] {
+        Package::new("a", version).publish();
+        Package::new("b", version).publish();
+    }
+    p.cargo("update a --precise 1.0.1").run();
+    fs::write(p.root().join("ours.lock"), p.read_lockfile()).unwrap();
+
+    fs::write(p.root().join("Cargo.lock"), &base).unwrap();
+    p.cargo("update a --precise 1.0.2").run();
+    p.cargo("update b --precise 1.0.1").run();
+    fs::write(p.root().join("theirs.lock"), p.read_lockfile()).unwrap();
+    // Neither side has it, so the merge doesn't pick it.
+    Package::new("a", "1.0.3").publish();
+
+    p.cargo("lockfile merge base.lock ours.lock theirs.lock -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[NOTE] keeping the higher version of conflicting packages, or ours: `a`
+[LOCKING] 2 packages to latest compatible versions
+[UPDATING] a v1.0.1 -> v1.0.2
+[UPDATING] b v1.0.0 -> v1.0.1 (available: v1.0.2)
+
+"#]])
+        .run();
+    p.cargo("lockfile diff base.lock ours.lock -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+   Updating a v1.0.0 -> v1.0.2
+   Updating b v1.0.0 -> v1.0.1
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn verify() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("lockfile verify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[VERIFIED] 3 entries of `Cargo.lock`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn verify_without_lockfile() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("lockfile verify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] no `Cargo.lock` to verify in [ROOT]/foo
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn verify_tampered_entries() {
+    let a_checksum = Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    Package::new("b", "1.0.0").yanked(true).publish();
+    Package::new("c", "1.0.0").publish();
+    let lock = p.read_lockfile().replace(&a_checksum, &"0".repeat(64));
+    let lock = format!(
+        "{lock}
+[[package]]
+name = \"c\"
+version = \"1.0.0\"
+source = \"registry+https://github.com/rust-lang/crates.io-index\"
+
+[[package]]
+name = \"d\"
+version = \"1.0.0\"
+source = \"registry+https://github.com/rust-lang/crates.io-index\"
+"
+    );
+    p.change_file("Cargo.lock", &lock);
+
+    p.cargo("lockfile verify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[ERROR] 4 entries of `Cargo.lock` failed verification:
+a v1.0.0:
+  - checksum `0000000000000000000000000000000000000000000000000000000000000000` does not match checksum `3a351dafbc8a3a9cba7c06dfe8caa11a3a45f800a336bb5b913a8f1e2652d454` of `dummy-registry` index (which is replacing registry `crates-io`)
+b v1.0.0:
+  - yanked in `dummy-registry` index (which is replacing registry `crates-io`)
+c v1.0.0:
+  - orphaned, not used by any workspace member
+  - missing checksum, `dummy-registry` index (which is replacing registry `crates-io`) has one
+d v1.0.0:
+  - orphaned, not used by any workspace member
+  - version 1.0.0 not found in `dummy-registry` index (which is replacing registry `crates-io`)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn verify_vendored() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    p.cargo("vendor --respect-source-config").run();
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [source.crates-io]
+            replace-with = "vendored-sources"
+
+            [source.vendored-sources]
+            directory = "vendor"
+        "#,
+    );
+
+    p.cargo("lockfile verify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[VERIFIED] 3 entries of `Cargo.lock`
+
+"#]])
+        .run();
+
+    p.change_file("vendor/a/src/lib.rs", "pub fn injected() {}");
+    p.cargo("lockfile verify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] 1 entry of `Cargo.lock` failed verification:
+a v1.0.0:
+  - the listed checksum of `[ROOT]/foo/vendor/a/src/lib.rs` has changed:
+    expected: e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855
+    actual:   78730a5107ea8a7d29a462e9b4878222f3ab7d1553b78bff0abe05f231403b8f
+
+    directory sources are not intended to be edited, if modifications are required then it is recommended that `[patch]` is used with a forked copy of the source
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn verify_git_revision() {
+    let dep = git::new("dep", |p| {
+        p.file("Cargo.toml", &basic_lib_manifest("dep"))
+            .file("src/lib.rs", "")
+    });
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+                    edition = "2015"
+
+                    [dependencies]
+                    dep = {{ git = "{}" }}
+                "#,
+                dep.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("lockfile verify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[VERIFIED] 2 entries of `Cargo.lock`
+
+"#]])
+        .run();
+
+    let rev = git2::Repository::open(&dep.root())
+        .unwrap()
+        .head()
+        .unwrap()
+        .target()
+        .unwrap();
+    let lock = p.read_lockfile().replace(&rev.to_string(), &"1".repeat(40));
+    p.change_file("Cargo.lock", &lock);
+    p.cargo("lockfile verify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] git repository `[ROOTURL]/dep`
+[ERROR] 1 entry of `Cargo.lock` failed verification:
+dep v0.5.0 ([ROOTURL]/dep#11111111):
+  - revspec '1111111111111111111111111111111111111111' not found; class=Reference (4); code=NotFound (-3)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn unify() {
+    Package::new("dep", "1.0.0").publish();
+    let p = project()
+        .no_manifest()
+        .file(
+            "one/Cargo.toml",
+            r#"
+                [package]
+                name = "one"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("one/src/lib.rs", "")
+        .file(
+            "two/Cargo.toml",
+            r#"
+                [package]
+                name = "two"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("two/src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [super-workspace]
+                members = ["one", "two"]
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").cwd(p.root().join("one")).run();
+    // `two` is locked to a newer `dep` than `one`.
+    Package::new("dep", "1.0.1").publish();
+    p.cargo("generate-lockfile").cwd(p.root().join("two")).run();
+
+    p.cargo("lockfile unify --check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[ERROR] 1 of 2 lockfiles don't agree with the joint resolve of `super-workspace.members`:
+[ROOT]/foo/one/Cargo.lock:
+  Updating dep v1.0.0 -> v1.0.1
+
+"#]])
+        .run();
+
+    p.cargo("lockfile unify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[UNIFYING] [ROOT]/foo/one/Cargo.lock
+[LOCKING] 1 package to latest compatible version
+[UPDATING] dep v1.0.0 -> v1.0.1
+
+"#]])
+        .run();
+
+    p.cargo("lockfile unify --check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn unify_constrained_by_other_workspace() {
+    Package::new("dep", "1.0.0").publish();
+    let p = project()
+        .no_manifest()
+        .file(
+            "one/Cargo.toml",
+            r#"
+                [package]
+                name = "one"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                dep = "=1.0.0"
+            "#,
+        )
+        .file("one/src/lib.rs", "")
+        .file(
+            "two/Cargo.toml",
+            r#"
+                [package]
+                name = "two"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("two/src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [super-workspace]
+                members = ["one", "two"]
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").cwd(p.root().join("one")).run();
+    // `two` is locked to a newer `dep` than `one`.
+    Package::new("dep", "1.0.1").publish();
+    p.cargo("generate-lockfile").cwd(p.root().join("two")).run();
+
+    p.cargo("lockfile unify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[UNIFYING] [ROOT]/foo/two/Cargo.lock
+[LOCKING] 1 package to latest compatible version
+[DOWNGRADING] dep v1.0.1 -> v1.0.0 (available: v1.0.1)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn unify_without_config() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("lockfile unify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] no workspaces listed in the `super-workspace.members` config
+
+"#]])
+        .run();
+}
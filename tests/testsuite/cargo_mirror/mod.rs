@@ -0,0 +1,316 @@
+//! Tests for the `cargo mirror` command.
+
+use std::fs;
+
+use cargo_test_support::compare::assert_e2e;
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::{self, Dependency, Package};
+use cargo_test_support::{basic_manifest, project, str};
+
+mod help;
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("mirror")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `cargo mirror` command is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#cargo-mirror for more information about the `cargo mirror` command.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn requires_lockfile() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("mirror -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the workspace has no lockfile, run `cargo generate-lockfile` first
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn mirror_as_local_registry() {
+    Package::new("bar", "0.1.0")
+        .file("src/lib.rs", "pub fn bar() {}")
+        .publish();
+    Package::new("baz", "0.2.0").dep("bar", "0.1").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                baz = "0.2"
+                bar = "0.1"
+                local = { path = "local" }
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            "extern crate bar; pub fn foo() { bar::bar(); }",
+        )
+        .file("local/Cargo.toml", &basic_manifest("local", "0.1.0"))
+        .file("local/src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("mirror -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+[source.crates-io]
+replace-with = "mirror"
+
+[source.mirror]
+local-registry = "[ROOT]/foo/mirror"
+
+"#]])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] baz v0.2.0 (registry `dummy-registry`)
+[DOWNLOADED] bar v0.1.0 (registry `dummy-registry`)
+[MIRRORED] 2 packages to mirror
+To use the mirror, add this to your .cargo/config.toml:
+
+
+"#]]
+            .unordered(),
+        )
+        .run();
+
+    assert!(p.root().join("mirror/bar-0.1.0.crate").is_file());
+    assert!(p.root().join("mirror/baz-0.2.0.crate").is_file());
+    assert!(p.root().join("mirror/index/3/b/bar").is_file());
+    assert!(p.root().join("mirror/index/3/b/baz").is_file());
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [source.crates-io]
+            replace-with = "mirror"
+
+            [source.mirror]
+            local-registry = "mirror"
+        "#,
+    );
+    p.cargo("build --offline")
+        .with_stderr_data(
+            str![[r#"
+[UNPACKING] bar v0.1.0 (registry `[ROOT]/foo/mirror`)
+[UNPACKING] baz v0.2.0 (registry `[ROOT]/foo/mirror`)
+[COMPILING] bar v0.1.0
+[COMPILING] baz v0.2.0
+[COMPILING] local v0.1.0 ([ROOT]/foo/local)
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn index_entries() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("qux", "0.1.0").publish();
+    Package::new("baz", "0.2.0")
+        .add_dep(Dependency::new("renamed", "0.1").package("bar"))
+        .add_dep(Dependency::new("qux", "0.1").optional(true).public(true))
+        .feature("std", &["renamed/std", "qux?/std"])
+        .rust_version("1.60")
+        .links("baz")
+        .pubtime("2024-01-01T00:00:00Z")
+        .yanked(true)
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                baz = "0.2"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.change_file(
+        "Cargo.lock",
+        r#"
+            version = 4
+
+            [[package]]
+            name = "bar"
+            version = "0.1.0"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+
+            [[package]]
+            name = "baz"
+            version = "0.2.0"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+            dependencies = ["bar"]
+
+            [[package]]
+            name = "foo"
+            version = "0.1.0"
+            dependencies = ["baz"]
+        "#,
+    );
+
+    p.cargo("mirror -Zunstable-options --url https://mirror.example.com/crates/")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+[source.crates-io]
+replace-with = "mirror"
+
+[source.mirror]
+registry = "sparse+https://mirror.example.com/crates/index/"
+
+"#]])
+        .run();
+
+    assert_e2e().eq(
+        fs::read_to_string(p.root().join("mirror/index/config.json")).unwrap(),
+        str![[r#"
+{
+  "dl": "https://mirror.example.com/crates/{crate}-{version}.crate"
+}
+
+"#]],
+    );
+    // The entries are copied from the registry as is.
+    let baz = fs::read_to_string(p.root().join("mirror/index/3/b/baz")).unwrap();
+    assert_eq!(
+        baz,
+        fs::read_to_string(registry::registry_path().join("3/b/baz")).unwrap()
+    );
+    assert_e2e().eq(
+        baz,
+        str![[r#"
+{"cksum":"[..]","deps":[{"artifact":null,"bindep_target":null,"default_features":true,"features":[],"kind":"normal","lib":false,"name":"renamed","optional":false,"package":"bar","public":false,"registry":null,"req":"0.1","target":null},{"artifact":null,"bindep_target":null,"default_features":true,"features":[],"kind":"normal","lib":false,"name":"qux","optional":true,"package":null,"public":true,"registry":null,"req":"0.1","target":null}],"features":{"std":[]},"features2":{"std":["renamed/std","qux?/std"]},"links":"baz","name":"baz","pubtime":"2024-01-01T00:00:00Z","rust_version":"1.60","v":2,"vers":"0.2.0","yanked":true}
+
+"#]],
+    );
+}
+
+#[cargo_test]
+fn several_lockfiles() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.2.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    let other = project()
+        .at("other")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "other"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "0.2"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    other.cargo("generate-lockfile").run();
+
+    p.cargo("mirror -Zunstable-options --lockfile Cargo.lock")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+...
+[MIRRORED] 1 package to mirror
+...
+"#]])
+        .run();
+    // Mirroring again adds to the mirror.
+    p.cargo("mirror -Zunstable-options --lockfile Cargo.lock --lockfile ../other/Cargo.lock")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+...
+[MIRRORED] 2 packages to mirror
+...
+"#]])
+        .run();
+
+    let index = fs::read_to_string(p.root().join("mirror/index/3/b/bar")).unwrap();
+    let versions: Vec<_> = index
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["vers"].clone())
+        .collect();
+    assert_eq!(versions, ["0.1.0", "0.2.0"]);
+    assert!(p.root().join("mirror/bar-0.1.0.crate").is_file());
+    assert!(p.root().join("mirror/bar-0.2.0.crate").is_file());
+}
+
+#[cargo_test]
+fn skips_other_registries() {
+    let _alt = cargo_test_support::registry::alt_init();
+    Package::new("bar", "0.1.0").publish();
+    Package::new("alt", "0.1.0").alternative(true).publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "0.1"
+                alt = { version = "0.1", registry = "alternative" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("mirror -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[WARNING] skipping `alt v0.1.0 (registry `alternative`)`, only packages from crates.io are mirrored
+...
+[MIRRORED] 1 package to mirror
+...
+"#]])
+        .run();
+
+    assert!(p.root().join("mirror/bar-0.1.0.crate").is_file());
+    assert!(!p.root().join("mirror/alt-0.1.0.crate").exists());
+}
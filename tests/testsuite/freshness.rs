@@ -3149,3 +3149,252 @@ fn use_mtime_cache_in_cargo_home() {
 "#]])
         .run();
 }
+
+#[cargo_test]
+fn explain_rebuild_gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("check --explain-rebuild")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--explain-rebuild` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://github.com/rust-lang/cargo/issues/2904 for more information about the `--explain-rebuild` flag.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn explain_rebuild() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "extern crate bar;")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                baz = { path = "../baz" }
+            "#,
+        )
+        .file("bar/src/lib.rs", "extern crate baz;")
+        .file("baz/Cargo.toml", &basic_manifest("baz", "0.0.1"))
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    p.cargo("check --explain-rebuild -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["explain-rebuild"])
+        .with_stderr_data(str![[r#"
+[LOCKING] 2 packages to latest compatible versions
+[CHECKING] baz v0.0.1 ([ROOT]/foo/baz)
+[CHECKING] bar v0.0.1 ([ROOT]/foo/bar)
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+    p.root().move_into_the_past();
+    p.root().join("target").move_into_the_past();
+
+    p.change_file("baz/src/lib.rs", "pub fn baz() {}");
+    p.cargo("check --explain-rebuild -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["explain-rebuild"])
+        .with_stderr_data(str![[r#"
+[DIRTY] baz v0.0.1 ([ROOT]/foo/baz) lib (check): the file `baz/src/lib.rs` has changed ([TIME_DIFF_AFTER_LAST_BUILD])
+[CHECKING] baz v0.0.1 ([ROOT]/foo/baz)
+[DIRTY] bar v0.0.1 ([ROOT]/foo/bar) lib (check): the dependency baz was rebuilt
+[NOTE] caused by baz v0.0.1 ([ROOT]/foo/baz) lib (check): the file `baz/src/lib.rs` has changed ([TIME_DIFF_AFTER_LAST_BUILD])
+[CHECKING] bar v0.0.1 ([ROOT]/foo/bar)
+[DIRTY] foo v0.0.1 ([ROOT]/foo) lib (check): the dependency bar was rebuilt
+[NOTE] caused by bar v0.0.1 ([ROOT]/foo/bar) lib (check): the dependency baz was rebuilt
+[NOTE] caused by baz v0.0.1 ([ROOT]/foo/baz) lib (check): the file `baz/src/lib.rs` has changed ([TIME_DIFF_AFTER_LAST_BUILD])
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    p.change_file("baz/src/lib.rs", "pub fn baz2() {}");
+    p.cargo("check --explain-rebuild -Zunstable-options --message-format=json")
+        .masquerade_as_nightly_cargo(&["explain-rebuild"])
+        .with_stdout_data(
+            str![[r#"
+[
+  {
+    "caused_by": [],
+    "kind": "file-changed",
+    "message": "the file `baz/src/lib.rs` has changed ([TIME_DIFF_AFTER_LAST_BUILD])",
+    "mode": "check",
+    "package_id": "path+[ROOTURL]/foo/baz#0.0.1",
+    "path": "[ROOT]/foo/baz/src/lib.rs",
+    "reason": "rebuild-reason",
+    "target": "{...}"
+  },
+  "{...}",
+  "{...}",
+  {
+    "caused_by": [
+      {
+        "dependency": "baz",
+        "kind": "stale-dependency-fingerprint",
+        "message": "the dependency baz was rebuilt",
+        "mode": "check",
+        "package_id": "path+[ROOTURL]/foo/bar#0.0.1",
+        "target": "{...}"
+      },
+      {
+        "kind": "file-changed",
+        "message": "the file `baz/src/lib.rs` has changed ([TIME_DIFF_AFTER_LAST_BUILD])",
+        "mode": "check",
+        "package_id": "path+[ROOTURL]/foo/baz#0.0.1",
+        "path": "[ROOT]/foo/baz/src/lib.rs",
+        "target": "{...}"
+      }
+    ],
+    "dependency": "bar",
+    "kind": "stale-dependency-fingerprint",
+    "message": "the dependency bar was rebuilt",
+    "mode": "check",
+    "package_id": "path+[ROOTURL]/foo#0.0.1",
+    "reason": "rebuild-reason",
+    "target": "{...}"
+  },
+  "{...}",
+  "{...}"
+]
+"#]]
+            .is_json()
+            .against_jsonlines(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn checksum_freshness_ignores_mtime_only_changes() {
+    let p = project()
+        .file("src/lib.rs", "mod a;")
+        .file("src/a.rs", "")
+        .build();
+
+    p.cargo("check -Zchecksum-freshness")
+        .masquerade_as_nightly_cargo(&["checksum-freshness"])
+        .run();
+
+    // Simulate a fresh checkout, with the same contents but newer mtimes.
+    p.root().join("src/lib.rs").move_into_the_future();
+    p.root().join("src/a.rs").move_into_the_future();
+    p.cargo("check -Zchecksum-freshness -v")
+        .masquerade_as_nightly_cargo(&["checksum-freshness"])
+        .with_stderr_data(str![[r#"
+[FRESH] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    // Without the flag only the mtime is considered.
+    p.cargo("check -v")
+        .with_stderr_data(str![[r#"
+[DIRTY] foo v0.0.1 ([ROOT]/foo): the file `src/lib.rs` has changed ([TIME_DIFF_AFTER_LAST_BUILD])
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn checksum_freshness_detects_changes() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check -Zchecksum-freshness")
+        .masquerade_as_nightly_cargo(&["checksum-freshness"])
+        .run();
+
+    p.root().move_into_the_past();
+    p.root().join("target").move_into_the_past();
+    p.change_file("src/lib.rs", "pub fn foo() {}");
+    p.cargo("check -Zchecksum-freshness -v")
+        .masquerade_as_nightly_cargo(&["checksum-freshness"])
+        .with_stderr_data(str![[r#"
+[DIRTY] foo v0.0.1 ([ROOT]/foo): the file `src/lib.rs` has changed ([TIME_DIFF_AFTER_LAST_BUILD])
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    // The new contents were recorded.
+    p.root().join("src/lib.rs").move_into_the_future();
+    p.cargo("check -Zchecksum-freshness -v")
+        .masquerade_as_nightly_cargo(&["checksum-freshness"])
+        .with_stderr_data(str![[r#"
+[FRESH] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn checksum_freshness_rerun_if_changed() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::rerun-if-changed=build.rs");
+                    println!("cargo::rerun-if-changed=data.txt");
+                }
+            "#,
+        )
+        .file("data.txt", "data")
+        .build();
+
+    p.cargo("check -Zchecksum-freshness")
+        .masquerade_as_nightly_cargo(&["checksum-freshness"])
+        .run();
+
+    p.root().join("build.rs").move_into_the_future();
+    p.root().join("data.txt").move_into_the_future();
+    p.cargo("check -Zchecksum-freshness -v")
+        .masquerade_as_nightly_cargo(&["checksum-freshness"])
+        .with_stderr_data(str![[r#"
+[FRESH] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    p.change_file("data.txt", "changed");
+    p.root().join("data.txt").move_into_the_future();
+    p.cargo("check -Zchecksum-freshness -v")
+        .masquerade_as_nightly_cargo(&["checksum-freshness"])
+        .with_stderr_data(str![[r#"
+[DIRTY] foo v0.0.1 ([ROOT]/foo): the file `data.txt` has changed ([TIME_DIFF_AFTER_LAST_BUILD])
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `[ROOT]/foo/target/debug/build/foo-[HASH]/build-script-build`
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
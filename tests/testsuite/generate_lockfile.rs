@@ -284,3 +284,50 @@ fn generate_lockfile_holds_lock_and_offline() {
 "#]])
         .run();
 }
+
+#[cargo_test]
+fn message_format_json() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.1.0").rust_version("1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile --message-format json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(
+            str![[r#"
+[
+  {
+    "direct": true,
+    "kind": "added",
+    "latest": "1.1.0",
+    "latest_compatible": "1.1.0",
+    "name": "dep",
+    "package_id": "registry+https://github.com/rust-lang/crates.io-index#dep@1.1.0",
+    "reason": "lockfile-change",
+    "rust_version": "1.0",
+    "rust_version_compatible": true,
+    "source": "registry+https://github.com/rust-lang/crates.io-index",
+    "version": "1.1.0"
+  }
+]
+"#]]
+            .is_json()
+            .against_jsonlines(),
+        )
+        .run();
+}
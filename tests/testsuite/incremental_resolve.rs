@@ -0,0 +1,249 @@
+//! Tests for the `-Zincremental-resolve` feature.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, str};
+
+#[cargo_test]
+fn changed_requirement() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    Package::new("a", "1.0.1").publish();
+    Package::new("b", "1.0.1").publish();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            a = "1.0.1"
+            b = "1.0"
+        "#,
+    );
+
+    p.cargo("tree -Zincremental-resolve")
+        .masquerade_as_nightly_cargo(&["incremental-resolve"])
+        .with_stdout_data(str![[r#"
+foo v0.1.0 ([ROOT]/foo)
+├── a v1.0.1
+└── b v1.0.0
+
+"#]])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[UPDATING] a v1.0.0 -> v1.0.1
+[DOWNLOADING] crates ...
+[DOWNLOADED] b v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] a v1.0.1 (registry `dummy-registry`)
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn added_dependency() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    Package::new("a", "1.0.1").publish();
+    Package::new("b", "1.0.1").publish();
+    Package::new("c", "1.0.0").dep("a", "1.0").publish();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            a = "1.0"
+            b = "1.0"
+            c = "1.0"
+        "#,
+    );
+
+    p.cargo("tree -Zincremental-resolve")
+        .masquerade_as_nightly_cargo(&["incremental-resolve"])
+        .with_stdout_data(str![[r#"
+foo v0.1.0 ([ROOT]/foo)
+├── a v1.0.0
+├── b v1.0.0
+└── c v1.0.0
+    └── a v1.0.0
+
+"#]])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[ADDING] c v1.0.0
+[DOWNLOADING] crates ...
+[DOWNLOADED] c v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] b v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] a v1.0.0 (registry `dummy-registry`)
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn added_dependency_needing_locked_update() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    Package::new("a", "1.0.1").publish();
+    Package::new("b", "1.0.1").publish();
+    Package::new("c", "1.0.0").dep("b", "1.0.1").publish();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            a = "1.0"
+            b = "1.0"
+            c = "1.0"
+        "#,
+    );
+
+    // `b` is locked to a version `c` doesn't allow, so it is resolved again
+    // like without `-Zincremental-resolve`.
+    p.cargo("tree -Zincremental-resolve")
+        .masquerade_as_nightly_cargo(&["incremental-resolve"])
+        .with_stdout_data(str![[r#"
+foo v0.1.0 ([ROOT]/foo)
+├── a v1.0.0
+├── b v1.0.1
+└── c v1.0.0
+    └── b v1.0.1
+
+"#]])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[UPDATING] b v1.0.0 -> v1.0.1
+[ADDING] c v1.0.0
+[DOWNLOADING] crates ...
+[DOWNLOADED] c v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] b v1.0.1 (registry `dummy-registry`)
+[DOWNLOADED] a v1.0.0 (registry `dummy-registry`)
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn removed_dependency() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    Package::new("a", "1.0.1").publish();
+    Package::new("b", "1.0.1").publish();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            a = "1.0"
+        "#,
+    );
+
+    p.cargo("tree -Zincremental-resolve")
+        .masquerade_as_nightly_cargo(&["incremental-resolve"])
+        .with_stdout_data(str![[r#"
+foo v0.1.0 ([ROOT]/foo)
+└── a v1.0.0
+
+"#]])
+        .with_stderr_data(str![[r#"
+[DOWNLOADING] crates ...
+[DOWNLOADED] a v1.0.0 (registry `dummy-registry`)
+
+"#]])
+        .run();
+}
@@ -3,6 +3,7 @@
 #![allow(clippy::print_stdout)]
 
 mod advanced_env;
+mod advisory_db;
 mod alt_registry;
 mod artifact_dep;
 mod artifact_dir;
@@ -11,6 +12,7 @@ mod bad_manifest_path;
 mod bench;
 mod binary_name;
 mod build;
+mod build_cache;
 mod build_plan;
 mod build_script;
 mod build_script_env;
@@ -38,9 +40,11 @@ mod cargo_info;
 mod cargo_init;
 mod cargo_install;
 mod cargo_locate_project;
+mod cargo_lockfile;
 mod cargo_login;
 mod cargo_logout;
 mod cargo_metadata;
+mod cargo_mirror;
 mod cargo_new;
 mod cargo_owner;
 mod cargo_package;
@@ -102,6 +106,7 @@ mod glob_targets;
 mod global_cache_tracker;
 mod help;
 mod https;
+mod incremental_resolve;
 mod inheritable_workspace_fields;
 mod install;
 mod install_upgrade;
@@ -118,11 +123,14 @@ mod logout;
 mod lto;
 mod member_discovery;
 mod member_errors;
+mod memory_limit;
 mod message_format;
 mod messages;
 mod metabuild;
 mod metadata;
+mod min_publish_age;
 mod minimal_versions;
+mod minimize_duplicates;
 mod multitarget;
 mod net_config;
 mod new;
@@ -166,6 +174,7 @@ mod rustflags;
 mod rustup;
 mod script;
 mod search;
+mod shared_deps;
 mod shell_quoting;
 mod source_replacement;
 mod ssh;
@@ -175,8 +184,10 @@ mod timings;
 mod tool_paths;
 mod tree;
 mod tree_graph_features;
+mod tree_why_version;
 mod unit_graph;
 mod update;
+mod update_policy;
 mod vendor;
 mod verify_project;
 mod version;
@@ -0,0 +1,218 @@
+//! Tests for the `-Zmemory-limit` feature.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_manifest, project, str};
+
+#[cargo_test]
+fn unit_memory_holds_back_jobs() {
+    // The build scripts of `a` and `b` fail if they run at the same time.
+    let build_rs = r#"
+        use std::path::Path;
+        use std::time::Duration;
+
+        fn main() {
+            let lock = Path::new(env!("CARGO_MANIFEST_DIR")).join("../lock");
+            std::fs::create_dir(&lock).expect("another build script is running");
+            std::thread::sleep(Duration::from_millis(500));
+            std::fs::remove_dir(&lock).unwrap();
+        }
+    "#;
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = { path = "a" }
+                b = { path = "b" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/build.rs", build_rs)
+        .file("a/src/lib.rs", "")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/build.rs", build_rs)
+        .file("b/src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                memory-limit = "1.5GiB"
+                unit-memory = { a = "1GiB", b = "1GiB" }
+            "#,
+        )
+        .build();
+
+    p.cargo("check -j2 -Zmemory-limit")
+        .masquerade_as_nightly_cargo(&["memory-limit"])
+        .with_stderr_data(
+            str![[r#"
+[LOCKING] 2 packages to latest compatible versions
+[COMPILING] a v0.1.0 ([ROOT]/foo/a)
+[COMPILING] b v0.1.0 ([ROOT]/foo/b)
+[CHECKING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn single_unit_over_the_limit() {
+    let build_rs = r#"
+        use std::path::Path;
+        use std::time::Duration;
+
+        fn main() {
+            let lock = Path::new(env!("CARGO_MANIFEST_DIR")).join("../lock");
+            std::fs::create_dir(&lock).expect("another build script is running");
+            std::thread::sleep(Duration::from_millis(500));
+            std::fs::remove_dir(&lock).unwrap();
+        }
+    "#;
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = { path = "a" }
+                b = { path = "b" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/build.rs", build_rs)
+        .file("a/src/lib.rs", "")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/build.rs", build_rs)
+        .file("b/src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                memory-limit = "1GiB"
+                unit-memory = { a = "2GiB", b = "2GiB" }
+            "#,
+        )
+        .build();
+
+    // Units needing more than the limit still run, one at a time.
+    p.cargo("check -j2 -Zmemory-limit")
+        .masquerade_as_nightly_cargo(&["memory-limit"])
+        .run();
+}
+
+#[cargo_test]
+fn invalid_config() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                memory-limit = "lots"
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Zmemory-limit")
+        .masquerade_as_nightly_cargo(&["memory-limit"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse `build.memory-limit`
+
+Caused by:
+  invalid size `lots`, expected a number with an optional B, kB, MB, GB, kiB, MiB, or GiB suffix
+
+"#]])
+        .run();
+
+    // The configuration is ignored without `-Zmemory-limit`.
+    p.cargo("check")
+        .with_stderr_data(str![[r#"
+[WARNING] ignoring `build.memory-limit`, `build.link-jobs` and `build.unit-memory` config without `-Zmemory-limit`
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [build]
+            link-jobs = 0
+        "#,
+    );
+    p.cargo("check -Zmemory-limit")
+        .masquerade_as_nightly_cargo(&["memory-limit"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `build.link-jobs` may not be 0
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn link_jobs() {
+    let p = project()
+        .file("src/bin/a.rs", "fn main() {}")
+        .file("src/bin/b.rs", "fn main() {}")
+        .file("src/bin/c.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                link-jobs = 1
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Zmemory-limit")
+        .masquerade_as_nightly_cargo(&["memory-limit"])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cfg(target_os = "linux")]
+#[cargo_test]
+fn peak_memory_is_recorded() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            "fn main() { std::thread::sleep(std::time::Duration::from_secs(1)); }",
+        )
+        .build();
+
+    p.cargo("build -Zmemory-limit -Zunstable-options --timings=json-file")
+        .masquerade_as_nightly_cargo(&["memory-limit", "timings"])
+        .run();
+
+    let data = p.read_file("target/cargo-timings/cargo-timing.json");
+    let data: serde_json::Value = serde_json::from_str(&data).unwrap();
+    let run_build_script = data["units"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|unit| unit["mode"] == "run-custom-build")
+        .unwrap();
+    assert!(run_build_script["peak_memory"].as_u64().unwrap() > 0);
+}
@@ -489,10 +489,16 @@ fn metabuild_build_plan() {
   "invocations": [
     {
       "args": "{...}",
+      "build_script_uses": [],
       "compile_mode": "build",
       "cwd": "[ROOT]/foo",
+      "dep_info": "[ROOT]/foo/target/debug/deps/mb-[HASH].d",
       "deps": [],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [
+        "[ROOT]/foo/mb/src/lib.rs"
+      ],
       "kind": null,
       "links": {},
       "outputs": [
@@ -504,14 +510,21 @@ fn metabuild_build_plan() {
       "program": "rustc",
       "target_kind": [
         "lib"
-      ]
+      ],
+      "target_name": "mb"
     },
     {
       "args": "{...}",
+      "build_script_uses": [],
       "compile_mode": "build",
       "cwd": "[ROOT]/foo",
+      "dep_info": "[ROOT]/foo/target/debug/deps/mb_other-[HASH].d",
       "deps": [],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [
+        "[ROOT]/foo/mb-other/src/lib.rs"
+      ],
       "kind": null,
       "links": {},
       "outputs": [
@@ -523,17 +536,22 @@ fn metabuild_build_plan() {
       "program": "rustc",
       "target_kind": [
         "lib"
-      ]
+      ],
+      "target_name": "mb_other"
     },
     {
       "args": "{...}",
+      "build_script_uses": [],
       "compile_mode": "build",
       "cwd": "[ROOT]/foo",
+      "dep_info": "[ROOT]/foo/target/debug/build/foo-[HASH]/metabuild_foo-[HASH].d",
       "deps": [
         0,
         1
       ],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [],
       "kind": null,
       "links": "{...}",
       "outputs": "{...}",
@@ -542,16 +560,35 @@ fn metabuild_build_plan() {
       "program": "rustc",
       "target_kind": [
         "custom-build"
-      ]
+      ],
+      "target_name": "metabuild-foo"
     },
     {
       "args": "{...}",
+      "build_script": {
+        "links": null,
+        "out_dir": "[ROOT]/foo/target/debug/build/foo-[HASH]/out",
+        "output_file": "[ROOT]/foo/target/debug/build/foo-[HASH]/output",
+        "targets": [
+          {
+            "kind": "lib",
+            "name": "foo"
+          },
+          {
+            "kind": "build-script",
+            "name": "metabuild-foo"
+          }
+        ]
+      },
+      "build_script_uses": [],
       "compile_mode": "run-custom-build",
       "cwd": "[ROOT]/foo",
       "deps": [
         2
       ],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [],
       "kind": null,
       "links": {},
       "outputs": [],
@@ -560,16 +597,32 @@ fn metabuild_build_plan() {
       "program": "[ROOT]/foo/target/debug/build/foo-[HASH]/metabuild-foo",
       "target_kind": [
         "custom-build"
-      ]
+      ],
+      "target_name": "metabuild-foo"
     },
     {
       "args": "{...}",
+      "build_script_uses": [
+        {
+          "flags": true,
+          "invocation": 3,
+          "link_args": true,
+          "link_lib": true,
+          "link_search": true,
+          "metadata": false
+        }
+      ],
       "compile_mode": "build",
       "cwd": "[ROOT]/foo",
+      "dep_info": "[ROOT]/foo/target/debug/deps/foo-[HASH].d",
       "deps": [
         3
       ],
       "env": "{...}",
+      "input_env": [],
+      "inputs": [
+        "[ROOT]/foo/src/lib.rs"
+      ],
       "kind": null,
       "links": "{...}",
       "outputs": [
@@ -581,9 +634,11 @@ fn metabuild_build_plan() {
       "program": "rustc",
       "target_kind": [
         "lib"
-      ]
+      ],
+      "target_name": "foo"
     }
-  ]
+  ],
+  "version": 1
 }
 "#]]
             .is_json(),
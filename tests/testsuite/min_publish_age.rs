@@ -0,0 +1,288 @@
+//! Tests for the `-Zmin-publish-age` feature.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, str};
+
+#[cargo_test]
+fn recent_versions_are_avoided() {
+    Package::new("dep", "1.0.0")
+        .pubtime("2000-01-01T00:00:00Z")
+        .publish();
+    Package::new("dep", "1.1.0")
+        .pubtime("2000-02-01T00:00:00Z")
+        .publish();
+    // Published "now", as far as the test is concerned.
+    Package::new("dep", "1.2.0")
+        .pubtime("2999-01-01T00:00:00Z")
+        .publish();
+    // Registries may not record the publish time.
+    Package::new("dep", "0.9.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                min-publish-age = "7 days"
+            "#,
+        )
+        .build();
+
+    p.cargo("generate-lockfile -Zmin-publish-age")
+        .masquerade_as_nightly_cargo(&["min-publish-age"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[ADDING] dep v1.1.0 (available: v1.2.0)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn recent_versions_are_a_fallback() {
+    Package::new("dep", "1.0.0")
+        .pubtime("2999-01-01T00:00:00Z")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                min-publish-age = "7 days"
+            "#,
+        )
+        .build();
+
+    p.cargo("generate-lockfile -Zmin-publish-age")
+        .masquerade_as_nightly_cargo(&["min-publish-age"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn recent_versions_denied() {
+    Package::new("dep", "1.0.0")
+        .pubtime("2999-01-01T00:00:00Z")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                min-publish-age = "7 days"
+                recent-versions = "deny"
+            "#,
+        )
+        .build();
+
+    p.cargo("generate-lockfile -Zmin-publish-age")
+        .masquerade_as_nightly_cargo(&["min-publish-age"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[ERROR] failed to select a version for the requirement `dep = "^1.0"`
+candidate versions found which didn't match: 1.0.0
+location searched: `dummy-registry` index (which is replacing registry `crates-io`)
+required by package `foo v0.0.1 ([ROOT]/foo)`
+versions published less than `resolver.min-publish-age` ago are denied: 1.0.0
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn recent_versions_without_min_publish_age() {
+    Package::new("dep", "1.0.0")
+        .pubtime("2999-01-01T00:00:00Z")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                recent-versions = "deny"
+            "#,
+        )
+        .build();
+
+    p.cargo("generate-lockfile -Zmin-publish-age")
+        .masquerade_as_nightly_cargo(&["min-publish-age"])
+        .with_stderr_data(str![[r#"
+[WARNING] `resolver.recent-versions` has no effect without `resolver.min-publish-age`
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn locked_recent_version_is_kept() {
+    Package::new("dep", "1.0.0")
+        .pubtime("2000-01-01T00:00:00Z")
+        .publish();
+    Package::new("dep", "1.1.0")
+        .pubtime("2999-01-01T00:00:00Z")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    assert!(p.read_lockfile().contains("1.1.0"));
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [resolver]
+            min-publish-age = "7 days"
+            recent-versions = "deny"
+        "#,
+    );
+    p.cargo("check -Zmin-publish-age")
+        .masquerade_as_nightly_cargo(&["min-publish-age"])
+        .with_stderr_data(str![[r#"
+[DOWNLOADING] crates ...
+[DOWNLOADED] dep v1.1.0 (registry `dummy-registry`)
+[CHECKING] dep v1.1.0
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+    assert!(p.read_lockfile().contains("1.1.0"));
+
+    // Updating moves away from the recent version.
+    p.cargo("update -Zmin-publish-age")
+        .masquerade_as_nightly_cargo(&["min-publish-age"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[DOWNGRADING] dep v1.1.0 -> v1.0.0 (available: v1.1.0)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn invalid_config() {
+    Package::new("dep", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                min-publish-age = "a week"
+            "#,
+        )
+        .build();
+
+    p.cargo("generate-lockfile -Zmin-publish-age")
+        .masquerade_as_nightly_cargo(&["min-publish-age"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse `resolver.min-publish-age`
+
+Caused by:
+  expected a value of the form "N seconds/minutes/days/weeks/months", got: "a week"
+
+"#]])
+        .run();
+
+    // The configuration is ignored without `-Zmin-publish-age`.
+    p.cargo("generate-lockfile")
+        .with_stderr_data(str![[r#"
+[WARNING] ignoring `resolver.min-publish-age` and `resolver.recent-versions` config without `-Zmin-publish-age`
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+
+"#]])
+        .run();
+}
@@ -0,0 +1,164 @@
+//! Tests for the `-Zminimize-duplicates` feature.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, str};
+
+#[cargo_test]
+fn duplicates_without_flag() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.1.0").publish();
+    Package::new("dep", "2.0.0").publish();
+    Package::new("bar", "1.0.0")
+        .dep("dep", ">=1.0, <3.0")
+        .publish();
+    Package::new("baz", "1.0.0").dep("dep", "1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    p.cargo("tree --duplicates")
+        .with_stdout_data(str![[r#"
+dep v1.1.0
+└── baz v1.0.0
+    └── foo v0.1.0 ([ROOT]/foo)
+
+dep v2.0.0
+└── bar v1.0.0
+    └── foo v0.1.0 ([ROOT]/foo)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn unify_across_major_versions() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.1.0").publish();
+    Package::new("dep", "2.0.0").publish();
+    Package::new("bar", "1.0.0")
+        .dep("dep", ">=1.0, <3.0")
+        .publish();
+    Package::new("baz", "1.0.0").dep("dep", "1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Zminimize-duplicates")
+        .masquerade_as_nightly_cargo(&["minimize-duplicates"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest compatible versions
+
+"#]])
+        .run();
+    p.cargo("tree")
+        .with_stdout_data(str![[r#"
+foo v0.1.0 ([ROOT]/foo)
+├── bar v1.0.0
+│   └── dep v1.1.0
+└── baz v1.0.0
+    └── dep v1.1.0
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn unify_with_direct_dependency() {
+    Package::new("dep", "0.1.0").publish();
+    Package::new("dep", "0.2.0").publish();
+    Package::new("bar", "1.0.0")
+        .dep("dep", ">=0.1, <0.3")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                dep = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Zminimize-duplicates")
+        .masquerade_as_nightly_cargo(&["minimize-duplicates"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[ADDING] dep v0.1.0 (available: v0.2.0)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn unavoidable_duplicates_are_kept() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "2.0.0").publish();
+    Package::new("bar", "1.0.0").dep("dep", "2.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Zminimize-duplicates")
+        .masquerade_as_nightly_cargo(&["minimize-duplicates"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest compatible versions
+[ADDING] dep v1.0.0 (available: v2.0.0)
+
+"#]])
+        .run();
+}
@@ -2701,6 +2701,151 @@ fn reproducible_output() {
     }
 }
 
+#[cargo_test]
+fn source_date_epoch() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+            "#,
+        )
+        .file("src/main.rs", r#"fn main() { println!("hello"); }"#)
+        .build();
+
+    let mtimes = || {
+        let f = File::open(&p.root().join("target/package/foo-0.0.1.crate")).unwrap();
+        let mut archive = Archive::new(GzDecoder::new(f));
+        archive
+            .entries()
+            .unwrap()
+            .map(|ent| ent.unwrap().header().mtime().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // Only honored with `-Zunstable-options`.
+    p.cargo("package --no-verify")
+        .env("SOURCE_DATE_EPOCH", "1700000000")
+        .run();
+    assert!(mtimes().iter().all(|&mtime| mtime != 1700000000));
+
+    p.cargo("package --no-verify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .env("SOURCE_DATE_EPOCH", "1700000000")
+        .run();
+    assert!(mtimes().iter().all(|&mtime| mtime == 1700000000));
+
+    p.cargo("package --no-verify -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .env("SOURCE_DATE_EPOCH", "yesterday")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[WARNING] manifest has no documentation, homepage or repository.
+See https://doc.rust-lang.org/cargo/reference/manifest.html#package-metadata for more info.
+[PACKAGING] foo v0.0.1 ([ROOT]/foo)
+[ERROR] failed to prepare local package for uploading
+
+Caused by:
+  invalid `SOURCE_DATE_EPOCH` environment variable `yesterday`, expected a number of seconds since the Unix epoch
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn verify_reproducible() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", r#"fn main() { println!("hello"); }"#)
+        .file("script.sh", "#!/bin/sh")
+        .build();
+    Package::new("bar", "1.0.0").publish();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let script = p.root().join("script.sh");
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o775)).unwrap();
+    }
+
+    p.cargo("package --no-verify --verify-reproducible")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--verify-reproducible` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#cargo-package---verify-reproducible for more information about the `--verify-reproducible` flag.
+
+"#]])
+        .run();
+
+    p.cargo("package --no-verify --verify-reproducible -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .env("SOURCE_DATE_EPOCH", "1700000000")
+        .with_stderr_data(str![[r#"
+[WARNING] manifest has no documentation, homepage or repository.
+See https://doc.rust-lang.org/cargo/reference/manifest.html#package-metadata for more info.
+[PACKAGING] foo v0.0.1 ([ROOT]/foo)
+[UPDATING] `dummy-registry` index
+[PACKAGED] 5 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[REPACKAGING] foo v0.0.1 ([ROOT]/foo)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn verify_reproducible_tracked_target_file() {
+    // Files tracked in `target/` are packaged, but they are skipped when
+    // listing the files of a copy which isn't a git checkout.
+    let (p, _repo) = git::new_repo("foo", |p| {
+        p.file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+            "#,
+        )
+        .file("src/main.rs", r#"fn main() { println!("hello"); }"#)
+        .file("target/data.txt", "data")
+    });
+
+    p.cargo("package --no-verify --verify-reproducible -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[WARNING] manifest has no documentation, homepage or repository.
+See https://doc.rust-lang.org/cargo/reference/manifest.html#package-metadata for more info.
+[PACKAGING] foo v0.0.1 ([ROOT]/foo)
+[PACKAGED] 6 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[REPACKAGING] foo v0.0.1 ([ROOT]/foo)
+[ERROR] `foo v0.0.1 ([ROOT]/foo)` is not reproducible, packaging it again produced a different `foo-0.0.1.crate`:
+  foo-0.0.1/target/data.txt: missing the second time
+
+"#]])
+        .run();
+}
+
 #[cargo_test]
 fn package_with_resolver_and_metadata() {
     let p = project()
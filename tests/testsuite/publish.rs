@@ -3948,3 +3948,382 @@ fn one_unpublishable_package() {
 "#]])
         .run();
 }
+
+#[cargo_test]
+fn semver_check_requires_unstable_options() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+                documentation = "foo"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("publish --semver-check")
+        .replace_crates_io(registry.index_url())
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--semver-check` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#cargo-publish---semver-check for more information about the `--semver-check` flag.
+
+"#]])
+        .run();
+}
+
+#[cargo_test(nightly, reason = "rustdoc JSON is unstable")]
+fn semver_check_breaking_change() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    Package::new("foo", "0.1.0")
+        .file(
+            "src/lib.rs",
+            r#"
+                pub fn foo(_: u32) {}
+                pub fn bar() {}
+                pub enum E { A }
+                pub trait T { fn t(&self) {} }
+            "#,
+        )
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+                documentation = "foo"
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                pub fn foo(_: u64) {}
+                pub enum E { A, B }
+                pub trait T { fn t(&self); }
+            "#,
+        )
+        .build();
+
+    p.cargo("publish --no-verify -Zunstable-options --semver-check")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .replace_crates_io(registry.index_url())
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[PACKAGING] foo v0.1.1 ([ROOT]/foo)
+[PACKAGED] 3 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPDATING] crates.io index
+[DOWNLOADING] crates ...
+[DOWNLOADED] foo v0.1.0
+[CHECKING] foo v0.1.1 ([ROOT]/foo) for semver compatibility with v0.1.0
+[DOCUMENTING] foo v0.1.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[WARNING] no edition set: defaulting to the 2015 edition while the latest is 2021
+[UPDATING] crates.io index
+[DOCUMENTING] foo v0.1.0
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[ERROR] `foo v0.1.1 ([ROOT]/foo)` is not semver compatible with v0.1.0:
+  removed the default of function `foo::T::t`
+  removed function `foo::bar`
+  changed the signature of function `foo::foo`
+  added variant `foo::E::B` to exhaustive enum `foo::E`
+bump the version to 0.2.0 to publish these changes
+
+"#]])
+        .run();
+}
+
+#[cargo_test(nightly, reason = "rustdoc JSON is unstable")]
+fn semver_check_compatible_change() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    Package::new("foo", "0.1.0")
+        .file("src/lib.rs", "pub mod m { pub fn foo() {} }")
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+                documentation = "foo"
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                mod imp { pub fn foo() {} pub fn bar() {} }
+                pub mod m { pub use imp::*; }
+            "#,
+        )
+        .build();
+
+    p.cargo("publish --dry-run --no-verify -Zunstable-options --semver-check")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .replace_crates_io(registry.index_url())
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[PACKAGING] foo v0.1.1 ([ROOT]/foo)
+[PACKAGED] 3 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPDATING] crates.io index
+[DOWNLOADING] crates ...
+[DOWNLOADED] foo v0.1.0
+[CHECKING] foo v0.1.1 ([ROOT]/foo) for semver compatibility with v0.1.0
+[DOCUMENTING] foo v0.1.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[WARNING] no edition set: defaulting to the 2015 edition while the latest is 2021
+[UPDATING] crates.io index
+[DOCUMENTING] foo v0.1.0
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[UPLOADING] foo v0.1.1 ([ROOT]/foo)
+[WARNING] aborting upload due to dry run
+
+"#]])
+        .run();
+}
+
+#[cargo_test(nightly, reason = "rustdoc JSON is unstable")]
+fn semver_check_features() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    Package::new("foo", "0.1.0")
+        .feature("extra", &[])
+        .file(
+            "src/lib.rs",
+            r#"
+                #[cfg(feature = "extra")]
+                pub fn extra() {}
+            "#,
+        )
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+                documentation = "foo"
+
+                [features]
+                extra = []
+                new = []
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                #[cfg(feature = "new")]
+                pub fn new() {}
+            "#,
+        )
+        .build();
+
+    // The previous version doesn't have the `new` feature.
+    p.cargo("publish --dry-run --no-verify -Zunstable-options --semver-check --features extra,new")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .replace_crates_io(registry.index_url())
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[PACKAGING] foo v0.1.1 ([ROOT]/foo)
+[PACKAGED] 3 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPDATING] crates.io index
+[DOWNLOADING] crates ...
+[DOWNLOADED] foo v0.1.0
+[CHECKING] foo v0.1.1 ([ROOT]/foo) for semver compatibility with v0.1.0
+[DOCUMENTING] foo v0.1.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[WARNING] no edition set: defaulting to the 2015 edition while the latest is 2021
+[UPDATING] crates.io index
+[DOCUMENTING] foo v0.1.0
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[ERROR] `foo v0.1.1 ([ROOT]/foo)` is not semver compatible with v0.1.0:
+  removed function `foo::extra`
+bump the version to 0.2.0 to publish these changes
+
+"#]])
+        .run();
+}
+
+#[cargo_test(nightly, reason = "rustdoc JSON is unstable")]
+fn semver_check_non_exhaustive_attribute() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    Package::new("foo", "0.1.0")
+        .file(
+            "src/lib.rs",
+            r#"
+                #[doc(alias = "non_exhaustive")]
+                pub enum E { A }
+            "#,
+        )
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+                documentation = "foo"
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                #[doc(alias = "non_exhaustive")]
+                pub enum E { A, B }
+            "#,
+        )
+        .build();
+
+    p.cargo("publish --dry-run --no-verify -Zunstable-options --semver-check")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .replace_crates_io(registry.index_url())
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[PACKAGING] foo v0.1.1 ([ROOT]/foo)
+[PACKAGED] 3 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPDATING] crates.io index
+[DOWNLOADING] crates ...
+[DOWNLOADED] foo v0.1.0
+[CHECKING] foo v0.1.1 ([ROOT]/foo) for semver compatibility with v0.1.0
+[DOCUMENTING] foo v0.1.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[WARNING] no edition set: defaulting to the 2015 edition while the latest is 2021
+[UPDATING] crates.io index
+[DOCUMENTING] foo v0.1.0
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[ERROR] `foo v0.1.1 ([ROOT]/foo)` is not semver compatible with v0.1.0:
+  added variant `foo::E::B` to exhaustive enum `foo::E`
+bump the version to 0.2.0 to publish these changes
+
+"#]])
+        .run();
+}
+
+#[cargo_test(nightly, reason = "rustdoc JSON is unstable")]
+fn semver_check_struct_fields() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    Package::new("foo", "0.1.0")
+        .file(
+            "src/lib.rs",
+            r#"
+                pub struct S { pub a: u8 }
+                pub struct P { pub a: u8, b: u8 }
+                pub struct T(pub u8, u8);
+                #[non_exhaustive]
+                pub struct N { pub a: u8 }
+            "#,
+        )
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+                documentation = "foo"
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                pub struct S { pub a: u8, pub c: u8 }
+                pub struct P { pub a: u8, b: u8, pub c: u8 }
+                pub struct T(pub u8, u8, pub u8);
+                #[non_exhaustive]
+                pub struct N { pub a: u8, pub c: u8 }
+            "#,
+        )
+        .build();
+
+    // Only `S` can be built with a struct literal outside of `foo`.
+    p.cargo("publish --dry-run --no-verify -Zunstable-options --semver-check")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .replace_crates_io(registry.index_url())
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[PACKAGING] foo v0.1.1 ([ROOT]/foo)
+[PACKAGED] 3 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPDATING] crates.io index
+[DOWNLOADING] crates ...
+[DOWNLOADED] foo v0.1.0
+[CHECKING] foo v0.1.1 ([ROOT]/foo) for semver compatibility with v0.1.0
+[DOCUMENTING] foo v0.1.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[WARNING] no edition set: defaulting to the 2015 edition while the latest is 2021
+[UPDATING] crates.io index
+[DOCUMENTING] foo v0.1.0
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[ERROR] `foo v0.1.1 ([ROOT]/foo)` is not semver compatible with v0.1.0:
+  added field `foo::S::c` to exhaustive struct `foo::S`
+bump the version to 0.2.0 to publish these changes
+
+"#]])
+        .run();
+}
+
+#[cargo_test(nightly, reason = "rustdoc JSON is unstable")]
+fn semver_check_breaking_version() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+    Package::new("foo", "0.1.0")
+        .file("src/lib.rs", "pub fn foo() {}")
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.2.0"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+                documentation = "foo"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("publish --dry-run --no-verify -Zunstable-options --semver-check")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .replace_crates_io(registry.index_url())
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[PACKAGING] foo v0.2.0 ([ROOT]/foo)
+[PACKAGED] 3 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPDATING] crates.io index
+[UPLOADING] foo v0.2.0 ([ROOT]/foo)
+[WARNING] aborting upload due to dry run
+
+"#]])
+        .run();
+}
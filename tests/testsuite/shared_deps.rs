@@ -0,0 +1,333 @@
+//! Tests for the `-Zshared-deps` feature.
+
+use std::path::{Path, PathBuf};
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{paths, project, str};
+use filetime::FileTime;
+
+fn glob(pattern: &Path) -> Vec<PathBuf> {
+    glob::glob(pattern.to_str().unwrap())
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+}
+
+#[cargo_test]
+fn shared_between_workspaces() {
+    Package::new("baz", "1.0.0")
+        .file("src/lib.rs", "pub fn baz() { println!(\"baz\"); }")
+        .publish();
+    Package::new("bar", "1.0.0")
+        .dep("baz", "1.0")
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo::rustc-env=BAR_MESSAGE=bar"); }"#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                extern crate baz;
+                pub fn bar() { println!(env!("BAR_MESSAGE")); baz::baz(); }
+            "#,
+        )
+        .publish();
+    let shared = paths::cargo_home().join("shared-deps");
+
+    let p1 = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .build();
+    p1.cargo("build -Zshared-deps")
+        .masquerade_as_nightly_cargo(&["shared-deps"])
+        .run();
+    assert_eq!(glob(&shared.join("*/*/deps/libbar-*.rlib")).len(), 1);
+    assert_eq!(glob(&shared.join("*/*/deps/libbaz-*.rlib")).len(), 1);
+    assert!(glob(&p1.root().join("target/debug/deps/libba*")).is_empty());
+    assert!(glob(&p1.root().join("target/debug/build/bar-*")).is_empty());
+    assert_eq!(glob(&p1.root().join("target/debug/deps/foo-*.d")).len(), 1);
+
+    // Another workspace reuses the dependencies.
+    let p2 = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .build();
+    p2.cargo("run -v -Zshared-deps")
+        .masquerade_as_nightly_cargo(&["shared-deps"])
+        .with_stdout_data(str![[r#"
+bar
+baz
+
+"#]])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[FRESH] baz v1.0.0
+[FRESH] bar v1.0.0
+[COMPILING] foo v0.1.0 ([ROOT]/b)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] `target/debug/foo[EXE]`
+
+"#]]
+            .unordered(),
+        )
+        .run();
+
+    // Different flags use a different directory.
+    p2.cargo("build -v -Zshared-deps")
+        .masquerade_as_nightly_cargo(&["shared-deps"])
+        .env("RUSTFLAGS", "-Cdebug-assertions")
+        .with_stderr_data(
+            str![[r#"
+[COMPILING] baz v1.0.0
+[RUNNING] `rustc --crate-name baz [..]`
+[COMPILING] bar v1.0.0
+[RUNNING] `rustc --crate-name build_script_build [..]`
+[RUNNING] `[ROOT]/home/.cargo/shared-deps/[..]/build/bar-[HASH]/build-script-build`
+[RUNNING] `rustc --crate-name bar [..]`
+[DIRTY] foo v0.1.0 ([ROOT]/b): the rustflags changed
+[COMPILING] foo v0.1.0 ([ROOT]/b)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+    assert_eq!(glob(&shared.join("*/*/deps/libbar-*.rlib")).len(), 2);
+
+    // Without the flag, the target directory is used.
+    p1.cargo("build -v")
+        .with_stderr_data(
+            str![[r#"
+[COMPILING] baz v1.0.0
+[RUNNING] `rustc --crate-name baz [..]`
+[COMPILING] bar v1.0.0
+[RUNNING] `rustc --crate-name build_script_build [..]`
+[RUNNING] `[ROOT]/a/target/debug/build/bar-[HASH]/build-script-build`
+[RUNNING] `rustc --crate-name bar [..]`
+[DIRTY] foo v0.1.0 ([ROOT]/a): dependency info changed
+[COMPILING] foo v0.1.0 ([ROOT]/a)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn path_dependencies_are_not_shared() {
+    Package::new("baz", "1.0.0")
+        .file("src/lib.rs", "pub fn baz() { println!(\"baz\"); }")
+        .publish();
+    Package::new("bar", "1.0.0")
+        .dep("baz", "1.0")
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo::rustc-env=BAR_MESSAGE=bar"); }"#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                extern crate baz;
+                pub fn bar() { println!(env!("BAR_MESSAGE")); baz::baz(); }
+            "#,
+        )
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                local = { path = "local" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "local/Cargo.toml",
+            r#"
+                [package]
+                name = "local"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                baz = "1.0"
+            "#,
+        )
+        .file("local/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zshared-deps")
+        .masquerade_as_nightly_cargo(&["shared-deps"])
+        .run();
+    let shared = paths::cargo_home().join("shared-deps");
+    assert_eq!(glob(&shared.join("*/*/deps/libbar-*.rmeta")).len(), 1);
+    assert!(glob(&shared.join("*/*/deps/liblocal-*")).is_empty());
+    assert_eq!(
+        glob(&p.root().join("target/debug/deps/liblocal-*.rmeta")).len(),
+        1
+    );
+}
+
+#[cargo_test]
+fn shared_deps_dir_config() {
+    Package::new("baz", "1.0.0")
+        .file("src/lib.rs", "pub fn baz() { println!(\"baz\"); }")
+        .publish();
+    Package::new("bar", "1.0.0")
+        .dep("baz", "1.0")
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo::rustc-env=BAR_MESSAGE=bar"); }"#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                extern crate baz;
+                pub fn bar() { println!(env!("BAR_MESSAGE")); baz::baz(); }
+            "#,
+        )
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                shared-deps-dir = "../shared"
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Zshared-deps")
+        .masquerade_as_nightly_cargo(&["shared-deps"])
+        .run();
+    assert_eq!(
+        glob(&paths::root().join("shared/*/*/deps/libbar-*.rlib")).len(),
+        1
+    );
+    assert!(!paths::cargo_home().join("shared-deps").exists());
+}
+
+#[cargo_test]
+fn gc_removes_unused_units() {
+    Package::new("baz", "1.0.0")
+        .file("src/lib.rs", "pub fn baz() { println!(\"baz\"); }")
+        .publish();
+    Package::new("bar", "1.0.0")
+        .dep("baz", "1.0")
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo::rustc-env=BAR_MESSAGE=bar"); }"#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                extern crate baz;
+                pub fn bar() { println!(env!("BAR_MESSAGE")); baz::baz(); }
+            "#,
+        )
+        .publish();
+    let shared = paths::cargo_home().join("shared-deps");
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .build();
+    p.cargo("build -Zshared-deps")
+        .masquerade_as_nightly_cargo(&["shared-deps"])
+        .run();
+
+    // Recently used units are kept.
+    p.cargo("clean gc -Zgc --max-shared-deps-age=1day")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .run();
+    assert_eq!(glob(&shared.join("*/*/deps/libbar-*.rlib")).len(), 1);
+
+    let two_days_ago = FileTime::from_unix_time(FileTime::now().unix_seconds() - 2 * 86400, 0);
+    for fingerprint in glob(&shared.join("*/*/.fingerprint/*")) {
+        filetime::set_file_mtime(&fingerprint, two_days_ago).unwrap();
+    }
+    p.cargo("clean gc -Zgc --max-shared-deps-age=1day")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .with_stderr_data(str![[r#"
+[REMOVED] [FILE_NUM] files, [FILE_SIZE]B total
+
+"#]])
+        .run();
+    assert!(glob(&shared.join("*/*/deps/*")).is_empty());
+    assert!(glob(&shared.join("*/*/build/*")).is_empty());
+    assert!(glob(&shared.join("*/*/.fingerprint/*")).is_empty());
+
+    p.cargo("build -Zshared-deps")
+        .masquerade_as_nightly_cargo(&["shared-deps"])
+        .with_stderr_data(
+            str![[r#"
+[COMPILING] baz v1.0.0
+[COMPILING] bar v1.0.0
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
@@ -1,9 +1,10 @@
 //! Tests for --timings.
 
+use cargo_test_support::compare::assert_e2e;
 use cargo_test_support::prelude::*;
-use cargo_test_support::project;
 use cargo_test_support::registry::Package;
 use cargo_test_support::str;
+use cargo_test_support::{project, rustc_host};
 
 #[cargo_test]
 fn timings_works() {
@@ -54,3 +55,425 @@ fn timings_works() {
 
     p.cargo("doc --timings").run();
 }
+
+#[cargo_test]
+fn timings_json_file_and_trace() {
+    Package::new("dep", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            dep = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build --timings=json-file,trace")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--timings=json-file` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://github.com/rust-lang/cargo/issues/7405 for more information about the `--timings=json-file` flag.
+
+"#]])
+        .run();
+
+    p.cargo("build -Zunstable-options --timings=json-file,trace")
+        .masquerade_as_nightly_cargo(&["timings"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[DOWNLOADING] crates ...
+[DOWNLOADED] dep v0.1.0 (registry `dummy-registry`)
+[COMPILING] dep v0.1.0
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+      Timing data saved to [ROOT]/foo/target/cargo-timings/cargo-timing-[..].json
+      Timing trace saved to [ROOT]/foo/target/cargo-timings/cargo-timing-[..].trace.json
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    let data = p.read_file("target/cargo-timings/cargo-timing.json");
+    assert_e2e().eq(
+        data,
+        str![[r#"
+{
+  "concurrency": "{...}",
+  "cpu_usage": "{...}",
+  "critical_path": "{...}",
+  "critical_path_duration": "{...}",
+  "dirty_units": 2,
+  "duration": "{...}",
+  "error": null,
+  "fresh_units": 0,
+  "host": "{...}",
+  "jobs": "{...}",
+  "profile": "dev",
+  "roots": [
+    {
+      "package": "foo 0.1.0",
+      "targets": [
+        "lib"
+      ]
+    }
+  ],
+  "rustc": "{...}",
+  "start": "{...}",
+  "units": [
+    {
+      "duration": "{...}",
+      "features": [],
+      "i": 0,
+      "kind": "{...}",
+      "max_speedup": "{...}",
+      "mode": "build",
+      "name": "dep",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#dep@0.1.0",
+      "rmeta_time": "{...}",
+      "slack": "{...}",
+      "start": "{...}",
+      "target": "",
+      "unlocked_rmeta_units": [
+        1
+      ],
+      "unlocked_units": [],
+      "version": "0.1.0"
+    },
+    {
+      "duration": "{...}",
+      "features": [],
+      "i": 1,
+      "kind": "{...}",
+      "max_speedup": "{...}",
+      "mode": "build",
+      "name": "foo",
+      "package_id": "path+[ROOTURL]/foo#0.1.0",
+      "rmeta_time": "{...}",
+      "slack": "{...}",
+      "start": "{...}",
+      "target": "",
+      "unlocked_rmeta_units": [],
+      "unlocked_units": [],
+      "version": "0.1.0"
+    }
+  ],
+  "version": 1
+}
+"#]]
+        .is_json(),
+    );
+
+    let trace = p.read_file("target/cargo-timings/cargo-timing.trace.json");
+    let trace: serde_json::Value = serde_json::from_str(&trace).unwrap();
+    let units: Vec<_> = trace["traceEvents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["ph"] == "X")
+        .map(|e| e["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(units, ["dep v0.1.0", "foo v0.1.0"]);
+}
+
+fn timing_data(
+    start: &str,
+    duration: f64,
+    units: &[(&str, f64, f64)],
+    critical_path: &[usize],
+) -> String {
+    let units: Vec<_> = units
+        .iter()
+        .enumerate()
+        .map(|(i, (name, unit_start, unit_duration))| {
+            serde_json::json!({
+                "i": i,
+                "package_id": format!("registry+https://github.com/rust-lang/crates.io-index#{name}@1.0.0"),
+                "name": name,
+                "version": "1.0.0",
+                "target": "",
+                "mode": "build",
+                "kind": rustc_host(),
+                "features": [],
+                "start": unit_start,
+                "duration": unit_duration,
+                "rmeta_time": unit_duration / 2.0,
+                "unlocked_units": [],
+                "unlocked_rmeta_units": [],
+                "slack": if critical_path.contains(&i) { 0.0 } else { 1.0 },
+                "max_speedup": critical_path.contains(&i).then_some(unit_duration),
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "version": 1,
+        "start": start,
+        "profile": "dev",
+        "duration": duration,
+        "roots": [{"package": "foo 0.1.0", "targets": ["lib"]}],
+        "fresh_units": 0,
+        "dirty_units": units.len(),
+        "jobs": 4,
+        "rustc": "rustc 1.0.0",
+        "host": rustc_host(),
+        "error": null,
+        "units": units,
+        "concurrency": [{"t": 0.0, "active": 2, "waiting": 0, "inactive": 0}],
+        "cpu_usage": [],
+        "critical_path_duration": duration,
+        "critical_path": critical_path,
+    })
+    .to_string()
+}
+
+#[cargo_test]
+fn report_timings() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "target/cargo-timings/cargo-timing-20240101T000000Z.json",
+            &timing_data(
+                "2024-01-01T00:00:00Z",
+                10.0,
+                &[("a", 0.0, 4.0), ("b", 0.0, 2.0), ("c", 4.0, 6.0)],
+                &[0, 2],
+            ),
+        )
+        .file(
+            "target/cargo-timings/cargo-timing-20240102T000000Z.json",
+            &timing_data(
+                "2024-01-02T00:00:00Z",
+                12.0,
+                &[("a", 0.0, 4.0), ("c", 4.0, 8.0), ("d", 0.0, 1.0)],
+                &[0, 1],
+            ),
+        )
+        .file(
+            "target/cargo-timings/cargo-timing-20240102T000000Z.trace.json",
+            "{}",
+        )
+        .file(
+            "target/cargo-timings/cargo-timing-20231231T000000Z.json",
+            r#"{"version": 0}"#,
+        )
+        .build();
+
+    p.cargo("report timings")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `cargo report timings` command is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://github.com/rust-lang/cargo/issues/7405 for more information about the `cargo report timings` command.
+
+"#]])
+        .run();
+
+    p.cargo("report timings --list -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["timings"])
+        .with_stdout_data(str![[r#"
+ID                 PROFILE     UNITS   DURATION  STATUS
+20240101T000000Z   dev             3      10.0s  ok
+20240102T000000Z   dev             3      12.0s  ok
+
+"#]])
+        .with_stderr_data(str![[r#"
+[WARNING] skipping timing data: failed to parse timing data `[ROOT]/foo/target/cargo-timings/cargo-timing-20231231T000000Z.json`: [..]
+
+"#]])
+        .run();
+
+    p.cargo("report timings --top 2 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["timings"])
+        .with_stdout_data(str![[r#"
+Timing report 20240102T000000Z (foo 0.1.0)
+  Profile: dev
+  Units: 3 (0 fresh, 3 dirty)
+  Total time: 12.0s
+  Parallelism: 1.1 average, 2 max (jobs=4)
+  Critical path: 12.0s (2 units)
+
+Slowest units:
+   1.     8.0s  c v1.0.0 (codegen 4.0s, 50%)
+   2.     4.0s  a v1.0.0 (codegen 2.0s, 50%)
+
+Critical path:
+      4.0s  a v1.0.0 (build up to 4.0s faster)
+      8.0s  c v1.0.0 (build up to 8.0s faster)
+
+"#]])
+        .run();
+
+    p.cargo("report timings --compare 20240101T000000Z -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["timings"])
+        .with_stdout_data(str![[r#"
+Comparing timing report 20240101T000000Z with 20240102T000000Z
+  Total time: 10.0s -> 12.0s (+2.0s, +20.0%)
+  Average parallelism: 1.2 -> 1.1
+  Units: 3 -> 3
+
+Largest changes:
+     +2.0s  c v1.0.0: 6.0s -> 8.0s, codegen 3.0s -> 4.0s (+1.0s)
+
+Only in 20240102T000000Z:
+      1.0s  d v1.0.0
+
+Only in 20240101T000000Z:
+      2.0s  b v1.0.0
+
+"#]])
+        .run();
+
+    p.cargo("report timings --id 20230101T000000Z -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["timings"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[WARNING] skipping timing data: [..]
+[ERROR] timing data with id `20230101T000000Z` not found
+
+[HELP] use `cargo report timings --list` to list available runs
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn timings_critical_path() {
+    Package::new("dep", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            dep = "0.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -Zunstable-options --timings=json")
+        .masquerade_as_nightly_cargo(&["timings"])
+        .with_stdout_data(
+            str![[r#"
+[
+  {
+    "reason": "timing-info",
+    "...": "{...}"
+  },
+  {
+    "reason": "timing-info",
+    "...": "{...}"
+  },
+  {
+    "duration": "{...}",
+    "reason": "timing-critical-path",
+    "units": [
+      {
+        "critical": true,
+        "max_speedup": "{...}",
+        "mode": "build",
+        "package_id": "registry+https://github.com/rust-lang/crates.io-index#dep@0.1.0",
+        "slack": 0.0,
+        "target": "{...}"
+      },
+      {
+        "critical": true,
+        "max_speedup": "{...}",
+        "mode": "build",
+        "package_id": "path+[ROOTURL]/foo#0.1.0",
+        "slack": 0.0,
+        "target": "{...}"
+      }
+    ]
+  }
+]
+"#]]
+            .is_json()
+            .against_jsonlines(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn scheduling_critical_path() {
+    for name in ["a", "b", "c"] {
+        Package::new(name, "1.0.0").publish();
+    }
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            a = "1.0"
+            b = "1.0"
+            c = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "target/cargo-timings/cargo-timing.json",
+            &timing_data(
+                "2024-01-01T00:00:00Z",
+                10.0,
+                &[("a", 0.0, 5.0), ("b", 0.0, 1.0), ("c", 0.0, 10.0)],
+                &[2],
+            ),
+        )
+        .build();
+
+    p.cargo("fetch").run();
+
+    // With a single job, the slowest units of the previous build go first.
+    p.cargo("build -j1 -Zscheduling=critical-path")
+        .masquerade_as_nightly_cargo(&["scheduling"])
+        .with_stderr_data(str![[r#"
+[COMPILING] c v1.0.0
+[COMPILING] a v1.0.0
+[COMPILING] b v1.0.0
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn scheduling_unknown_policy() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build -Zscheduling=fastest")
+        .masquerade_as_nightly_cargo(&["scheduling"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] unknown scheduling policy `fastest`, expected one of `dependents` or `critical-path`
+
+"#]])
+        .run();
+
+    p.cargo("build -Zscheduling")
+        .masquerade_as_nightly_cargo(&["scheduling"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] flag -Zscheduling requires a value, expected one of `dependents` or `critical-path`
+
+"#]])
+        .run();
+}
@@ -0,0 +1,152 @@
+//! Tests for the `cargo tree --why-version` command.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, str};
+
+#[cargo_test]
+fn duplicate_versions() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "2.0.0").publish();
+    Package::new("bar", "1.0.0").dep("dep", "1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                dep = "2.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree --why-version dep -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+selected:
+  dep v1.0.0
+  dep v2.0.0
+
+requirements:
+  bar v1.0.0 requires `^1.0`, selected v1.0.0
+  foo v0.1.0 ([ROOT]/foo) requires `^2.0`, selected v2.0.0
+
+candidates:
+  v2.0.0: selected
+  v1.0.0: selected
+
+suggestion:
+  changing the requirement `^1.0` of bar v1.0.0 to allow v2.0.0 would allow dep to unify on v2.0.0
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn rejected_candidates() {
+    Package::new("dep", "1.0.0").feature("std", &[]).publish();
+    Package::new("libz-sys", "1.0.0").links("z").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2021"
+                rust-version = "1.60"
+
+                [dependencies]
+                dep = { version = "1.0", features = ["std"] }
+                libz-sys = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                incompatible-rust-versions = "fallback"
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile -Zmsrv-policy")
+        .masquerade_as_nightly_cargo(&["msrv-policy"])
+        .run();
+
+    Package::new("dep", "1.0.0")
+        .feature("std", &[])
+        .yanked(true)
+        .publish();
+    Package::new("dep", "1.1.0").feature("std", &[]).publish();
+    Package::new("dep", "1.2.0").publish();
+    Package::new("dep", "1.3.0")
+        .feature("std", &[])
+        .rust_version("1.9876.0")
+        .publish();
+    Package::new("dep", "1.4.0")
+        .feature("std", &[])
+        .links("z")
+        .publish();
+    Package::new("dep", "2.0.0").feature("std", &[]).publish();
+
+    p.cargo("tree --why-version dep -Zunstable-options -Zmsrv-policy")
+        .masquerade_as_nightly_cargo(&["unstable-options", "msrv-policy"])
+        .with_stdout_data(str![[r#"
+selected:
+  dep v1.0.0
+
+requirements:
+  foo v0.1.0 ([ROOT]/foo) requires `^1.0` with features `std`, selected v1.0.0
+
+candidates:
+  v2.0.0: does not match `^1.0` required by foo v0.1.0 ([ROOT]/foo)
+  v1.4.0: `links = "z"` is already used by libz-sys v1.0.0
+  v1.3.0: requires Rust 1.9876.0, newer than Rust 1.60
+  v1.2.0: missing feature `std` required by foo v0.1.0 ([ROOT]/foo)
+  v1.1.0: matches all requirements, but the lockfile keeps the selected version
+  v1.0.0: selected; yanked
+
+suggestion:
+  all requirements allow v1.1.0, `cargo update dep` would allow dep v1.1.0 to be selected
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("tree --why-version foo")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--why-version` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#tree-why-version for more information about the `--why-version` flag.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn no_matching_package() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("tree --why-version dep -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] package ID specification `dep` did not match any packages
+
+"#]])
+        .run();
+}
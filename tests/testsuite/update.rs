@@ -2709,3 +2709,133 @@ fn update_breaking_pre_release_upgrade() {
 "#]])
         .run();
 }
+
+#[cargo_test]
+fn message_format_json() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("old", "1.0.0").publish();
+    Package::new("transitive", "1.0.0").publish();
+    Package::new("direct", "1.0.0")
+        .dep("transitive", "1.0")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+                direct = "1.0"
+                old = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    Package::new("dep", "1.0.1").rust_version("1.0").publish();
+    Package::new("dep", "2.0.0").publish();
+    Package::new("transitive", "1.1.0").publish();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+
+            [dependencies]
+            dep = "1.0"
+            direct = "1.0"
+        "#,
+    );
+
+    p.cargo("update --message-format json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(
+            str![[r#"
+[
+  {
+    "direct": true,
+    "kind": "upgraded",
+    "latest": "2.0.0",
+    "latest_compatible": "1.0.1",
+    "name": "dep",
+    "package_id": "registry+https://github.com/rust-lang/crates.io-index#dep@1.0.1",
+    "previous_package_id": "registry+https://github.com/rust-lang/crates.io-index#dep@1.0.0",
+    "previous_version": "1.0.0",
+    "reason": "lockfile-change",
+    "rust_version": "1.0",
+    "rust_version_compatible": true,
+    "source": "registry+https://github.com/rust-lang/crates.io-index",
+    "version": "1.0.1"
+  },
+  {
+    "direct": true,
+    "kind": "unchanged",
+    "latest": "1.0.0",
+    "latest_compatible": "1.0.0",
+    "name": "direct",
+    "package_id": "registry+https://github.com/rust-lang/crates.io-index#direct@1.0.0",
+    "reason": "lockfile-change",
+    "source": "registry+https://github.com/rust-lang/crates.io-index",
+    "version": "1.0.0"
+  },
+  {
+    "kind": "removed",
+    "latest": "1.0.0",
+    "latest_compatible": "1.0.0",
+    "name": "old",
+    "package_id": "registry+https://github.com/rust-lang/crates.io-index#old@1.0.0",
+    "reason": "lockfile-change",
+    "source": "registry+https://github.com/rust-lang/crates.io-index",
+    "version": "1.0.0"
+  },
+  {
+    "direct": false,
+    "kind": "upgraded",
+    "latest": "1.1.0",
+    "latest_compatible": "1.1.0",
+    "name": "transitive",
+    "package_id": "registry+https://github.com/rust-lang/crates.io-index#transitive@1.1.0",
+    "previous_package_id": "registry+https://github.com/rust-lang/crates.io-index#transitive@1.0.0",
+    "previous_version": "1.0.0",
+    "reason": "lockfile-change",
+    "source": "registry+https://github.com/rust-lang/crates.io-index",
+    "version": "1.1.0"
+  }
+]
+"#]]
+            .is_json()
+            .against_jsonlines(),
+        )
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[UPDATING] dep v1.0.0 -> v1.0.1 (available: v2.0.0)
+[REMOVING] old v1.0.0
+[UPDATING] transitive v1.0.0 -> v1.1.0
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn message_format_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("update --message-format json")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--message-format` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#lockfile-message-format for more information about the `--message-format` flag.
+
+"#]])
+        .run();
+}
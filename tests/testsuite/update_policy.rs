@@ -0,0 +1,407 @@
+//! Tests for the `-Zupdate-policy` feature.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, str};
+
+#[cargo_test]
+fn patch_only() {
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.0").publish();
+    }
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                serde = "1.0"
+                tokio = "1.0"
+                tokio-util = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [update-policy.packages]
+                serde = { allow = "patch" }
+                "tokio*" = { allow = "none" }
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.1").publish();
+        Package::new(name, "1.1.0").publish();
+    }
+
+    p.cargo("update -Zupdate-policy")
+        .masquerade_as_nightly_cargo(&["update-policy"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[UPDATING] serde v1.0.0 -> v1.0.1 (available: v1.1.0)
+[NOTE] pass `--verbose` to see 2 unchanged dependencies behind latest
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn max_version() {
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.0").publish();
+    }
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                serde = "1.0"
+                tokio = "1.0"
+                tokio-util = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [update-policy.packages]
+                "*" = { max-version = "1.0" }
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.1").publish();
+        Package::new(name, "1.1.0").publish();
+    }
+
+    p.cargo("update -Zupdate-policy")
+        .masquerade_as_nightly_cargo(&["update-policy"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest compatible versions
+[UPDATING] serde v1.0.0 -> v1.0.1 (available: v1.1.0)
+[UPDATING] tokio v1.0.0 -> v1.0.1 (available: v1.1.0)
+[UPDATING] tokio-util v1.0.0 -> v1.0.1 (available: v1.1.0)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn policy_ignored_without_flag() {
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.0").publish();
+    }
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                serde = "1.0"
+                tokio = "1.0"
+                tokio-util = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [update-policy.packages]
+                "*" = { allow = "none" }
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.1").publish();
+        Package::new(name, "1.1.0").publish();
+    }
+
+    p.cargo("update")
+        .with_stderr_data(str![[r#"
+[WARNING] ignoring `update-policy` config without `-Zupdate-policy`
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest compatible versions
+[UPDATING] serde v1.0.0 -> v1.1.0
+[UPDATING] tokio v1.0.0 -> v1.1.0
+[UPDATING] tokio-util v1.0.0 -> v1.1.0
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn groups_update_together() {
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.0").publish();
+    }
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                serde = "1.0"
+                tokio = "1.0"
+                tokio-util = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [update-policy.groups]
+                tokio = ["tokio", "tokio-*"]
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.1").publish();
+        Package::new(name, "1.1.0").publish();
+    }
+
+    p.cargo("update tokio -Zupdate-policy")
+        .masquerade_as_nightly_cargo(&["update-policy"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[UPDATING] tokio v1.0.0 -> v1.1.0
+[UPDATING] tokio-util v1.0.0 -> v1.1.0
+[NOTE] pass `--verbose` to see 1 unchanged dependencies behind latest
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn dry_run_report() {
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.0").publish();
+    }
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                serde = "1.0"
+                tokio = "1.0"
+                tokio-util = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [update-policy.packages]
+                serde = { allow = "patch" }
+
+                [update-policy.groups]
+                tokio = ["tokio", "tokio-*"]
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.1").publish();
+        Package::new(name, "1.1.0").publish();
+    }
+
+    p.cargo("update --dry-run -Zupdate-policy")
+        .masquerade_as_nightly_cargo(&["update-policy"])
+        .with_stdout_data(str![[r#"
+group `tokio`:
+   Updating tokio v1.0.0 -> v1.1.0
+   Updating tokio-util v1.0.0 -> v1.1.0
+ungrouped:
+   Updating serde v1.0.0 -> v1.0.1 (v1.1.0 held back by update policy)
+
+"#]])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest compatible versions
+[UPDATING] serde v1.0.0 -> v1.0.1 (available: v1.1.0)
+[UPDATING] tokio v1.0.0 -> v1.1.0
+[UPDATING] tokio-util v1.0.0 -> v1.1.0
+[WARNING] not updating lockfile due to dry run
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn dry_run_report_json() {
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.0").publish();
+    }
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                serde = "1.0"
+                tokio = "1.0"
+                tokio-util = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [update-policy.packages]
+                serde = { allow = "patch" }
+
+                [update-policy.groups]
+                tokio = ["tokio", "tokio-*"]
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.1").publish();
+        Package::new(name, "1.1.0").publish();
+    }
+
+    p.cargo("update --dry-run --message-format json -Zupdate-policy -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["update-policy", "unstable-options"])
+        .with_stdout_data(
+            str![[r#"
+[
+  {
+    "direct": true,
+    "held_back": "1.1.0",
+    "kind": "upgraded",
+    "latest": "1.1.0",
+    "latest_compatible": "1.1.0",
+    "name": "serde",
+    "package_id": "registry+https://github.com/rust-lang/crates.io-index#serde@1.0.1",
+    "previous_package_id": "registry+https://github.com/rust-lang/crates.io-index#serde@1.0.0",
+    "previous_version": "1.0.0",
+    "reason": "lockfile-change",
+    "source": "registry+https://github.com/rust-lang/crates.io-index",
+    "version": "1.0.1"
+  },
+  {
+    "direct": true,
+    "group": "tokio",
+    "kind": "upgraded",
+    "latest": "1.1.0",
+    "latest_compatible": "1.1.0",
+    "name": "tokio",
+    "package_id": "registry+https://github.com/rust-lang/crates.io-index#tokio@1.1.0",
+    "previous_package_id": "registry+https://github.com/rust-lang/crates.io-index#tokio@1.0.0",
+    "previous_version": "1.0.0",
+    "reason": "lockfile-change",
+    "source": "registry+https://github.com/rust-lang/crates.io-index",
+    "version": "1.1.0"
+  },
+  {
+    "direct": true,
+    "group": "tokio",
+    "kind": "upgraded",
+    "latest": "1.1.0",
+    "latest_compatible": "1.1.0",
+    "name": "tokio-util",
+    "package_id": "registry+https://github.com/rust-lang/crates.io-index#tokio-util@1.1.0",
+    "previous_package_id": "registry+https://github.com/rust-lang/crates.io-index#tokio-util@1.0.0",
+    "previous_version": "1.0.0",
+    "reason": "lockfile-change",
+    "source": "registry+https://github.com/rust-lang/crates.io-index",
+    "version": "1.1.0"
+  }
+]
+"#]]
+            .is_json()
+            .against_jsonlines(),
+        )
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest compatible versions
+[UPDATING] serde v1.0.0 -> v1.0.1 (available: v1.1.0)
+[UPDATING] tokio v1.0.0 -> v1.1.0
+[UPDATING] tokio-util v1.0.0 -> v1.1.0
+[WARNING] not updating lockfile due to dry run
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn invalid_config() {
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.0").publish();
+    }
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                serde = "1.0"
+                tokio = "1.0"
+                tokio-util = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [update-policy.packages]
+                serde = { max-version = "one" }
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+    for name in ["serde", "tokio", "tokio-util"] {
+        Package::new(name, "1.0.1").publish();
+        Package::new(name, "1.1.0").publish();
+    }
+
+    p.cargo("update -Zupdate-policy")
+        .masquerade_as_nightly_cargo(&["update-policy"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse `update-policy.packages.serde.max-version`
+
+Caused by:
+  unexpected character 'o' while parsing major version number
+
+"#]])
+        .run();
+}
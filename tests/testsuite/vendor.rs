@@ -11,7 +11,7 @@ use cargo_test_support::git;
 use cargo_test_support::prelude::*;
 use cargo_test_support::registry::{self, Package, RegistryBuilder};
 use cargo_test_support::str;
-use cargo_test_support::{basic_lib_manifest, basic_manifest, paths, project, Project};
+use cargo_test_support::{basic_lib_manifest, basic_manifest, paths, project, rustc_host, Project};
 
 #[cargo_test]
 fn vendor_simple() {
@@ -1952,3 +1952,386 @@ fn vendor_crate_with_ws_inherit() {
 "#]])
         .run();
 }
+
+#[cargo_test]
+fn filter_by_target_and_dev_deps() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("haiku-dep", "1.0.0")
+        .file("src/lib.rs", "pub fn haiku() {}")
+        .publish();
+    Package::new("dev-dep", "1.0.0")
+        .file("src/lib.rs", "pub fn dev() {}")
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2021"
+
+                [dependencies]
+                dep = "1.0"
+
+                [target.i686-unknown-haiku.dependencies]
+                haiku-dep = "1.0"
+
+                [dev-dependencies]
+                dev-dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    let target = rustc_host();
+    p.cargo(&format!(
+        "vendor --respect-source-config --target {target} --no-dev-deps -Zunstable-options"
+    ))
+    .masquerade_as_nightly_cargo(&["unstable-options"])
+    .with_stderr_data(
+        str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest compatible versions
+[DOWNLOADING] crates ...
+[DOWNLOADED] dep v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] haiku-dep v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] dev-dep v1.0.0 (registry `dummy-registry`)
+   Vendoring dep v1.0.0 ([ROOT]/home/.cargo/registry/src/-[HASH]/dep-1.0.0) to vendor/dep
+[STUBBING] dev-dep v1.0.0 to vendor/dev-dep, not needed to build
+[STUBBING] haiku-dep v1.0.0 to vendor/haiku-dep, not needed to build
+To use vendored sources, add this to your .cargo/config.toml for this project:
+
+
+"#]]
+        .unordered(),
+    )
+    .run();
+
+    assert!(p.root().join("vendor/dep/src/lib.rs").exists());
+    assert_eq!(p.read_file("vendor/haiku-dep/src/lib.rs"), "");
+    assert_eq!(p.read_file("vendor/dev-dep/src/lib.rs"), "");
+    assert!(p
+        .read_file("vendor/haiku-dep/Cargo.toml")
+        .contains("name = \"haiku-dep\""));
+
+    // The stubs keep the lockfile valid.
+    add_crates_io_vendor_config(&p);
+    p.cargo("check --locked")
+        .with_stderr_data(str![[r#"
+[CHECKING] dep v1.0.0
+[CHECKING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn only_git_deps() {
+    Package::new("dep", "0.1.0").publish();
+    let git_project = git::new("gitdep", |project| {
+        project
+            .file("Cargo.toml", &basic_lib_manifest("gitdep"))
+            .file("src/lib.rs", "")
+    });
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2021"
+
+                [dependencies]
+                dep = "0.1"
+                gitdep = {{git='{}'}}
+                "#,
+                git_project.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("vendor --respect-source-config --only-git-deps -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+[source."git+[ROOTURL]/gitdep"]
+git = "[ROOTURL]/gitdep"
+replace-with = "vendored-sources"
+
+[source.vendored-sources]
+directory = "vendor"
+
+"#]])
+        .run();
+
+    assert!(p.root().join("vendor/gitdep").exists());
+    assert!(!p.root().join("vendor/dep").exists());
+}
+
+#[cargo_test]
+fn disallow_duplicates() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bitflags = "0.8.0"
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.1.0"
+
+                [target.i686-unknown-haiku.dependencies]
+                bitflags = "0.7.0"
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    Package::new("bitflags", "0.7.0").publish();
+    Package::new("bitflags", "0.8.0").publish();
+
+    p.cargo("vendor --respect-source-config --disallow-duplicates -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+...
+[ERROR] failed to sync
+
+Caused by:
+  found several versions of packages to vendor, which `--disallow-duplicates` forbids:
+    bitflags: v0.7.0, v0.8.0
+
+"#]])
+        .run();
+
+    // Versions only vendored as stubs aren't duplicates.
+    let target = rustc_host();
+    p.cargo(&format!(
+        "vendor --respect-source-config --disallow-duplicates --target {target} -Zunstable-options"
+    ))
+    .masquerade_as_nightly_cargo(&["unstable-options"])
+    .run();
+    assert_eq!(p.read_file("vendor/bitflags-0.7.0/src/lib.rs"), "");
+}
+
+#[cargo_test]
+fn filter_flags_are_unstable() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("vendor --respect-source-config --only-git-deps")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--only-git-deps` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#vendor-filtering for more information about the `--only-git-deps` flag.
+
+"#]])
+        .run();
+}
+
+/// A project depending on `a` and `b`, vendored with `-Zvendor-manifest`.
+fn vendor_manifest_project() -> Project {
+    Package::new("a", "1.0.0")
+        .file("src/lib.rs", "pub fn a() -> u32 { 1 }\n")
+        .publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2021"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("vendor --respect-source-config -Zvendor-manifest")
+        .masquerade_as_nightly_cargo(&["vendor-manifest"])
+        .run();
+    p
+}
+
+#[cargo_test]
+fn vendor_manifest() {
+    let p = vendor_manifest_project();
+
+    assert_e2e().eq(
+        p.read_file("vendor/.vendor-manifest.toml"),
+        str![[r#"
+# This file is automatically @generated by Cargo.
+# It records the provenance of the vendored packages.
+[[package]]
+name = "a"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+directory = "a"
+checksum = "[..]"
+files-checksum = "[..]"
+
+[[package]]
+name = "b"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+directory = "b"
+checksum = "[..]"
+files-checksum = "[..]"
+
+"#]],
+    );
+}
+
+#[cargo_test]
+fn vendor_manifest_incremental() {
+    let p = vendor_manifest_project();
+    // Unchanged packages aren't downloaded again.
+    let registry = paths::home().join(".cargo/registry");
+    fs::remove_dir_all(registry.join("cache")).unwrap();
+    fs::remove_dir_all(registry.join("src")).unwrap();
+
+    p.cargo("vendor --respect-source-config -Zvendor-manifest --incremental")
+        .masquerade_as_nightly_cargo(&["vendor-manifest"])
+        .with_stderr_data(str![[r#"
+To use vendored sources, add this to your .cargo/config.toml for this project:
+
+
+"#]])
+        .run();
+
+    Package::new("b", "1.0.1").publish();
+    p.cargo("update b").run();
+    p.cargo("vendor --respect-source-config -Zvendor-manifest --incremental")
+        .masquerade_as_nightly_cargo(&["vendor-manifest"])
+        .with_stderr_data(str![[r#"
+[DOWNLOADING] crates ...
+[DOWNLOADED] b v1.0.1 (registry `dummy-registry`)
+   Vendoring b v1.0.1 ([ROOT]/home/.cargo/registry/src/-[HASH]/b-1.0.1) to [ROOT]/foo/vendor/b
+To use vendored sources, add this to your .cargo/config.toml for this project:
+
+
+"#]])
+        .run();
+    assert!(p
+        .read_file("vendor/.vendor-manifest.toml")
+        .contains("1.0.1"));
+}
+
+#[cargo_test]
+fn vendor_manifest_local_changes() {
+    let p = vendor_manifest_project();
+    p.change_file("vendor/a/src/lib.rs", "pub fn a() -> u32 { 2 }\n");
+    p.change_file("vendor/a/src/new.rs", "// new\n");
+
+    p.cargo("vendor --respect-source-config -Zvendor-manifest")
+        .masquerade_as_nightly_cargo(&["vendor-manifest"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to sync
+
+Caused by:
+  local changes to vendored packages would be lost:
+    a v1.0.0 (vendor/a)
+  keep them as patches with `--save-patches`, or delete the directories of the packages to discard them
+
+"#]])
+        .run();
+
+    p.cargo("vendor --respect-source-config -Zvendor-manifest --save-patches")
+        .masquerade_as_nightly_cargo(&["vendor-manifest"])
+        .with_stderr_data(
+            str![[r#"
+[SAVED] local changes to a v1.0.0 as [ROOT]/foo/vendor/.patches/a-1.0.0.patch
+   Vendoring a v1.0.0 ([ROOT]/home/.cargo/registry/src/-[HASH]/a-1.0.0) to [ROOT]/foo/vendor/a
+[PATCHING] a v1.0.0 with [ROOT]/foo/vendor/.patches/a-1.0.0.patch
+   Vendoring b v1.0.0 ([ROOT]/home/.cargo/registry/src/-[HASH]/b-1.0.0) to [ROOT]/foo/vendor/b
+To use vendored sources, add this to your .cargo/config.toml for this project:
+
+
+"#]]
+            .unordered(),
+        )
+        .run();
+    assert_e2e().eq(
+        p.read_file("vendor/.patches/a-1.0.0.patch"),
+        str![[r#"
+diff --git a/src/lib.rs b/src/lib.rs
+index [..]
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1 +1 @@
+-pub fn a() -> u32 { 1 }
++pub fn a() -> u32 { 2 }
+diff --git a/src/new.rs b/src/new.rs
+new file mode 100644
+index [..]
+--- /dev/null
++++ b/src/new.rs
+@@ -0,0 +1 @@
++// new
+
+"#]],
+    );
+
+    // The patch is applied again when re-vendoring.
+    p.cargo("vendor --respect-source-config -Zvendor-manifest")
+        .masquerade_as_nightly_cargo(&["vendor-manifest"])
+        .run();
+    assert_eq!(
+        p.read_file("vendor/a/src/lib.rs"),
+        "pub fn a() -> u32 { 2 }\n"
+    );
+    assert_eq!(p.read_file("vendor/a/src/new.rs"), "// new\n");
+
+    // The checksums of the patched files are the ones of the vendored files.
+    p.change_file("src/lib.rs", "pub fn foo() { assert_eq!(a::a(), 2); }");
+    add_crates_io_vendor_config(&p);
+    p.cargo("check").run();
+
+    // Patches of versions which aren't vendored anymore are reported.
+    Package::new("a", "1.0.1").publish();
+    p.change_file(".cargo/config.toml", "");
+    p.cargo("update a").run();
+    p.cargo("vendor --respect-source-config -Zvendor-manifest")
+        .masquerade_as_nightly_cargo(&["vendor-manifest"])
+        .with_stderr_data(str![[r#"
+...
+[WARNING] patch `[ROOT]/foo/vendor/.patches/a-1.0.0.patch` was not applied, as no vendored package matches it
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn vendor_manifest_flags_are_unstable() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("vendor --respect-source-config --incremental")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--incremental` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#vendor-manifest for more information about the `--incremental` flag.
+
+"#]])
+        .run();
+}